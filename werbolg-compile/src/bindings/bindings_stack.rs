@@ -40,6 +40,13 @@ impl<T> BindingsStack<T> {
         None
     }
 
+    /// Iterate over the idents bound in all the scopes, including the shadowed ones
+    pub fn idents(&self) -> impl Iterator<Item = &Ident> {
+        self.stack
+            .iter()
+            .flat_map(|bindings| bindings.iter().map(|(ident, _)| ident))
+    }
+
     #[allow(unused)]
     pub fn dump<W: core::fmt::Write>(&self, writer: &mut W) -> Result<(), core::fmt::Error> {
         writeln!(writer, "bindings-stack: {}", self.stack.len())?;
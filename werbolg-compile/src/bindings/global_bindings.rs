@@ -31,7 +31,6 @@ impl GlobalBindings {
         bindings.get(&ident)
     }
 
-    #[allow(unused)]
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = (AbsPath, &'a BindingType)> {
         self.0.flat_iterator(
             Namespace::root(),
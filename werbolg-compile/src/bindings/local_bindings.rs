@@ -1,40 +1,91 @@
 use super::bindings_stack::BindingsStack;
 use super::types::BindingType;
 use crate::defs::LocalStackSize;
-use crate::instructions::{LocalBindIndex, ParamBindIndex};
+use crate::instructions::{CaptureBindIndex, LocalBindIndex, ParamBindIndex};
 use alloc::{vec, vec::Vec};
-use werbolg_core::Ident;
+use core::cell::Cell;
+use werbolg_core::{Ident, Span};
 
 pub struct LocalBindings {
-    bindings: BindingsStack<BindingType>,
+    bindings: BindingsStack<(BindingType, Option<usize>)>,
+    declared: Vec<Declared>,
     local: Vec<u16>,
     max_local: u16,
 }
 
+/// A parameter or local named in the function, tracked to know whether it is used
+pub struct Declared {
+    pub ident: Ident,
+    pub span: Span,
+    pub param: bool,
+    /// Whether this local hides a parameter of the function
+    pub shadows_param: bool,
+    used: Cell<bool>,
+}
+
+impl Declared {
+    pub fn is_used(&self) -> bool {
+        self.used.get()
+    }
+}
+
 impl LocalBindings {
     pub fn new() -> Self {
         Self {
             bindings: BindingsStack::new(),
+            declared: Vec::new(),
             local: vec![0],
             max_local: 0,
         }
     }
 
-    pub fn add_param(&mut self, ident: Ident, n: u8) {
+    pub fn add_param(&mut self, ident: Ident, n: u8, span: Span) {
+        let declared = self.declare(ident.clone(), span, true);
+        self.bindings.add(
+            ident,
+            (BindingType::Param(ParamBindIndex(n)), Some(declared)),
+        )
+    }
+
+    pub fn add_captured(&mut self, ident: Ident, n: u8) {
+        // the use of the captured value is tracked by the function capturing it
+        self.bindings
+            .add(ident, (BindingType::Captured(CaptureBindIndex(n)), None))
+    }
+
+    pub fn add_local(&mut self, ident: Ident, span: Span) -> LocalBindIndex {
+        let local = self.add_anonymous_local();
+        self.add_local_alias(ident, local, span);
+        local
+    }
+
+    /// Bind an ident to an already reserved local slot
+    pub fn add_local_alias(&mut self, ident: Ident, local: LocalBindIndex, span: Span) {
+        let declared = self.declare(ident.clone(), span, false);
         self.bindings
-            .add(ident, BindingType::Param(ParamBindIndex(n)))
+            .add(ident, (BindingType::Local(local), Some(declared)));
+    }
+
+    fn declare(&mut self, ident: Ident, span: Span, param: bool) -> usize {
+        let shadows_param = !param && matches!(self.peek(&ident), Some(BindingType::Param(_)));
+        self.declared.push(Declared {
+            ident,
+            span,
+            param,
+            shadows_param,
+            used: Cell::new(false),
+        });
+        self.declared.len() - 1
     }
 
-    pub fn add_local(&mut self, ident: Ident) -> LocalBindIndex {
+    /// Reserve a local slot that is not reachable by name, for compiler temporaries
+    pub fn add_anonymous_local(&mut self) -> LocalBindIndex {
         match self.local.last_mut() {
             None => panic!("internal error: cannot add local without an empty binding stack"),
             Some(x) => {
                 let local = *x;
                 *x += 1;
-
-                let local = LocalBindIndex(local);
-                self.bindings.add(ident, BindingType::Local(local));
-                local
+                LocalBindIndex(local)
             }
         }
     }
@@ -57,7 +108,27 @@ impl LocalBindings {
         LocalStackSize(self.max_local as u16)
     }
 
+    /// Get the binding of an ident, which is then considered used
     pub fn get(&self, ident: &Ident) -> Option<&BindingType> {
-        self.bindings.get(ident)
+        let (binding, declared) = self.bindings.get(ident)?;
+        if let Some(declared) = declared {
+            self.declared[*declared].used.set(true);
+        }
+        Some(binding)
+    }
+
+    /// Get the binding of an ident, without marking it as used
+    pub fn peek(&self, ident: &Ident) -> Option<&BindingType> {
+        self.bindings.get(ident).map(|(binding, _)| binding)
+    }
+
+    /// Iterate over the idents that can be bound from the current scope
+    pub fn idents(&self) -> impl Iterator<Item = &Ident> {
+        self.bindings.idents()
+    }
+
+    /// All the parameters and locals named in the function, in order of declaration
+    pub fn declared(&self) -> &[Declared] {
+        &self.declared
     }
 }
@@ -1,11 +1,12 @@
-use crate::instructions::{LocalBindIndex, ParamBindIndex};
+use crate::instructions::{CaptureBindIndex, LocalBindIndex, ParamBindIndex};
 use werbolg_core::{FunId, GlobalId, NifId};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum BindingType {
     Global(GlobalId),
     Nif(NifId),
     Fun(FunId),
     Param(ParamBindIndex),
     Local(LocalBindIndex),
+    Captured(CaptureBindIndex),
 }
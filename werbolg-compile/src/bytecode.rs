@@ -0,0 +1,660 @@
+//! Binary bytecode format of a compilation unit
+//!
+//! The bytecode starts with a header, followed by the payload containing the
+//! compilation unit:
+//!
+//! * the magic `WBGC` (4 bytes)
+//! * the format version (u16, little endian)
+//! * the CRC-32 checksum of the payload (u32, little endian)
+//! * the length of the payload (u32, little endian)
+//!
+//! The payload contains, in order, the literals, the constructors, the functions symbols,
+//! the functions, the globals symbols, the module initialisers and the code. The integers
+//! of the payload are encoded as LEB128 (with zigzag for the signed ones), and the literals
+//! are encoded by the user provided [`LiteralCodec`].
+//!
+//! The bytecode doesn't contain the environment, so the ids of the NIFs and of
+//! the environment globals are only valid with the same environment used to compile it.
+//! It doesn't contain the source map either, so a loaded compilation unit has an empty
+//! source map.
+
+use super::code::InstructionDiff;
+use super::defs::*;
+use super::instructions::*;
+use super::sourcemap::SourceMap;
+use super::symbols::{IdVec, SymbolsTable, SymbolsTableData};
+use super::CompilationUnit;
+use alloc::{string::String, vec::Vec};
+use werbolg_core::id::IdF;
+use werbolg_core::{AbsPath, ConstrId, FunId, GlobalId, Ident, LitId, Namespace, NifId, Privacy};
+
+/// The magic at the start of the bytecode
+pub const BYTECODE_MAGIC: [u8; 4] = *b"WBGC";
+
+/// The version of the bytecode format written, and the only version accepted by the loader
+pub const BYTECODE_VERSION: u16 = 3;
+
+const HEADER_SIZE: usize = 14;
+
+/// Conversion of the literals to and from bytes
+pub struct LiteralCodec<L> {
+    /// Append the bytes of a literal
+    pub encode: fn(&L, &mut Vec<u8>),
+    /// Get back a literal from its bytes, or None if the bytes are not valid
+    pub decode: fn(&[u8]) -> Option<L>,
+}
+
+/// Error when loading a bytecode
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BytecodeError {
+    /// The data doesn't start with the bytecode magic
+    InvalidMagic,
+    /// The bytecode format version is not supported
+    UnsupportedVersion(u16),
+    /// The data is shorter than the header or the payload length
+    Truncated,
+    /// The data is longer than the payload length
+    TrailingData,
+    /// The checksum of the payload doesn't match the checksum in the header
+    ChecksumMismatch {
+        /// Checksum in the header
+        expected: u32,
+        /// Checksum of the payload
+        got: u32,
+    },
+    /// An element goes past the end of the payload
+    UnexpectedEnd(usize),
+    /// An integer is too big for its type
+    InvalidInteger(usize),
+    /// An unknown tag (e.g. instruction opcode) for an element
+    InvalidTag(usize, u8),
+    /// An ident is not valid utf8
+    InvalidIdent(usize),
+    /// The literal codec cannot decode the literal
+    InvalidLiteral(LitId),
+    /// A symbol is defined multiple times
+    DuplicateSymbol(AbsPath),
+    /// Some bytes left in the payload after the code
+    TrailingPayload(usize),
+}
+
+/// Write a compilation unit as bytecode
+pub fn write_bytecode<L>(unit: &CompilationUnit<L>, codec: &LiteralCodec<L>) -> Vec<u8> {
+    let mut w = Writer { out: Vec::new() };
+
+    w.len(unit.lits.iter().count());
+    let mut lit = Vec::new();
+    for (_, l) in unit.lits.iter() {
+        lit.clear();
+        (codec.encode)(l, &mut lit);
+        w.bytes(&lit);
+    }
+
+    w.symbols(&unit.constrs.table);
+    w.len(unit.constrs.vecdata.iter().count());
+    for (_, constr) in unit.constrs.vecdata.iter() {
+        w.constr(constr)
+    }
+
+    w.symbols(&unit.funs_tbl);
+    w.len(unit.funs.iter().count());
+    for (_, fundef) in unit.funs.iter() {
+        match &fundef.name {
+            None => w.u8(0),
+            Some(name) => {
+                w.u8(1);
+                w.ident(name)
+            }
+        }
+        w.u8(fundef.arity.0);
+        w.uint(fundef.stack_size.0 as u64);
+        w.id(fundef.code_pos);
+    }
+
+    w.symbols(&unit.globals_tbl);
+    w.len(unit.inits.len());
+    for init in unit.inits.iter() {
+        w.namespace(&init.namespace);
+        w.id(init.fun);
+        w.len(init.globals.len());
+        for global in init.globals.iter() {
+            w.id(*global)
+        }
+    }
+
+    w.len(unit.code.iter().count());
+    for (_, instruction) in unit.code.iter() {
+        w.instruction(instruction)
+    }
+
+    let payload = w.out;
+    let mut out = Vec::with_capacity(HEADER_SIZE + payload.len());
+    out.extend_from_slice(&BYTECODE_MAGIC);
+    out.extend_from_slice(&BYTECODE_VERSION.to_le_bytes());
+    out.extend_from_slice(&crc32(&payload).to_le_bytes());
+    out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    out.extend_from_slice(&payload);
+    out
+}
+
+/// Load a compilation unit from bytecode
+pub fn read_bytecode<L>(
+    data: &[u8],
+    codec: &LiteralCodec<L>,
+) -> Result<CompilationUnit<L>, BytecodeError> {
+    if data.len() < HEADER_SIZE {
+        let prefix = core::cmp::min(data.len(), BYTECODE_MAGIC.len());
+        return Err(if data[..prefix] == BYTECODE_MAGIC[..prefix] {
+            BytecodeError::Truncated
+        } else {
+            BytecodeError::InvalidMagic
+        });
+    }
+    if data[0..4] != BYTECODE_MAGIC {
+        return Err(BytecodeError::InvalidMagic);
+    }
+    let version = u16::from_le_bytes([data[4], data[5]]);
+    if version != BYTECODE_VERSION {
+        return Err(BytecodeError::UnsupportedVersion(version));
+    }
+    let expected = u32::from_le_bytes([data[6], data[7], data[8], data[9]]);
+    let len = u32::from_le_bytes([data[10], data[11], data[12], data[13]]) as usize;
+    let payload = &data[HEADER_SIZE..];
+    if payload.len() < len {
+        return Err(BytecodeError::Truncated);
+    } else if payload.len() > len {
+        return Err(BytecodeError::TrailingData);
+    }
+    let got = crc32(payload);
+    if got != expected {
+        return Err(BytecodeError::ChecksumMismatch { expected, got });
+    }
+
+    let mut r = Reader {
+        data: payload,
+        pos: 0,
+    };
+
+    let mut lits = IdVec::new();
+    for _ in 0..r.len()? {
+        let bytes = r.bytes()?;
+        let lit = (codec.decode)(bytes).ok_or(BytecodeError::InvalidLiteral(lits.next_id()))?;
+        lits.push(lit);
+    }
+
+    let table = r.symbols()?;
+    let mut vecdata = IdVec::new();
+    for _ in 0..r.len()? {
+        vecdata.push(r.constr()?);
+    }
+    let constrs = SymbolsTableData { table, vecdata };
+
+    let funs_tbl = r.symbols()?;
+    let mut funs = IdVec::new();
+    for _ in 0..r.len()? {
+        let name = match r.u8()? {
+            0 => None,
+            1 => Some(r.ident()?),
+            tag => return Err(BytecodeError::InvalidTag(r.pos - 1, tag)),
+        };
+        let arity = CallArity(r.u8()?);
+        let stack_size = LocalStackSize(r.uint_to()?);
+        let code_pos = r.id()?;
+        funs.push(FunDef {
+            name,
+            arity,
+            stack_size,
+            code_pos,
+        });
+    }
+
+    let globals_tbl = r.symbols()?;
+    let mut inits = Vec::new();
+    for _ in 0..r.len()? {
+        let namespace = r.namespace()?;
+        let fun = r.id()?;
+        let globals = (0..r.len()?).map(|_| r.id()).collect::<Result<_, _>>()?;
+        inits.push(ModuleInit {
+            namespace,
+            fun,
+            globals,
+        });
+    }
+
+    let mut code = IdVec::new();
+    for _ in 0..r.len()? {
+        code.push(r.instruction()?);
+    }
+
+    if r.pos != payload.len() {
+        return Err(BytecodeError::TrailingPayload(r.pos));
+    }
+
+    Ok(CompilationUnit {
+        lits,
+        constrs,
+        funs_tbl,
+        funs,
+        globals_tbl,
+        inits,
+        code,
+        source_map: SourceMap::new(),
+    })
+}
+
+/// CRC-32 (IEEE) of the data
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for b in data {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+struct Writer {
+    out: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, v: u8) {
+        self.out.push(v)
+    }
+
+    fn uint(&mut self, mut v: u64) {
+        loop {
+            let byte = (v & 0x7f) as u8;
+            v >>= 7;
+            if v == 0 {
+                self.out.push(byte);
+                break;
+            }
+            self.out.push(byte | 0x80);
+        }
+    }
+
+    fn int(&mut self, v: i64) {
+        self.uint(((v << 1) ^ (v >> 63)) as u64)
+    }
+
+    fn len(&mut self, len: usize) {
+        self.uint(len as u64)
+    }
+
+    fn id<ID: IdF>(&mut self, id: ID) {
+        self.uint(id.as_index() as u64)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.len(bytes.len());
+        self.out.extend_from_slice(bytes)
+    }
+
+    fn ident(&mut self, ident: &Ident) {
+        self.bytes(ident.0.as_bytes())
+    }
+
+    fn namespace(&mut self, namespace: &Namespace) {
+        let idents = namespace.iter().collect::<Vec<_>>();
+        self.len(idents.len());
+        for ident in idents {
+            self.ident(ident)
+        }
+    }
+
+    fn path(&mut self, path: &AbsPath) {
+        let (namespace, ident) = path.split();
+        self.namespace(&namespace);
+        self.ident(&ident)
+    }
+
+    /// Write the symbols sorted by path, so that the same table always give the same bytes
+    fn symbols<ID: IdF>(&mut self, table: &SymbolsTable<ID>) {
+        let mut symbols = table.iter().collect::<Vec<_>>();
+        symbols.sort_by(|(p1, _), (p2, _)| p1.cmp(p2));
+        self.len(symbols.len());
+        for (path, id) in symbols {
+            let (_, privacy) = table.get_with_privacy(&path).expect("symbol in table");
+            self.path(&path);
+            self.id(id);
+            self.u8(match privacy {
+                Privacy::Public => 0,
+                Privacy::Private => 1,
+            });
+        }
+    }
+
+    fn constr(&mut self, constr: &ConstrDef) {
+        match constr {
+            ConstrDef::Struct(structdef) => {
+                self.u8(0);
+                self.ident(&structdef.name);
+                self.len(structdef.fields.len());
+                for field in structdef.fields.iter() {
+                    self.ident(field)
+                }
+            }
+            ConstrDef::Enum(enumdef) => {
+                self.u8(1);
+                self.ident(&enumdef.name);
+                self.len(enumdef.variants.len());
+                for variant in enumdef.variants.iter() {
+                    self.ident(&variant.name);
+                    self.id(variant.constr);
+                }
+            }
+        }
+    }
+
+    fn instruction(&mut self, instruction: &Instruction) {
+        match instruction {
+            Instruction::PushLiteral(lit) => {
+                self.u8(0);
+                self.id(*lit)
+            }
+            Instruction::PushDummy => self.u8(1),
+            Instruction::FetchGlobal(global) => {
+                self.u8(2);
+                self.id(*global)
+            }
+            Instruction::FetchNif(nif) => {
+                self.u8(3);
+                self.id(*nif)
+            }
+            Instruction::FetchFun(fun) => {
+                self.u8(4);
+                self.id(*fun)
+            }
+            Instruction::FetchStackParam(index) => {
+                self.u8(5);
+                self.u8(index.0)
+            }
+            Instruction::FetchStackLocal(index) => {
+                self.u8(6);
+                self.uint(index.0 as u64)
+            }
+            Instruction::FetchCaptured(index) => {
+                self.u8(7);
+                self.u8(index.0)
+            }
+            Instruction::AccessField(constr, index) => {
+                self.u8(8);
+                self.id(*constr);
+                self.u8(index.0)
+            }
+            Instruction::AccessTuple(index) => {
+                self.u8(9);
+                self.u8(index.0)
+            }
+            Instruction::CheckTuple(arity) => {
+                self.u8(30);
+                self.u8(arity.0)
+            }
+            Instruction::Index => self.u8(10),
+            Instruction::IndexConst(index) => {
+                self.u8(11);
+                self.uint(*index as u64)
+            }
+            Instruction::MakeClosure(fun, arity) => {
+                self.u8(12);
+                self.id(*fun);
+                self.u8(arity.0)
+            }
+            Instruction::MakeStruct(constr, arity) => {
+                self.u8(13);
+                self.id(*constr);
+                self.u8(arity.0)
+            }
+            Instruction::MakeTuple(arity) => {
+                self.u8(14);
+                self.u8(arity.0)
+            }
+            Instruction::TestConstr(constr) => {
+                self.u8(15);
+                self.id(*constr)
+            }
+            Instruction::TestLiteral(lit) => {
+                self.u8(16);
+                self.id(*lit)
+            }
+            Instruction::LocalBind(index) => {
+                self.u8(17);
+                self.uint(index.0 as u64)
+            }
+            Instruction::StoreLocal(index) => {
+                self.u8(18);
+                self.uint(index.0 as u64)
+            }
+            Instruction::MakeRef => self.u8(19),
+            Instruction::RefGet => self.u8(20),
+            Instruction::RefSet => self.u8(21),
+            Instruction::StoreGlobal(global) => {
+                self.u8(22);
+                self.id(*global)
+            }
+            Instruction::IgnoreOne => self.u8(23),
+            Instruction::Call(tc, arity) => {
+                self.u8(24);
+                self.tail_call(*tc);
+                self.u8(arity.0)
+            }
+            Instruction::CallFun(fun, arity, tc) => {
+                self.u8(29);
+                self.id(*fun);
+                self.u8(arity.0);
+                self.tail_call(*tc)
+            }
+            Instruction::CallNif(nif, arity) => {
+                self.u8(25);
+                self.id(*nif);
+                self.u8(arity.0)
+            }
+            Instruction::Jump(diff) => {
+                self.u8(26);
+                self.int(diff.0 as i64)
+            }
+            Instruction::CondJump(diff) => {
+                self.u8(27);
+                self.int(diff.0 as i64)
+            }
+            Instruction::Ret => self.u8(28),
+        }
+    }
+
+    fn tail_call(&mut self, tc: TailCall) {
+        self.u8(match tc {
+            TailCall::No => 0,
+            TailCall::Yes => 1,
+        })
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn u8(&mut self) -> Result<u8, BytecodeError> {
+        let v = *self
+            .data
+            .get(self.pos)
+            .ok_or(BytecodeError::UnexpectedEnd(self.pos))?;
+        self.pos += 1;
+        Ok(v)
+    }
+
+    fn uint(&mut self) -> Result<u64, BytecodeError> {
+        let start = self.pos;
+        let mut v = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift >= 64 || (shift == 63 && byte > 1) {
+                return Err(BytecodeError::InvalidInteger(start));
+            }
+            v |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(v);
+            }
+            shift += 7;
+        }
+    }
+
+    /// An unsigned integer fitting in T
+    fn uint_to<T: TryFrom<u64>>(&mut self) -> Result<T, BytecodeError> {
+        let start = self.pos;
+        let v = self.uint()?;
+        T::try_from(v).map_err(|_| BytecodeError::InvalidInteger(start))
+    }
+
+    fn int_to<T: TryFrom<i64>>(&mut self) -> Result<T, BytecodeError> {
+        let start = self.pos;
+        let v = self.uint()?;
+        let v = ((v >> 1) as i64) ^ -((v & 1) as i64);
+        T::try_from(v).map_err(|_| BytecodeError::InvalidInteger(start))
+    }
+
+    fn len(&mut self) -> Result<usize, BytecodeError> {
+        let start = self.pos;
+        let len = self.uint_to::<usize>()?;
+        // every element takes at least one byte, so a bigger length is necessarily invalid
+        if len > self.data.len() - self.pos {
+            return Err(BytecodeError::UnexpectedEnd(start));
+        }
+        Ok(len)
+    }
+
+    fn id<ID: IdF>(&mut self) -> Result<ID, BytecodeError> {
+        let v = self.uint_to::<u32>()?;
+        Ok(ID::from_collection_len(v as usize))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], BytecodeError> {
+        let len = self.len()?;
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn ident(&mut self) -> Result<Ident, BytecodeError> {
+        let start = self.pos;
+        let bytes = self.bytes()?;
+        let s = core::str::from_utf8(bytes).map_err(|_| BytecodeError::InvalidIdent(start))?;
+        Ok(Ident(String::from(s)))
+    }
+
+    fn namespace(&mut self) -> Result<Namespace, BytecodeError> {
+        let mut namespace = Namespace::root();
+        for _ in 0..self.len()? {
+            namespace = namespace.append(self.ident()?);
+        }
+        Ok(namespace)
+    }
+
+    fn path(&mut self) -> Result<AbsPath, BytecodeError> {
+        let namespace = self.namespace()?;
+        let ident = self.ident()?;
+        Ok(AbsPath::new(&namespace, &ident))
+    }
+
+    fn symbols<ID: IdF>(&mut self) -> Result<SymbolsTable<ID>, BytecodeError> {
+        let mut table = SymbolsTable::new();
+        for _ in 0..self.len()? {
+            let path = self.path()?;
+            let id = self.id()?;
+            let privacy = match self.u8()? {
+                0 => Privacy::Public,
+                1 => Privacy::Private,
+                tag => return Err(BytecodeError::InvalidTag(self.pos - 1, tag)),
+            };
+            let (namespace, _) = path.split();
+            let inserted = table
+                .create_namespace(namespace)
+                .is_ok_and(|()| table.insert(&path, id, privacy).is_ok());
+            if !inserted {
+                return Err(BytecodeError::DuplicateSymbol(path));
+            }
+        }
+        Ok(table)
+    }
+
+    fn constr(&mut self) -> Result<ConstrDef, BytecodeError> {
+        match self.u8()? {
+            0 => {
+                let name = self.ident()?;
+                let fields = (0..self.len()?)
+                    .map(|_| self.ident())
+                    .collect::<Result<_, _>>()?;
+                Ok(ConstrDef::Struct(StructDef { name, fields }))
+            }
+            1 => {
+                let name = self.ident()?;
+                let mut variants = Vec::new();
+                for _ in 0..self.len()? {
+                    let name = self.ident()?;
+                    let constr = self.id()?;
+                    variants.push(Variant { name, constr })
+                }
+                Ok(ConstrDef::Enum(EnumDef { name, variants }))
+            }
+            tag => Err(BytecodeError::InvalidTag(self.pos - 1, tag)),
+        }
+    }
+
+    fn instruction(&mut self) -> Result<Instruction, BytecodeError> {
+        let instruction = match self.u8()? {
+            0 => Instruction::PushLiteral(self.id::<LitId>()?),
+            1 => Instruction::PushDummy,
+            2 => Instruction::FetchGlobal(self.id::<GlobalId>()?),
+            3 => Instruction::FetchNif(self.id::<NifId>()?),
+            4 => Instruction::FetchFun(self.id::<FunId>()?),
+            5 => Instruction::FetchStackParam(ParamBindIndex(self.u8()?)),
+            6 => Instruction::FetchStackLocal(LocalBindIndex(self.uint_to()?)),
+            7 => Instruction::FetchCaptured(CaptureBindIndex(self.u8()?)),
+            8 => Instruction::AccessField(self.id::<ConstrId>()?, StructFieldIndex(self.u8()?)),
+            9 => Instruction::AccessTuple(TupleFieldIndex(self.u8()?)),
+            10 => Instruction::Index,
+            11 => Instruction::IndexConst(self.uint_to()?),
+            12 => Instruction::MakeClosure(self.id::<FunId>()?, CallArity(self.u8()?)),
+            13 => Instruction::MakeStruct(self.id::<ConstrId>()?, CallArity(self.u8()?)),
+            14 => Instruction::MakeTuple(CallArity(self.u8()?)),
+            15 => Instruction::TestConstr(self.id::<ConstrId>()?),
+            16 => Instruction::TestLiteral(self.id::<LitId>()?),
+            17 => Instruction::LocalBind(LocalBindIndex(self.uint_to()?)),
+            18 => Instruction::StoreLocal(LocalBindIndex(self.uint_to()?)),
+            19 => Instruction::MakeRef,
+            20 => Instruction::RefGet,
+            21 => Instruction::RefSet,
+            22 => Instruction::StoreGlobal(self.id::<GlobalId>()?),
+            23 => Instruction::IgnoreOne,
+            24 => {
+                let tc = self.tail_call()?;
+                Instruction::Call(tc, CallArity(self.u8()?))
+            }
+            25 => Instruction::CallNif(self.id::<NifId>()?, CallArity(self.u8()?)),
+            26 => Instruction::Jump(InstructionDiff(self.int_to()?)),
+            27 => Instruction::CondJump(InstructionDiff(self.int_to()?)),
+            28 => Instruction::Ret,
+            29 => Instruction::CallFun(
+                self.id::<FunId>()?,
+                CallArity(self.u8()?),
+                self.tail_call()?,
+            ),
+            30 => Instruction::CheckTuple(CallArity(self.u8()?)),
+            tag => return Err(BytecodeError::InvalidTag(self.pos - 1, tag)),
+        };
+        Ok(instruction)
+    }
+
+    fn tail_call(&mut self) -> Result<TailCall, BytecodeError> {
+        match self.u8()? {
+            0 => Ok(TailCall::No),
+            1 => Ok(TailCall::Yes),
+            tag => Err(BytecodeError::InvalidTag(self.pos - 1, tag)),
+        }
+    }
+}
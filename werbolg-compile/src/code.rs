@@ -1,9 +1,13 @@
 use super::instructions::Instruction;
+use super::sourcemap::SourceMap;
 use super::symbols::IdVec;
+use alloc::vec::Vec;
 use werbolg_core::id::{IdArith, IdF};
+use werbolg_core::{Namespace, Span};
 
 /// Instruction Address
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InstructionAddress(u32);
 
 impl Default for InstructionAddress {
@@ -17,6 +21,11 @@ impl InstructionAddress {
     pub fn next(self) -> Self {
         InstructionAddress::add(self, InstructionDiff(1))
     }
+
+    /// Displace the instruction address by a diff, returning None if the result is not a valid address
+    pub fn checked_add(self, diff: InstructionDiff) -> Option<Self> {
+        self.0.checked_add_signed(diff.0).map(Self)
+    }
 }
 
 impl IdF for InstructionAddress {
@@ -40,11 +49,12 @@ impl IdArith for InstructionAddress {
     type IdDiff = InstructionDiff;
 
     fn add(left: Self, right: InstructionDiff) -> Self {
-        Self(left.0.checked_add(right.0).expect("ID valid add"))
+        Self(left.0.checked_add_signed(right.0).expect("ID valid add"))
     }
 
     fn diff(left: Self, right: Self) -> InstructionDiff {
-        InstructionDiff(left.0.checked_sub(right.0).expect("ID valid diff"))
+        let diff = (left.0 as i64) - (right.0 as i64);
+        InstructionDiff(diff.try_into().expect("ID valid diff"))
     }
 }
 
@@ -75,14 +85,20 @@ impl core::fmt::Display for InstructionAddress {
 pub struct Code {
     stmts: IdVec<InstructionAddress, Instruction>,
     temps: usize,
+    source_map: SourceMap,
+    /// Spans of the expressions being generated, innermost last
+    spans: Vec<Span>,
 }
 
 /// placeholder instruction reference
 pub struct CodeRef(InstructionAddress);
 
 /// A displacement type between instruction. i.e. the number of element between 2 different InstructionAddress
-#[derive(Debug, Copy, Clone)]
-pub struct InstructionDiff(u32);
+///
+/// The displacement is negative when the target is before the origin (e.g. jumping back to the start of a loop)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstructionDiff(pub(crate) i32);
 
 impl Code {
     /// Create a new empty Code builder
@@ -90,14 +106,42 @@ impl Code {
         Self {
             stmts: IdVec::new(),
             temps: 0,
+            source_map: SourceMap::new(),
+            spans: Vec::new(),
         }
     }
 
     /// Append a new instruction at the end of the current instructions
     pub fn push(&mut self, stmt: Instruction) {
+        self.map_position();
         self.stmts.push(stmt);
     }
 
+    /// Start generating the code of the expression at span
+    ///
+    /// The instructions are mapped to the span until the matching `span_leave`
+    pub fn span_enter(&mut self, span: Span) {
+        self.spans.push(span)
+    }
+
+    /// Stop generating the code of the current expression, going back to its parent span
+    pub fn span_leave(&mut self) {
+        self.spans.pop();
+    }
+
+    /// Set the namespace of the instructions generated from now on
+    pub fn set_namespace(&mut self, namespace: &Namespace) {
+        let position = self.position();
+        self.source_map.add_namespace(position, namespace.clone())
+    }
+
+    fn map_position(&mut self) {
+        if let Some(span) = self.spans.last() {
+            let position = self.position();
+            self.source_map.add_span(position, span.clone())
+        }
+    }
+
     /// Return the position of the next instruction
     pub fn position(&self) -> InstructionAddress {
         InstructionAddress(self.stmts.next_id().0)
@@ -108,6 +152,7 @@ impl Code {
     #[must_use]
     pub fn push_temp(&mut self) -> CodeRef {
         let r = self.position();
+        self.map_position();
         self.stmts.push(Instruction::IgnoreOne);
         self.temps += 1;
         CodeRef(r)
@@ -125,17 +170,17 @@ impl Code {
         self.temps -= 1;
     }
 
-    /// finalize the code into just the instructions vector
+    /// finalize the code into the instructions vector and its source map
     ///
     /// this function will panic if the code cannot be finalized, as there
     /// some unresolved instructions (temps > 0)
-    pub fn finalize(self) -> IdVec<InstructionAddress, Instruction> {
+    pub fn finalize(self) -> (IdVec<InstructionAddress, Instruction>, SourceMap) {
         if self.temps > 0 {
             panic!(
                 "internal error: temporary code is still in place : {} instances",
                 self.temps
             )
         }
-        self.stmts
+        (self.stmts, self.source_map)
     }
 }
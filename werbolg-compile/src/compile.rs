@@ -2,33 +2,78 @@ use super::bindings::{BindingType, GlobalBindings, LocalBindings};
 use super::code::*;
 use super::defs::*;
 use super::errors::*;
+use super::freevars::free_variables;
+use super::init::InitItemCode;
 use super::instructions::*;
+use super::matching::{decision_tree, Cell, Decision, MatchError, Occurrence, Test};
 use super::resolver::SymbolResolver;
+use super::suggest;
 use super::symbols::*;
+use super::warnings::{CompilationWarning, WarningKind};
 use super::CompilationParams;
 use alloc::{format, vec, vec::Vec};
+use hashbrown::HashMap;
 use werbolg_core as ir;
-use werbolg_core::{ConstrId, FunId, Ident, LitId, Namespace, Path, PathType, Span};
+use werbolg_core::{
+    AbsPath, ConstrId, FunId, GlobalId, Ident, LitId, Namespace, NifId, Path, PathType, Privacy,
+    Span,
+};
 
-pub(crate) struct CompilationSharedState {}
+pub(crate) struct CompilationSharedState {
+    /// Arity of the functions of all the modules, known before their code is generated,
+    /// or None when the function has more parameters than the limit
+    pub(crate) funs_arity: IdVec<FunId, Option<CallArity>>,
+    /// Arity of the NIFs of the environment, or None when it has not been specified
+    pub(crate) nifs_arity: IdVec<NifId, Option<CallArity>>,
+}
 pub(crate) struct CompilationLocalState {
     namespace: Namespace,
     bindings: LocalBindings,
+    /// Loops enclosing the expression being compiled, innermost last
+    loops: Vec<LoopState>,
+    /// Number of values pushed on the stack, waiting for the expression being compiled
+    /// (e.g. the previous arguments of a call)
+    temps: usize,
+    /// Whether the code is a module initialiser instead of a function, where `return` is not valid
+    in_initialiser: bool,
+}
+
+impl CompilationLocalState {
+    fn new(namespace: &Namespace) -> Self {
+        Self {
+            namespace: namespace.clone(),
+            bindings: LocalBindings::new(),
+            loops: Vec::new(),
+            temps: 0,
+            in_initialiser: false,
+        }
+    }
+}
+
+/// A loop being compiled, target of the `break` and `continue` in its body
+struct LoopState {
+    /// Address of the start of the loop, where `continue` jumps back to
+    start: InstructionAddress,
+    /// Number of values pushed on the stack when entering the loop
+    temps: usize,
+    /// Jumps to the end of the loop, resolved once the loop is generated
+    breaks: Vec<(CodeRef, InstructionAddress)>,
 }
 
 pub(crate) struct CodeBuilder<'a, L: Clone + Eq + core::hash::Hash> {
-    #[allow(unused)]
     pub(crate) shared: &'a CompilationSharedState,
     pub(crate) params: CompilationParams<L>,
     pub(crate) funs_tbl: SymbolsTable<FunId>,
+    pub(crate) globals_tbl: SymbolsTable<GlobalId>,
     pub(crate) funs_vec: IdVec<FunId, FunDef>,
     pub(crate) lambdas_vec: IdVecAfter<FunId, FunDef>,
     pub(crate) constrs: SymbolsTableData<ConstrId, ConstrDef>,
     pub(crate) lits: UniqueTableBuilder<LitId, L>,
     pub(crate) main_code: Code,
-    pub(crate) lambdas: Vec<(CodeRef, ir::FunImpl)>,
+    pub(crate) lambdas: Vec<(CodeRef, ir::FunImpl, Vec<Ident>)>,
     pub(crate) globals: GlobalBindings,
     pub(crate) resolver: Option<SymbolResolver>,
+    pub(crate) warnings: Vec<CompilationWarning>,
 }
 
 impl<'a, L: Clone + Eq + core::hash::Hash> CodeBuilder<'a, L> {
@@ -36,32 +81,51 @@ impl<'a, L: Clone + Eq + core::hash::Hash> CodeBuilder<'a, L> {
         shared: &'a CompilationSharedState,
         params: CompilationParams<L>,
         funs_tbl: SymbolsTable<FunId>,
+        globals_tbl: SymbolsTable<GlobalId>,
         lambdas_vec: IdVecAfter<FunId, FunDef>,
+        constrs: SymbolsTableData<ConstrId, ConstrDef>,
         globals: GlobalBindings,
     ) -> Self {
         Self {
             shared,
             params,
             funs_tbl,
+            globals_tbl,
             funs_vec: IdVec::new(),
             lambdas_vec,
             main_code: Code::new(),
             lambdas: Vec::new(),
-            constrs: SymbolsTableData::new(),
+            constrs,
             lits: UniqueTableBuilder::new(),
             globals,
             resolver: None,
+            warnings: Vec::new(),
         }
     }
 
-    fn lambda_setaside(&mut self, code_ref: CodeRef, fun_impl: ir::FunImpl) {
-        self.lambdas.push((code_ref, fun_impl));
+    fn lambda_setaside(&mut self, code_ref: CodeRef, fun_impl: ir::FunImpl, captures: Vec<Ident>) {
+        self.lambdas.push((code_ref, fun_impl, captures));
+    }
+
+    /// Drop the lambdas set aside by a function in error, which are not generated
+    ///
+    /// The code of the function is left as is, since the code cannot be finalized anymore
+    pub(crate) fn discard_lambdas(&mut self) {
+        self.lambdas.clear();
     }
 
     fn get_instruction_address(&self) -> InstructionAddress {
         self.main_code.position()
     }
 
+    pub(crate) fn warning(&mut self, namespace: &Namespace, span: Span, kind: WarningKind) {
+        self.warnings.push(CompilationWarning {
+            namespace: namespace.clone(),
+            span,
+            kind,
+        })
+    }
+
     pub fn set_module_resolver(&mut self, uses: &SymbolResolver) {
         self.resolver = Some(uses.clone());
     }
@@ -81,64 +145,213 @@ pub(crate) fn generate_func_code<'a, L: Clone + Eq + core::hash::Hash>(
     state: &mut CodeBuilder<'a, L>,
     namespace: &Namespace,
     fundef: Option<ir::FunDef>,
+    captures: &[Ident],
     funimpl: ir::FunImpl,
 ) -> Result<FunDef, CompilationError> {
     let name = fundef.map(|x| x.name.clone());
     let ir::FunImpl { vars, body } = funimpl;
 
-    let mut local = CompilationLocalState {
-        bindings: LocalBindings::new(),
-        namespace: namespace.clone(),
-    };
+    let mut local = CompilationLocalState::new(namespace);
     local.bindings.scope_enter();
 
+    // captured values are accessed through the closure value, and are shadowed by parameters
+    for (capture_i, ident) in captures.iter().enumerate() {
+        local.bindings.add_captured(ident.clone(), capture_i as u8);
+    }
+
     for (var_i, var) in vars.iter().enumerate() {
         let var_i = var_i.try_into().map_err(|_| {
             CompilationError::FunctionParamsMoreThanLimit(var.0.span.clone(), vars.len())
         })?;
-        local.bindings.add_param(var.0.clone().unspan(), var_i);
+        local
+            .bindings
+            .add_param(var.0.inner.clone(), var_i, var.0.span.clone());
     }
 
     let arity = vars.len().try_into().map(|n| CallArity(n)).unwrap();
 
     let code_pos = state.get_instruction_address();
-    let tc = generate_expression_code(state, &mut local, FunPos::Root, body.clone())?;
-    if !tc {
+    state.write_code().set_namespace(namespace);
+    // the return at the end of the function is mapped to the whole body
+    state.write_code().span_enter(body.span());
+    let tc = generate_expression_code(state, &mut local, FunPos::Root, body);
+    if let Ok(false) = tc {
         state.write_code().push(Instruction::Ret);
     }
+    state.write_code().span_leave();
+    tc?;
+    bindings_warnings(state, &local);
     let stack_size = local.bindings.scope_terminate();
 
+    generate_lambdas_code(state, namespace, &name)?;
+
+    Ok(FunDef {
+        name,
+        arity,
+        code_pos,
+        stack_size,
+    })
+}
+
+/// Top-level item of a module, evaluated by the module initialiser
+pub(crate) struct InitItem {
+    /// Span of the item
+    pub span: Span,
+    /// The global to store the value in, or None for a naked expression
+    pub global: Option<GlobalId>,
+    /// The expression to evaluate
+    pub expr: ir::Expr,
+}
+
+/// Generate the initialiser function of a module, evaluating all the items in order
+///
+/// The initialiser returns the value of the last item, and the code
+/// evaluating each item is returned along the function definition.
+/// An item in error doesn't stop the generation of the next items, so that
+/// the errors of all the items are returned
+pub(crate) fn generate_init_code<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    namespace: &Namespace,
+    items: Vec<InitItem>,
+) -> Result<(FunDef, Vec<InitItemCode>), Vec<CompilationError>> {
+    let mut local = CompilationLocalState::new(namespace);
+    local.in_initialiser = true;
+    local.bindings.scope_enter();
+
+    let code_pos = state.get_instruction_address();
+    state.write_code().set_namespace(namespace);
+    let nb_items = items.len();
+    let mut items_code = Vec::new();
+    let mut errors = Vec::new();
+    for (i, item) in items.into_iter().enumerate() {
+        let last = i + 1 == nb_items;
+        let start = state.get_instruction_address();
+        // the instructions storing the value are mapped to the whole item
+        state.write_code().span_enter(item.span.clone());
+        let r = generate_expression_code(state, &mut local, FunPos::NotRoot, item.expr);
+        if r.is_ok() {
+            match item.global {
+                Some(global) => state.write_code().push(Instruction::StoreGlobal(global)),
+                None if !last => state.write_code().push(Instruction::IgnoreOne),
+                None => {}
+            }
+        }
+        state.write_code().span_leave();
+        if let Err(e) = r {
+            // the error can happen anywhere in the item, e.g. inside a loop
+            local.loops.clear();
+            local.temps = 0;
+            errors.push(e);
+            continue;
+        }
+        items_code.push(InitItemCode {
+            span: item.span,
+            global: item.global,
+            code: start..state.get_instruction_address(),
+        });
+        if last && let Some(global) = item.global {
+            state.write_code().push(Instruction::FetchGlobal(global));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    state.write_code().push(Instruction::Ret);
+    bindings_warnings(state, &local);
+    let stack_size = local.bindings.scope_terminate();
+
+    generate_lambdas_code(state, namespace, &None).map_err(|e| vec![e])?;
+
+    let fundef = FunDef {
+        name: None,
+        arity: CallArity(0),
+        code_pos,
+        stack_size,
+    };
+    Ok((fundef, items_code))
+}
+
+/// Warn about the parameters and locals of the function that are never used, and the
+/// locals hiding a parameter
+///
+/// The bindings starting with `_` are explicitly unused, and never reported as such
+fn bindings_warnings<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    local: &CompilationLocalState,
+) {
+    for declared in local.bindings.declared() {
+        if declared.shadows_param {
+            let kind = WarningKind::ShadowedParam(declared.ident.clone());
+            state.warning(&local.namespace, declared.span.clone(), kind);
+        }
+        if !declared.is_used() && !declared.ident.0.starts_with('_') {
+            let kind = if declared.param {
+                WarningKind::UnusedParam(declared.ident.clone())
+            } else {
+                WarningKind::UnusedBinding(declared.ident.clone())
+            };
+            state.warning(&local.namespace, declared.span.clone(), kind);
+        }
+    }
+}
+
+/// Generate the code of the lambdas set aside while generating a function
+fn generate_lambdas_code<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    namespace: &Namespace,
+    name: &Option<Ident>,
+) -> Result<(), CompilationError> {
     // now compute the code for the lambdas. This is in a loop
     // since it can generate further lambdas
     while !state.lambdas.is_empty() {
         let mut lambdas = Vec::new();
         core::mem::swap(&mut state.lambdas, &mut lambdas);
 
-        for (code_ref, fun_impl) in lambdas {
-            let lirdef = generate_func_code(state, namespace, None, fun_impl).map_err(
+        for (code_ref, fun_impl, captures) in lambdas {
+            let lirdef = generate_func_code(state, namespace, None, &captures, fun_impl).map_err(
                 |e: CompilationError| e.context(format!("function lambda code {:?}", name)),
             )?;
             let lambda_funid = state.lambdas_vec.push(lirdef);
 
-            state
-                .write_code()
-                .resolve_temp(code_ref, Instruction::FetchFun(lambda_funid));
+            // a lambda without any captured values doesn't need a closure
+            let instruction = if captures.is_empty() {
+                Instruction::FetchFun(lambda_funid)
+            } else {
+                Instruction::MakeClosure(lambda_funid, CallArity(captures.len() as u8))
+            };
+            state.write_code().resolve_temp(code_ref, instruction);
         }
     }
-
-    Ok(FunDef {
-        name,
-        arity,
-        code_pos,
-        stack_size,
-    })
+    Ok(())
 }
 
+/// Generate the code of an expression, leaving its value on the stack
+///
+/// Return true if the code after the expression is dead, i.e. the expression always ends
+/// by leaving the function (return or tail call) or by jumping away (e.g. break)
 fn generate_expression_code<'a, L: Clone + Eq + core::hash::Hash>(
     state: &mut CodeBuilder<'a, L>,
     local: &mut CompilationLocalState,
     funpos: FunPos,
     expr: ir::Expr,
+) -> Result<bool, CompilationError> {
+    // a let doesn't generate any instruction by itself, only its body, binder and
+    // the expression following it, which all have their own span
+    if let ir::Expr::Let(..) = expr {
+        return generate_expression_unmapped(state, local, funpos, expr);
+    }
+    state.write_code().span_enter(expr.span());
+    let r = generate_expression_unmapped(state, local, funpos, expr);
+    state.write_code().span_leave();
+    r
+}
+
+/// Generate the code of an expression, without mapping the instructions to its span
+fn generate_expression_unmapped<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    local: &mut CompilationLocalState,
+    funpos: FunPos,
+    expr: ir::Expr,
 ) -> Result<bool, CompilationError> {
     match expr {
         ir::Expr::Literal(span, lit) => {
@@ -147,110 +360,87 @@ fn generate_expression_code<'a, L: Clone + Eq + core::hash::Hash>(
             Ok(false)
         }
         ir::Expr::Path(span, path) => {
-            let x = fetch_ident(state, local, span, path.clone())?;
-            match x {
-                BindingType::Global(idx) => {
-                    state.write_code().push(Instruction::FetchGlobal(idx));
-                }
-                BindingType::Nif(idx) => {
-                    state.write_code().push(Instruction::FetchNif(idx));
-                }
-                BindingType::Fun(idx) => {
-                    state.write_code().push(Instruction::FetchFun(idx));
-                }
-                BindingType::Local(idx) => {
-                    state.write_code().push(Instruction::FetchStackLocal(idx));
-                }
-                BindingType::Param(idx) => {
-                    state.write_code().push(Instruction::FetchStackParam(idx));
+            match fetch_ident(state, local, span.clone(), path.clone())? {
+                Resolution::Binding(x) => state.write_code().push(fetch_binding(x)),
+                Resolution::Constructor(constr_id, _) => {
+                    // a constructor used as a value is only valid without any fields
+                    let struct_def = constructor_structure(state, &span, &path, constr_id)?;
+                    if !struct_def.fields.is_empty() {
+                        return Err(CompilationError::StructureFieldsMismatch(
+                            span,
+                            path,
+                            struct_def.fields.len(),
+                            0,
+                        ));
+                    }
+                    state
+                        .write_code()
+                        .push(Instruction::MakeStruct(constr_id, CallArity(0)));
                 }
+                // private symbols are reported as error by fetch_ident
+                Resolution::Private(_) => unreachable!(),
             }
             Ok(false)
         }
+        ir::Expr::Struct(span, path, fields) => {
+            let (constr_id, struct_def) = fetch_structure(state, local, &path.span, &path.inner)?;
+            let fields = structure_fields_exprs(&span, &path.inner, &struct_def, fields)?;
+            let nb_fields = fields
+                .len()
+                .try_into()
+                .map_err(|sz| CompilationError::CallTooManyArguments(span, sz))?;
+            let dead = generate_values_code(state, local, fields)?;
+            state
+                .write_code()
+                .push(Instruction::MakeStruct(constr_id, nb_fields));
+            Ok(dead)
+        }
         ir::Expr::Sequence(span, l) => {
             let call_arity = l
                 .len()
                 .try_into()
                 .map_err(|sz| CompilationError::CallTooManyArguments(span.clone(), sz))?;
-            for e in l {
-                let _: bool = generate_expression_code(state, local, FunPos::NotRoot, e)?;
-            }
+            let dead = generate_values_code(state, local, l)?;
             match state.params.sequence_constructor {
                 None => return Err(CompilationError::SequenceNotSupported(span)),
                 Some(nifid) => {
                     state
                         .write_code()
                         .push(Instruction::CallNif(nifid, call_arity));
-                    Ok(true)
+                    Ok(dead)
                 }
             }
         }
+        ir::Expr::Tuple(span, l) => {
+            let arity = l
+                .len()
+                .try_into()
+                .map_err(|sz| CompilationError::CallTooManyArguments(span, sz))?;
+            let dead = generate_values_code(state, local, l)?;
+            state.write_code().push(Instruction::MakeTuple(arity));
+            Ok(dead)
+        }
         ir::Expr::Let(binder, body, in_expr) => {
+            if let ir::Binder::Ignore = binder
+                && let ir::Expr::Literal(span, _) = body.as_ref()
+            {
+                state.warning(&local.namespace, span.clone(), WarningKind::IgnoredLiteral);
+            }
+            check_tuple_binder(&binder, &body)?;
             let x = body.clone();
-            let _: bool = generate_expression_code(state, local, FunPos::NotRoot, *body)
+            let bind_span = body.span();
+            let dead = generate_expression_code(state, local, FunPos::NotRoot, *body)
                 .map_err(|e| e.context(alloc::format!("{:?}", *x)))?;
-            match binder {
-                ir::Binder::Ident(ident) => {
-                    let bind = local.bindings.add_local(ident.clone());
-                    state.write_code().push(Instruction::LocalBind(bind));
-                }
-                ir::Binder::Ignore => {
-                    state.write_code().push(Instruction::IgnoreOne);
-                }
-                ir::Binder::Unit => {
-                    // TODO, not sure ignore one is the best to do here
-                    state.write_code().push(Instruction::IgnoreOne);
-                }
-                ir::Binder::Deconstruct(_name, _) => {
-                    todo!("")
-                }
-            }
+            state.write_code().span_enter(bind_span.clone());
+            let r = generate_binder_code(state, local, &bind_span, binder);
+            state.write_code().span_leave();
+            r?;
             let tc = generate_expression_code(state, local, funpos, *in_expr)?;
-            Ok(tc)
+            Ok(dead || tc)
         }
         ir::Expr::Field(expr, struct_ident, field_ident) => {
-            let resolved = resolve_symbol(&state, &local, &struct_ident.inner);
-            let result = resolved
-                .into_iter()
-                .filter_map(|res| match res {
-                    Resolution::Constructor(c, _) => Some(c),
-                    _ => None,
-                })
-                .collect::<Vec<_>>();
-
-            let constr_id = if result.is_empty() {
-                return Err(CompilationError::MissingSymbol(
-                    struct_ident.span,
-                    struct_ident.inner,
-                ));
-            } else if result.len() > 1 {
-                // duplicated structs
-                /*
-                return Err(CompilationError::DuplicateSymbol(
-                    struct_ident.span,
-                    struct_ident.inner,
-                ));
-                */
-                todo!()
-            } else {
-                &result[0]
-            };
-
-            let constr_def =
-                state
-                    .constrs
-                    .get_by_id(*constr_id)
-                    .ok_or(CompilationError::MissingConstructor(
-                        struct_ident.span.clone(),
-                        struct_ident.inner.clone(),
-                    ))?;
-
-            let ConstrDef::Struct(struct_def) = constr_def else {
-                return Err(CompilationError::ConstructorNotStructure(
-                    struct_ident.span,
-                    struct_ident.inner,
-                ));
-            };
+            let (constr_id, struct_def) =
+                fetch_structure(state, local, &struct_ident.span, &struct_ident.inner)?;
 
             let Some(index) = struct_def.find_field_index(&field_ident.inner) else {
                 return Err(CompilationError::StructureFieldNotExistant(
@@ -260,40 +450,123 @@ fn generate_expression_code<'a, L: Clone + Eq + core::hash::Hash>(
                 ));
             };
 
-            let _: bool = generate_expression_code(state, local, funpos, *expr)?;
+            let dead = generate_expression_code(state, local, FunPos::NotRoot, *expr)?;
             state
                 .write_code()
-                .push(Instruction::AccessField(*constr_id, index));
-            Ok(false)
+                .push(Instruction::AccessField(constr_id, index));
+            Ok(dead)
         }
-        ir::Expr::Lambda(_span, funimpl) => {
+        ir::Expr::Index(_span, expr, index) => match index {
+            ir::IndexExpr::Static(index) => {
+                let dead = generate_expression_code(state, local, FunPos::NotRoot, *expr)?;
+                state.write_code().push(Instruction::IndexConst(index));
+                Ok(dead)
+            }
+            ir::IndexExpr::Dynamic(index) => {
+                let dead = generate_values_code(state, local, [*expr, *index])?;
+                state.write_code().push(Instruction::Index);
+                Ok(dead)
+            }
+        },
+        ir::Expr::Lambda(span, funimpl) => {
+            // any free variable of the lambda that is bound locally in the current
+            // function (local, parameter or already captured) need to be captured
+            let captures = free_variables(&funimpl)
+                .into_iter()
+                .filter_map(|ident| local.bindings.get(&ident).map(|b| (ident, *b)))
+                .collect::<Vec<_>>();
+            if captures.len() > u8::MAX as usize {
+                return Err(CompilationError::ClosureCapturesMoreThanLimit(
+                    span,
+                    captures.len(),
+                ));
+            }
+
+            // push the captured values on the stack, which are consumed when creating the closure
+            for (_, binding) in captures.iter() {
+                state.write_code().push(fetch_binding(*binding));
+            }
             let lambda_fetch = state.write_code().push_temp();
 
-            state.lambda_setaside(lambda_fetch, *funimpl);
+            let captures = captures.into_iter().map(|(ident, _)| ident).collect();
+            state.lambda_setaside(lambda_fetch, *funimpl, captures);
 
             Ok(false)
         }
-        ir::Expr::Call(span, args) => {
+        ir::Expr::Call(span, mut args) => {
             assert!(args.len() > 0);
             let len = args.len() - 1;
-            for arg in args {
-                let _: bool = generate_expression_code(state, local, FunPos::NotRoot, arg)?;
-                ()
+
+            let tc = if funpos == FunPos::Root {
+                TailCall::Yes
+            } else {
+                TailCall::No
+            };
+
+            if let ir::Expr::Path(path_span, path) = &args[0] {
+                match fetch_ident(state, local, path_span.clone(), path.clone())? {
+                    // calling a constructor create the structure from the arguments
+                    Resolution::Constructor(constr_id, _) => {
+                        let struct_def = constructor_structure(state, path_span, path, constr_id)?;
+                        if struct_def.fields.len() != len {
+                            return Err(CompilationError::StructureFieldsMismatch(
+                                span,
+                                path.clone(),
+                                struct_def.fields.len(),
+                                len,
+                            ));
+                        }
+                        let nb_fields = len
+                            .try_into()
+                            .map_err(|sz| CompilationError::CallTooManyArguments(span, sz))?;
+                        let dead = generate_values_code(state, local, args.drain(1..))?;
+                        state
+                            .write_code()
+                            .push(Instruction::MakeStruct(constr_id, nb_fields));
+                        return Ok(dead);
+                    }
+                    // the functions and NIFs called directly may have a known arity
+                    Resolution::Binding(binding) => {
+                        let arity = match binding {
+                            BindingType::Fun(funid) => state.shared.funs_arity[funid],
+                            BindingType::Nif(nifid) => state.shared.nifs_arity[nifid],
+                            _ => None,
+                        };
+                        if let Some(arity) = arity
+                            && arity.0 as usize != len
+                        {
+                            return Err(CompilationError::CallArityMismatch(
+                                span,
+                                path.clone(),
+                                arity.0 as usize,
+                                len,
+                            ));
+                        }
+                        // the function is known statically, so it is called directly
+                        // without pushing the function value on the stack
+                        if let BindingType::Fun(funid) = binding
+                            && let Some(arity) = arity
+                        {
+                            let dead = generate_values_code(state, local, args.drain(1..))?;
+                            state
+                                .write_code()
+                                .push(Instruction::CallFun(funid, arity, tc));
+                            return Ok(dead || tc == TailCall::Yes);
+                        }
+                    }
+                    Resolution::Private(_) => {}
+                }
             }
+
+            let dead = generate_values_code(state, local, args)?;
             let call_arity = len
                 .try_into()
                 .map_err(|sz| CompilationError::CallTooManyArguments(span, sz))?;
-            if funpos == FunPos::Root {
-                state
-                    .write_code()
-                    .push(Instruction::Call(TailCall::Yes, call_arity));
-                Ok(true)
-            } else {
-                state
-                    .write_code()
-                    .push(Instruction::Call(TailCall::No, call_arity));
-                Ok(false)
-            }
+            state.write_code().push(Instruction::Call(tc, call_arity));
+            Ok(dead || tc == TailCall::Yes)
+        }
+        ir::Expr::Match { span, expr, arms } => {
+            generate_match_code(state, local, funpos, span, *expr, arms)
         }
         ir::Expr::If {
             span: _,
@@ -301,7 +574,7 @@ fn generate_expression_code<'a, L: Clone + Eq + core::hash::Hash>(
             then_expr,
             else_expr,
         } => {
-            let _: bool =
+            let dead_cond =
                 generate_expression_code(state, local, FunPos::NotRoot, (*cond).unspan())?;
 
             let cond_jump_ref = state.write_code().push_temp();
@@ -347,43 +620,895 @@ fn generate_expression_code<'a, L: Clone + Eq + core::hash::Hash>(
                     .resolve_temp(jump_else_ref, Instruction::Jump(end_pos - else_pos));
             }
 
+            Ok(funpos == FunPos::Root || dead_cond || (tc_then && tc_else))
+        }
+        ir::Expr::Loop(_, body) => {
+            let start = loop_enter(state, local);
+
+            local.bindings.scope_enter();
+            let _: bool = generate_expression_code(state, local, FunPos::NotRoot, *body)?;
+            local.bindings.scope_leave();
+
+            state.write_code().push(Instruction::IgnoreOne);
+            generate_jump_back(state, start);
+
+            // without any break, the loop never ends
+            let has_break = loop_leave(state, local);
+            Ok(!has_break)
+        }
+        ir::Expr::While {
+            span: _,
+            cond,
+            body,
+        } => {
+            let start = loop_enter(state, local);
+
+            local.bindings.scope_enter();
+            let _: bool =
+                generate_expression_code(state, local, FunPos::NotRoot, (*cond).unspan())?;
+
+            let cond_jump_ref = state.write_code().push_temp();
+            let cond_pos = state.get_instruction_address();
+
+            let _: bool =
+                generate_expression_code(state, local, FunPos::NotRoot, (*body).unspan())?;
+            local.bindings.scope_leave();
+
+            state.write_code().push(Instruction::IgnoreOne);
+            generate_jump_back(state, start);
+
+            // the loop ending because of the condition doesn't have any value
+            let exit_pos = state.get_instruction_address();
+            state
+                .write_code()
+                .resolve_temp(cond_jump_ref, Instruction::CondJump(exit_pos - cond_pos));
+            state.write_code().push(Instruction::PushDummy);
+
+            let _: bool = loop_leave(state, local);
+            Ok(false)
+        }
+        ir::Expr::Break(span, expr) => {
+            let Some(loop_temps) = local.loops.last().map(|l| l.temps) else {
+                return Err(CompilationError::BreakOutsideLoop(span));
+            };
+            // drop the values pushed inside the loop before generating the value of the loop
+            generate_drop_temps(state, local, loop_temps);
+
+            let temps = core::mem::replace(&mut local.temps, loop_temps);
+            match expr {
+                None => state.write_code().push(Instruction::PushDummy),
+                Some(expr) => {
+                    let _: bool = generate_expression_code(state, local, FunPos::NotRoot, *expr)?;
+                }
+            }
+            local.temps = temps;
+
+            let break_ref = state.write_code().push_temp();
+            let break_pos = state.get_instruction_address();
+            // the innermost loop cannot change while generating the value
+            local
+                .loops
+                .last_mut()
+                .unwrap()
+                .breaks
+                .push((break_ref, break_pos));
             Ok(true)
         }
+        ir::Expr::Continue(span) => {
+            let Some((start, loop_temps)) = local.loops.last().map(|l| (l.start, l.temps)) else {
+                return Err(CompilationError::ContinueOutsideLoop(span));
+            };
+            generate_drop_temps(state, local, loop_temps);
+            generate_jump_back(state, start);
+            Ok(true)
+        }
+        ir::Expr::Return(span, expr) => {
+            if local.in_initialiser {
+                return Err(CompilationError::ReturnOutsideFunction(span));
+            }
+            // the values pushed on the stack are dropped by the return itself
+            match expr {
+                None => state.write_code().push(Instruction::PushDummy),
+                Some(expr) => {
+                    let _: bool = generate_expression_code(state, local, FunPos::NotRoot, *expr)?;
+                }
+            }
+            state.write_code().push(Instruction::Ret);
+            Ok(true)
+        }
+        ir::Expr::Assign(span, ident, expr) => {
+            // only the locals can be updated, the parameters and captured values are
+            // not owned by the function
+            let local_bind = match local.bindings.peek(&ident.inner) {
+                Some(BindingType::Local(local_bind)) => *local_bind,
+                _ => return Err(CompilationError::AssignNotLocal(span, ident.inner)),
+            };
+            let dead = generate_expression_code(state, local, FunPos::NotRoot, *expr)?;
+            state.write_code().push(Instruction::StoreLocal(local_bind));
+            state.write_code().push(Instruction::PushDummy);
+            Ok(dead)
+        }
+        ir::Expr::RefNew(_, expr) => {
+            let dead = generate_expression_code(state, local, FunPos::NotRoot, *expr)?;
+            state.write_code().push(Instruction::MakeRef);
+            Ok(dead)
+        }
+        ir::Expr::RefGet(_, expr) => {
+            let dead = generate_expression_code(state, local, FunPos::NotRoot, *expr)?;
+            state.write_code().push(Instruction::RefGet);
+            Ok(dead)
+        }
+        ir::Expr::RefSet(_, cell, expr) => {
+            let dead = generate_values_code(state, local, [*cell, *expr])?;
+            state.write_code().push(Instruction::RefSet);
+            state.write_code().push(Instruction::PushDummy);
+            Ok(dead)
+        }
+    }
+}
+
+/// Generate the code for values pushed on the stack one after the other (e.g. the arguments of a call)
+///
+/// The values already pushed are accounted as temporaries while generating the next ones,
+/// so that a `break` or `continue` can drop them. Return true if any of the values ends
+/// with dead code
+fn generate_values_code<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    local: &mut CompilationLocalState,
+    exprs: impl IntoIterator<Item = ir::Expr>,
+) -> Result<bool, CompilationError> {
+    let temps = local.temps;
+    let mut dead = false;
+    for expr in exprs {
+        dead |= generate_expression_code(state, local, FunPos::NotRoot, expr)?;
+        local.temps += 1;
     }
+    local.temps = temps;
+    Ok(dead)
+}
+
+/// Start a new loop at the current instruction address, and return this address
+fn loop_enter<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    local: &mut CompilationLocalState,
+) -> InstructionAddress {
+    let start = state.get_instruction_address();
+    local.loops.push(LoopState {
+        start,
+        temps: local.temps,
+        breaks: Vec::new(),
+    });
+    start
 }
 
+/// Terminate the innermost loop at the current instruction address, resolving the jumps of its `break`
+///
+/// Return true if the loop has any `break`
+fn loop_leave<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    local: &mut CompilationLocalState,
+) -> bool {
+    let loop_state = local.loops.pop().expect("internal error: no loop to leave");
+    let end_pos = state.get_instruction_address();
+    let has_break = !loop_state.breaks.is_empty();
+    for (break_ref, break_pos) in loop_state.breaks {
+        state
+            .write_code()
+            .resolve_temp(break_ref, Instruction::Jump(end_pos - break_pos));
+    }
+    has_break
+}
+
+/// Generate a jump to an instruction already generated
+fn generate_jump_back<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    target: InstructionAddress,
+) {
+    // the displacement is relative to the instruction following the jump
+    let jump_pos = state.get_instruction_address().next();
+    state
+        .write_code()
+        .push(Instruction::Jump(target - jump_pos));
+}
+
+/// Drop the values pushed on the stack since the number of temporaries was `temps`
+fn generate_drop_temps<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    local: &CompilationLocalState,
+    temps: usize,
+) {
+    for _ in temps..local.temps {
+        state.write_code().push(Instruction::IgnoreOne);
+    }
+}
+
+/// Generate the code for a match expression
+///
+/// The matched value is kept in an anonymous local, then the decision tree selecting
+/// the arm is generated, followed by each arm expression
+fn generate_match_code<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    local: &mut CompilationLocalState,
+    funpos: FunPos,
+    span: Span,
+    expr: ir::Expr,
+    arms: Vec<ir::MatchArm>,
+) -> Result<bool, CompilationError> {
+    let mut patterns = Vec::with_capacity(arms.len());
+    for arm in arms.iter() {
+        patterns.push(pattern_cell(state, local, &arm.span, &arm.pattern)?);
+    }
+
+    let decision = {
+        let constrs = &state.constrs;
+        let siblings = |constr_id| constructor_siblings(constrs, constr_id);
+        decision_tree(patterns, &siblings).map_err(|e| match e {
+            MatchError::NonExhaustive => CompilationError::MatchNonExhaustive(span),
+            MatchError::Unreachable(arm) => {
+                CompilationError::MatchArmUnreachable(arms[arm].span.clone())
+            }
+            MatchError::Incompatible(arm) => {
+                CompilationError::MatchArmIncompatible(arms[arm].span.clone())
+            }
+        })?
+    };
+
+    let dead_expr = generate_expression_code(state, local, FunPos::NotRoot, expr)?;
+    let matched = local.bindings.add_anonymous_local();
+    state.write_code().push(Instruction::LocalBind(matched));
+
+    let mut slots = HashMap::new();
+    slots.insert(Occurrence::ROOT, matched);
+    let mut leaves = arms.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+    let mut arms_bindings = arms.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+    generate_decision_code(
+        state,
+        local,
+        &mut slots,
+        &mut leaves,
+        &mut arms_bindings,
+        decision,
+    );
+
+    let nb_arms = arms.len();
+    let mut end_jumps = Vec::new();
+    let mut dead_arms = true;
+    for (i, ((arm, leaves), bindings)) in
+        arms.into_iter().zip(leaves).zip(arms_bindings).enumerate()
+    {
+        let arm_pos = state.get_instruction_address();
+        for (leaf_ref, leaf_pos) in leaves {
+            state
+                .write_code()
+                .resolve_temp(leaf_ref, Instruction::Jump(arm_pos - leaf_pos));
+        }
+
+        local.bindings.scope_enter();
+        for (ident, occurrence) in bindings {
+            local
+                .bindings
+                .add_local_alias(ident, slots[&occurrence], arm.span.clone());
+        }
+        let tc = generate_expression_code(state, local, funpos, arm.expr)?;
+        local.bindings.scope_leave();
+        dead_arms &= tc;
+
+        if funpos == FunPos::Root {
+            if !tc {
+                state.write_code().push(Instruction::Ret);
+            }
+        } else if i + 1 < nb_arms {
+            let jump_ref = state.write_code().push_temp();
+            end_jumps.push((jump_ref, state.get_instruction_address()));
+        }
+    }
+
+    let end_pos = state.get_instruction_address();
+    for (jump_ref, jump_pos) in end_jumps {
+        state
+            .write_code()
+            .resolve_temp(jump_ref, Instruction::Jump(end_pos - jump_pos));
+    }
+
+    Ok(funpos == FunPos::Root || dead_expr || dead_arms)
+}
+
+/// Generate the code of the decision tree, which jumps to the selected arm
+///
+/// The jumps to the arms are left as temporary instructions for each arm in `leaves`, and
+/// the occurrences are extracted in local slots, to be bound in the arms by `arms_bindings`
+fn generate_decision_code<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    local: &mut CompilationLocalState,
+    slots: &mut HashMap<Occurrence, LocalBindIndex>,
+    leaves: &mut Vec<Vec<(CodeRef, InstructionAddress)>>,
+    arms_bindings: &mut Vec<Vec<(Ident, Occurrence)>>,
+    decision: Decision,
+) {
+    match decision {
+        Decision::Leaf(arm, bindings) => {
+            let leaf_ref = state.write_code().push_temp();
+            leaves[arm].push((leaf_ref, state.get_instruction_address()));
+            // each leaf of the same arm bind the same occurrences
+            arms_bindings[arm] = bindings;
+        }
+        Decision::Switch {
+            occurrence,
+            cases,
+            default,
+        } => {
+            let slot = slots[&occurrence];
+            let nb_cases = cases.len();
+            for (i, case) in cases.into_iter().enumerate() {
+                let constr_id = match case.test {
+                    Test::Constr(constr_id) => Some(constr_id),
+                    Test::Literal(_) | Test::Tuple(_) => None,
+                };
+
+                // when the cases are complete, the last case is always matching
+                let cond_jump = if default.is_none() && i + 1 == nb_cases {
+                    None
+                } else {
+                    let instruction = match case.test {
+                        Test::Constr(constr_id) => Instruction::TestConstr(constr_id),
+                        Test::Literal(lit_id) => Instruction::TestLiteral(lit_id),
+                        Test::Tuple(_) => unreachable!("tuple switch is always complete"),
+                    };
+                    state.write_code().push(Instruction::FetchStackLocal(slot));
+                    state.write_code().push(instruction);
+                    let cond_jump_ref = state.write_code().push_temp();
+                    Some((cond_jump_ref, state.get_instruction_address()))
+                };
+
+                if let Test::Tuple(nb_elements) = case.test {
+                    state.write_code().push(Instruction::FetchStackLocal(slot));
+                    state
+                        .write_code()
+                        .push(Instruction::CheckTuple(nb_elements));
+                    state.write_code().push(Instruction::IgnoreOne);
+                }
+
+                for (index, field_occurrence) in case.fields {
+                    let field_slot = *slots
+                        .entry(field_occurrence)
+                        .or_insert_with(|| local.bindings.add_anonymous_local());
+                    state.write_code().push(Instruction::FetchStackLocal(slot));
+                    let access = match constr_id {
+                        Some(constr_id) => Instruction::AccessField(constr_id, index),
+                        None => Instruction::AccessTuple(TupleFieldIndex(index.0)),
+                    };
+                    state.write_code().push(access);
+                    state.write_code().push(Instruction::LocalBind(field_slot));
+                }
+
+                generate_decision_code(state, local, slots, leaves, arms_bindings, case.decision);
+
+                if let Some((cond_jump_ref, cond_pos)) = cond_jump {
+                    let next_pos = state.get_instruction_address();
+                    state
+                        .write_code()
+                        .resolve_temp(cond_jump_ref, Instruction::CondJump(next_pos - cond_pos));
+                }
+            }
+            if let Some(default) = default {
+                generate_decision_code(state, local, slots, leaves, arms_bindings, *default);
+            }
+        }
+    }
+}
+
+/// Resolve the pattern of a match arm against the constructors and literals
+fn pattern_cell<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    local: &CompilationLocalState,
+    span: &Span,
+    pattern: &ir::Pattern,
+) -> Result<Cell, CompilationError> {
+    match pattern {
+        ir::Pattern::Literal(lit_span, lit) => {
+            let lit = (state.params.literal_mapper)(lit_span.clone(), lit.clone())?;
+            Ok(Cell::Literal(state.lits.add(lit)))
+        }
+        ir::Pattern::Binder(binder) => binder_cell(state, local, span, binder),
+    }
+}
+
+fn binder_cell<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &CodeBuilder<'a, L>,
+    local: &CompilationLocalState,
+    span: &Span,
+    binder: &ir::Binder,
+) -> Result<Cell, CompilationError> {
+    match binder {
+        ir::Binder::Unit | ir::Binder::Ignore => Ok(Cell::Wild),
+        ir::Binder::Ident(ident) => Ok(Cell::Var(ident.clone())),
+        ir::Binder::Deconstruct(path, fields) => {
+            let (constr_id, struct_def) = fetch_structure(state, local, span, path)?;
+            let mut cells = vec![Cell::Wild; struct_def.fields.len()];
+            for (index, binder) in
+                structure_fields_binders(span, path, &struct_def, fields.clone())?
+            {
+                cells[index.0 as usize] = binder_cell(state, local, span, &binder)?;
+            }
+            Ok(Cell::Constr(constr_id, cells))
+        }
+        ir::Binder::Tuple(binders) => {
+            let _: CallArity = binders
+                .len()
+                .try_into()
+                .map_err(|sz| CompilationError::CallTooManyArguments(span.clone(), sz))?;
+            let cells = binders
+                .iter()
+                .map(|binder| binder_cell(state, local, span, binder))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Cell::Tuple(cells))
+        }
+    }
+}
+
+/// Get all the constructors of the same type as a constructor, which are all the
+/// variants of its enumeration, or only itself for a structure
+fn constructor_siblings(
+    constrs: &SymbolsTableData<ConstrId, ConstrDef>,
+    constr_id: ConstrId,
+) -> Vec<ConstrId> {
+    for (_, constr_def) in constrs.vecdata.iter() {
+        if let ConstrDef::Enum(enum_def) = constr_def
+            && enum_def.variants.iter().any(|v| v.constr == constr_id)
+        {
+            return enum_def.variants.iter().map(|v| v.constr).collect();
+        }
+    }
+    vec![constr_id]
+}
+
+/// Generate the code to bind the value at the top of the stack to the binder
+///
+/// The span is the span of the bound expression, used for reporting errors
+fn generate_binder_code<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    local: &mut CompilationLocalState,
+    span: &Span,
+    binder: ir::Binder,
+) -> Result<(), CompilationError> {
+    match binder {
+        ir::Binder::Ident(ident) => {
+            let bind = local.bindings.add_local(ident.clone(), span.clone());
+            state.write_code().push(Instruction::LocalBind(bind));
+        }
+        ir::Binder::Ignore => {
+            state.write_code().push(Instruction::IgnoreOne);
+        }
+        ir::Binder::Unit => {
+            // TODO, not sure ignore one is the best to do here
+            state.write_code().push(Instruction::IgnoreOne);
+        }
+        ir::Binder::Deconstruct(path, fields) => {
+            let (constr_id, struct_def) = fetch_structure(state, local, span, &path)?;
+            let fields = structure_fields_binders(span, &path, &struct_def, fields)?
+                .into_iter()
+                .map(|(index, binder)| (Instruction::AccessField(constr_id, index), binder))
+                .collect();
+            generate_fields_binder_code(state, local, span, fields)?;
+        }
+        ir::Binder::Tuple(binders) => {
+            let arity = binders
+                .len()
+                .try_into()
+                .map_err(|sz| CompilationError::CallTooManyArguments(span.clone(), sz))?;
+            state.write_code().push(Instruction::CheckTuple(arity));
+            let fields = binders
+                .into_iter()
+                .enumerate()
+                .map(|(i, binder)| (Instruction::AccessTuple(TupleFieldIndex(i as u8)), binder))
+                .collect();
+            generate_fields_binder_code(state, local, span, fields)?;
+        }
+    }
+    Ok(())
+}
+
+/// Check that a tuple binder has the same number of elements as the tuple expression bound
+///
+/// The length of other tuple values is only known at execution, where it is checked
+/// before binding the elements
+fn check_tuple_binder(binder: &ir::Binder, expr: &ir::Expr) -> Result<(), CompilationError> {
+    if let ir::Binder::Tuple(binders) = binder
+        && let ir::Expr::Tuple(span, elements) = expr
+    {
+        if binders.len() != elements.len() {
+            return Err(CompilationError::TupleBinderMismatch(
+                span.clone(),
+                elements.len(),
+                binders.len(),
+            ));
+        }
+        for (binder, element) in binders.iter().zip(elements.iter()) {
+            check_tuple_binder(binder, element)?;
+        }
+    }
+    Ok(())
+}
+
+/// Generate the code to bind the fields of the value at the top of the stack,
+/// each field being extracted by its access instruction
+fn generate_fields_binder_code<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &mut CodeBuilder<'a, L>,
+    local: &mut CompilationLocalState,
+    span: &Span,
+    fields: Vec<(Instruction, ir::Binder)>,
+) -> Result<(), CompilationError> {
+    let fields = fields
+        .into_iter()
+        .filter(|(_, binder)| !matches!(binder, ir::Binder::Ignore))
+        .collect::<Vec<_>>();
+
+    match fields.len() {
+        0 => state.write_code().push(Instruction::IgnoreOne),
+        1 => {
+            // only one field to extract, so we can extract it directly from the stack
+            let (access, binder) = fields.into_iter().next().unwrap();
+            state.write_code().push(access);
+            generate_binder_code(state, local, span, binder)?;
+        }
+        _ => {
+            // keep the value in an anonymous local, and extract each field from it
+            let value = local.bindings.add_anonymous_local();
+            state.write_code().push(Instruction::LocalBind(value));
+            for (access, binder) in fields {
+                state.write_code().push(Instruction::FetchStackLocal(value));
+                state.write_code().push(access);
+                generate_binder_code(state, local, span, binder)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Match the fields binders against the structure definition, returning the binder
+/// associated with each field index, in order of the binders
+fn structure_fields_binders(
+    span: &Span,
+    path: &Path,
+    struct_def: &StructDef,
+    fields: ir::FieldsBinder,
+) -> Result<Vec<(StructFieldIndex, ir::Binder)>, CompilationError> {
+    match fields {
+        ir::FieldsBinder::Sequential(binders, ellipsis) => {
+            let nb_fields = struct_def.fields.len();
+            if binders.len() > nb_fields
+                || (ellipsis == ir::BindEllipsis::No && binders.len() != nb_fields)
+            {
+                return Err(CompilationError::StructureFieldsMismatch(
+                    span.clone(),
+                    path.clone(),
+                    nb_fields,
+                    binders.len(),
+                ));
+            }
+            Ok(binders
+                .into_iter()
+                .enumerate()
+                .map(|(i, binder)| (StructFieldIndex(i as u8), binder))
+                .collect())
+        }
+        ir::FieldsBinder::Named(binders, ellipsis) => structure_named_fields(
+            span,
+            path,
+            struct_def,
+            binders,
+            ellipsis == ir::BindEllipsis::Yes,
+        ),
+    }
+}
+
+/// Match the fields expressions against the structure definition, returning the
+/// expressions in the order of the structure fields
+fn structure_fields_exprs(
+    span: &Span,
+    path: &Path,
+    struct_def: &StructDef,
+    fields: ir::FieldsExpr,
+) -> Result<Vec<ir::Expr>, CompilationError> {
+    match fields {
+        ir::FieldsExpr::Sequential(exprs) => {
+            if exprs.len() != struct_def.fields.len() {
+                return Err(CompilationError::StructureFieldsMismatch(
+                    span.clone(),
+                    path.clone(),
+                    struct_def.fields.len(),
+                    exprs.len(),
+                ));
+            }
+            Ok(exprs)
+        }
+        ir::FieldsExpr::Named(exprs) => {
+            let exprs = exprs
+                .into_iter()
+                .map(|(ident, expr)| (ident.unspan(), expr))
+                .collect();
+            let mut fields = structure_named_fields(span, path, struct_def, exprs, false)?;
+            fields.sort_by_key(|(index, _)| index.0);
+            Ok(fields.into_iter().map(|(_, expr)| expr).collect())
+        }
+    }
+}
+
+/// Resolve the named fields against the structure definition, checking for unknown and
+/// duplicated fields, and for missing fields unless `partial` is set
+fn structure_named_fields<T>(
+    span: &Span,
+    path: &Path,
+    struct_def: &StructDef,
+    named: Vec<(Ident, T)>,
+    partial: bool,
+) -> Result<Vec<(StructFieldIndex, T)>, CompilationError> {
+    let mut out: Vec<(StructFieldIndex, T)> = Vec::new();
+    for (ident, t) in named {
+        let Some(index) = struct_def.find_field_index(&ident) else {
+            return Err(CompilationError::StructureFieldNotExistant(
+                span.clone(),
+                path.clone(),
+                ident,
+            ));
+        };
+        if out.iter().any(|(i, _)| i.0 == index.0) {
+            return Err(CompilationError::StructureFieldDuplicate(
+                span.clone(),
+                path.clone(),
+                ident,
+            ));
+        }
+        out.push((index, t));
+    }
+    if !partial && out.len() != struct_def.fields.len() {
+        let missing = struct_def
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !out.iter().any(|(index, _)| index.0 as usize == *i))
+            .map(|(_, ident)| ident.clone())
+            .collect();
+        return Err(CompilationError::StructureFieldsMissing(
+            span.clone(),
+            path.clone(),
+            missing,
+        ));
+    }
+    Ok(out)
+}
+
+/// Resolve a path to a constructor, and return its constructor id and definition
+fn fetch_constructor<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &CodeBuilder<'a, L>,
+    local: &CompilationLocalState,
+    span: &Span,
+    path: &Path,
+) -> Result<(ConstrId, ConstrDef), CompilationError> {
+    let resolved = resolve_symbol(state, local, path);
+    let result = resolved
+        .iter()
+        .filter_map(|res| match res {
+            Resolution::Constructor(c, remaining) if remaining.is_empty() => Some(*c),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let constr_id = if result.is_empty() {
+        return Err(missing_symbol(
+            state,
+            local,
+            resolved,
+            span.clone(),
+            path.clone(),
+        ));
+    } else if result.len() > 1 {
+        return Err(CompilationError::MultipleSymbol(span.clone(), path.clone()));
+    } else {
+        result[0]
+    };
+
+    let constr_def =
+        state
+            .constrs
+            .get_by_id(constr_id)
+            .ok_or(CompilationError::MissingConstructor(
+                span.clone(),
+                path.clone(),
+            ))?;
+    Ok((constr_id, constr_def.clone()))
+}
+
+/// Resolve a path to a structure constructor, and return its constructor id and definition
+fn fetch_structure<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &CodeBuilder<'a, L>,
+    local: &CompilationLocalState,
+    span: &Span,
+    path: &Path,
+) -> Result<(ConstrId, StructDef), CompilationError> {
+    let (constr_id, _) = fetch_constructor(state, local, span, path)?;
+    let struct_def = constructor_structure(state, span, path, constr_id)?;
+    Ok((constr_id, struct_def))
+}
+
+/// Get the structure definition of a constructor, which is either a structure or an enumeration variant
+fn constructor_structure<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &CodeBuilder<'a, L>,
+    span: &Span,
+    path: &Path,
+    constr_id: ConstrId,
+) -> Result<StructDef, CompilationError> {
+    match state.constrs.get_by_id(constr_id) {
+        Some(ConstrDef::Struct(struct_def)) => Ok(struct_def.clone()),
+        Some(ConstrDef::Enum(_)) => Err(CompilationError::ConstructorNotStructure(
+            span.clone(),
+            path.clone(),
+        )),
+        None => Err(CompilationError::MissingConstructor(
+            span.clone(),
+            path.clone(),
+        )),
+    }
+}
+
+fn fetch_binding(binding: BindingType) -> Instruction {
+    match binding {
+        BindingType::Global(idx) => Instruction::FetchGlobal(idx),
+        BindingType::Nif(idx) => Instruction::FetchNif(idx),
+        BindingType::Fun(idx) => Instruction::FetchFun(idx),
+        BindingType::Local(idx) => Instruction::FetchStackLocal(idx),
+        BindingType::Param(idx) => Instruction::FetchStackParam(idx),
+        BindingType::Captured(idx) => Instruction::FetchCaptured(idx),
+    }
+}
+
+/// Resolve a path to a single binding or constructor
+///
+/// Constructors found only as a prefix of the path (e.g. the enumeration `E` when
+/// resolving `E::Variant`) are not considered
 fn fetch_ident<'a, L: Clone + Eq + core::hash::Hash>(
     state: &CodeBuilder<'a, L>,
     local: &CompilationLocalState,
     span: Span,
     path: Path,
-) -> Result<BindingType, CompilationError> {
-    let resolved = resolve_symbol(state, local, &path);
+) -> Result<Resolution, CompilationError> {
+    let (mut resolved, hidden) = resolve_symbol(state, local, &path)
+        .into_iter()
+        .partition::<Vec<_>, _>(|res| match res {
+            Resolution::Constructor(_, remaining) => remaining.is_empty(),
+            Resolution::Binding(_) => true,
+            Resolution::Private(_) => false,
+        });
 
     if resolved.is_empty() {
-        std::println!("resolution empty");
-        Err(CompilationError::MissingSymbol(span, path))
+        Err(missing_symbol(state, local, hidden, span, path))
     } else if resolved.len() > 1 {
         Err(CompilationError::MultipleSymbol(span, path))
     } else {
-        let r = &resolved[0];
-        match r {
-            Resolution::Constructor(_, _) => {
-                // some error
-                todo!()
-            }
-            Resolution::Binding(r) => Ok(*r),
+        Ok(resolved.pop().unwrap())
+    }
+}
+
+/// Error for a path without any usable resolution, reporting a private symbol if one was found
+fn missing_symbol<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &CodeBuilder<'a, L>,
+    local: &CompilationLocalState,
+    resolved: Vec<Resolution>,
+    span: Span,
+    path: Path,
+) -> CompilationError {
+    match resolved.into_iter().find_map(|res| match res {
+        Resolution::Private(abspath) => Some(abspath),
+        _ => None,
+    }) {
+        Some(abspath) => CompilationError::PrivateSymbol(span, abspath),
+        None => {
+            let suggestions = symbol_suggestions(state, local, &path);
+            CompilationError::MissingSymbol(span, path, suggestions)
         }
     }
 }
 
+/// Maximum number of similar symbols suggested for a missing symbol
+const MAX_SIMILAR_SYMBOLS: usize = 3;
+
+/// Find the symbols that may have been meant by a path that cannot be resolved
+///
+/// The similar symbols are the usable paths replacing the last ident of the path by a close
+/// ident, and the symbols not imported are the symbols of the same name in any namespace,
+/// which would be usable from the current module if imported
+fn symbol_suggestions<'a, L: Clone + Eq + core::hash::Hash>(
+    state: &CodeBuilder<'a, L>,
+    local: &CompilationLocalState,
+    path: &Path,
+) -> SymbolSuggestions {
+    let mut prefix = path.idents();
+    let missing = prefix.pop().expect("path is not empty");
+    let is_local = prefix.is_empty() && path.path_type() == PathType::Relative;
+
+    // every symbol defined, whatever the namespace: functions, values, NIFs and constructors
+    let symbols = state
+        .globals
+        .iter()
+        .map(|(abspath, _)| abspath)
+        .chain(state.constrs.table.iter().map(|(abspath, _)| abspath))
+        .collect::<Vec<_>>();
+
+    let mut names = symbols
+        .iter()
+        .map(|abspath| abspath.split().1)
+        .collect::<Vec<_>>();
+    if let Some(resolver) = &state.resolver {
+        names.extend(
+            resolver
+                .uses
+                .iter()
+                .flat_map(|(_, u)| u.renames.iter().map(|(_, rename)| rename.clone())),
+        );
+    }
+    if is_local {
+        names.extend(local.bindings.idents().cloned());
+    }
+    names.sort();
+    names.dedup();
+
+    let usable = |resolved: Vec<Resolution>| {
+        resolved.iter().any(|res| match res {
+            Resolution::Binding(_) => true,
+            Resolution::Constructor(_, remaining) => remaining.is_empty(),
+            Resolution::Private(_) => false,
+        })
+    };
+
+    let similar = suggest::similar(&missing, names.iter())
+        .into_iter()
+        .filter_map(|ident| {
+            let mut idents = prefix.clone();
+            idents.push(ident.clone());
+            let candidate = Path::new_raw(path.path_type(), idents);
+            // the locals are bound in scope, and must not be marked as used by the resolution
+            let found = (is_local && local.bindings.peek(ident).is_some())
+                || usable(resolve_symbol(state, local, &candidate));
+            found.then_some(candidate)
+        })
+        .take(MAX_SIMILAR_SYMBOLS)
+        .collect();
+
+    let not_imported = symbols
+        .into_iter()
+        .filter(|abspath| {
+            let (namespace, ident) = abspath.split();
+            ident == missing
+                && usable(resolve_symbol_at(
+                    state,
+                    &local.namespace,
+                    Namespace::root(),
+                    &namespace.path_with_ident(&ident),
+                ))
+        })
+        .collect();
+
+    SymbolSuggestions {
+        similar,
+        not_imported,
+    }
+}
+
+#[derive(PartialEq, Eq)]
 pub enum Resolution {
     Constructor(ConstrId, Vec<Ident>),
     Binding(BindingType),
+    /// The symbol exists, but is private to a module not visible from the referencing namespace
+    Private(AbsPath),
 }
 
+/// Resolve a path starting from a namespace, returning all the symbols found along the path
+///
+/// `from` is the namespace where the path is referenced from, which is used to check that
+/// private symbols are only used by their own module (or its sub-modules)
 pub fn resolve_symbol_at<'a, L: Clone + Eq + core::hash::Hash>(
     state: &CodeBuilder<'a, L>,
+    from: &Namespace,
     namespace: Namespace,
     path: &Path,
 ) -> Vec<Resolution> {
@@ -392,36 +1517,40 @@ pub fn resolve_symbol_at<'a, L: Clone + Eq + core::hash::Hash>(
     let mut constr_table = Some(&state.constrs.table.0);
     let mut bind_table = Some(&state.globals.0);
 
-    // if namespace is not empty, we need to tweak those symbol table
-    if !namespace.is_root() {
-        loop {
-            let (ident, ns) = namespace.clone().drop_first();
-            if let Some(tbl) = constr_table {
-                constr_table = tbl.get_sub(&ident).ok();
-            } else {
-                panic!("missing module {:?}", namespace)
-            }
-            if let Some(tbl) = bind_table {
-                bind_table = tbl.get_sub(&ident).ok();
-            } else {
-                panic!("missing module {:?}", namespace)
-            }
+    // the module owning the symbols, which doesn't include the enumeration
+    // namespaces as the variants take the privacy of their enumeration
+    let mut module_ns = Namespace::root();
 
-            if ns.is_root() {
-                break;
+    // if namespace is not empty, we need to tweak those symbol table. the namespace
+    // doesn't necessarily exist in both tables (e.g. a namespace with only constructors)
+    for ident in namespace.iter() {
+        if let Some(tbl) = constr_table {
+            if tbl.current().get(ident).is_none() {
+                module_ns = module_ns.append(ident.clone());
             }
+        } else {
+            module_ns = module_ns.append(ident.clone());
         }
+        constr_table = constr_table.and_then(|tbl| tbl.get_sub(ident).ok());
+        bind_table = bind_table.and_then(|tbl| tbl.get_sub(ident).ok());
     }
 
     let mut idents = path.components();
     let mut current_ns = namespace;
 
     while let Some((ident, remaining)) = idents.next() {
+        let visible = |privacy: Privacy| privacy == Privacy::Public || from.is_within(&module_ns);
         let mut namespace_entered = false;
+        let mut is_constr = false;
         // check in the constructor symbols (struct / enum)
         if let Some(tbl) = constr_table {
-            if let Some(constr) = tbl.current().get(ident) {
-                out.push(Resolution::Constructor(constr, remaining.to_vec()))
+            if let Some((constr, privacy)) = tbl.current().get_with_privacy(ident) {
+                is_constr = true;
+                if visible(privacy) {
+                    out.push(Resolution::Constructor(constr, remaining.to_vec()))
+                } else if remaining.is_empty() {
+                    out.push(Resolution::Private(AbsPath::new(&current_ns, ident)))
+                }
             }
             constr_table = tbl.get_sub(ident).ok();
             if constr_table.is_some() {
@@ -430,9 +1559,26 @@ pub fn resolve_symbol_at<'a, L: Clone + Eq + core::hash::Hash>(
         }
         // check in the function symbols
         if let Some(tbl) = bind_table {
-            if let Some(bty) = tbl.current().get(ident) {
-                if remaining.is_empty() {
+            if let Some(bty) = tbl.current().get(ident)
+                && remaining.is_empty()
+            {
+                let path = AbsPath::new(&current_ns, ident);
+                let privacy = match bty {
+                    BindingType::Fun(_) => state
+                        .funs_tbl
+                        .get_with_privacy(&path)
+                        .map_or(Privacy::Public, |(_, privacy)| privacy),
+                    // globals from the environment are not in the table, and always public
+                    BindingType::Global(_) => state
+                        .globals_tbl
+                        .get_with_privacy(&path)
+                        .map_or(Privacy::Public, |(_, privacy)| privacy),
+                    _ => Privacy::Public,
+                };
+                if visible(privacy) {
                     out.push(Resolution::Binding(*bty))
+                } else {
+                    out.push(Resolution::Private(path))
                 }
             }
             bind_table = tbl.get_sub(ident).ok();
@@ -446,6 +1592,9 @@ pub fn resolve_symbol_at<'a, L: Clone + Eq + core::hash::Hash>(
             break;
         }
 
+        if !is_constr {
+            module_ns = module_ns.append(ident.clone())
+        }
         current_ns = current_ns.append(ident.clone())
     }
     out
@@ -460,7 +1609,7 @@ pub fn resolve_symbol<'a, L: Clone + Eq + core::hash::Hash>(
         PathType::Absolute => {
             // if the path is absolute, we only lookup through the defined symbols, so we never
             // look in the local bindings
-            resolve_symbol_at(state, Namespace::root(), path)
+            resolve_symbol_at(state, &local.namespace, Namespace::root(), path)
         }
         PathType::Relative => {
             if let Some(local_path) = path.get_local() {
@@ -468,10 +1617,34 @@ pub fn resolve_symbol<'a, L: Clone + Eq + core::hash::Hash>(
                     return vec![Resolution::Binding(*bound)];
                 }
             }
-            let mut result = resolve_symbol_at(state, local.namespace.clone(), path);
-            let mut root_result = resolve_symbol_at(state, Namespace::root(), path);
-            result.append(&mut root_result);
-            result
+            // lookup in the current namespace, then the imported namespaces, then the root namespace.
+            // the same symbol can be reached multiple times, which is not considered ambiguous
+            let mut result =
+                resolve_symbol_at(state, &local.namespace, local.namespace.clone(), path);
+            if let Some(resolver) = &state.resolver {
+                for (namespace, imported_path) in resolver.imported_paths(path) {
+                    result.extend(resolve_symbol_at(
+                        state,
+                        &local.namespace,
+                        namespace,
+                        &imported_path,
+                    ));
+                }
+            }
+            result.extend(resolve_symbol_at(
+                state,
+                &local.namespace,
+                Namespace::root(),
+                path,
+            ));
+
+            let mut unique: Vec<Resolution> = Vec::new();
+            for resolution in result {
+                if !unique.contains(&resolution) {
+                    unique.push(resolution)
+                }
+            }
+            unique
         }
     }
 }
@@ -1,16 +1,18 @@
 use super::code::InstructionAddress;
 use super::instructions::*;
 use alloc::vec::Vec;
-use werbolg_core::{ConstrId, Ident};
+use werbolg_core::{ConstrId, FunId, GlobalId, Ident, Namespace};
 
 /// Local stack size (in unit of values)
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LocalStackSize(pub u16);
 
 /// Function definition
 ///
 /// For anonymous function the name is None
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunDef {
     /// name of the function. anonymous function has no name
     pub name: Option<Ident>,
@@ -22,8 +24,24 @@ pub struct FunDef {
     pub code_pos: InstructionAddress,
 }
 
+/// Module initialiser
+///
+/// The initialiser is a function without parameters, evaluating in order the
+/// top-level expressions and values of a module, and storing each value in its global
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModuleInit {
+    /// Namespace of the module
+    pub namespace: Namespace,
+    /// The initialiser function
+    pub fun: FunId,
+    /// The globals defined by the module, in definition order
+    pub globals: Vec<GlobalId>,
+}
+
 /// Structure definition
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StructDef {
     /// name of this structure
     pub name: Ident,
@@ -43,6 +61,7 @@ impl StructDef {
 
 /// Enumeration definition
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnumDef {
     /// name of this enumeration
     pub name: Ident,
@@ -52,6 +71,7 @@ pub struct EnumDef {
 
 /// Constructor definition (enumeration or struct)
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConstrDef {
     /// Struct variant of a constructor
     Struct(StructDef),
@@ -61,6 +81,7 @@ pub enum ConstrDef {
 
 /// Enumeration Variant type
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Variant {
     /// Name of this variant
     pub name: Ident,
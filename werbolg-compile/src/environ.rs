@@ -1,6 +1,7 @@
+use super::instructions::CallArity;
 use super::symbols::IdVec;
 use crate::symbols::{NamespaceError, SymbolInsertError, SymbolsTable};
-use werbolg_core::{AbsPath, GlobalId, Namespace, NifId};
+use werbolg_core::{AbsPath, GlobalId, Namespace, NifId, Privacy};
 
 /// Environment of the compilation
 ///
@@ -18,6 +19,8 @@ pub struct Environment<N, G> {
     pub(crate) globals: IdVec<GlobalId, G>,
     /// The symbols
     pub(crate) nifs: IdVec<NifId, N>,
+    /// The arity of each NIF when known, to check the calls at compilation
+    pub(crate) nifs_arity: IdVec<NifId, Option<CallArity>>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -38,6 +41,7 @@ impl<N, G> Environment<N, G> {
         Self {
             symbols: SymbolsTable::new(),
             nifs: IdVec::new(),
+            nifs_arity: IdVec::new(),
             globals: IdVec::new(),
         }
     }
@@ -48,21 +52,9 @@ impl<N, G> Environment<N, G> {
         Ok(())
     }
 
-    /// Add NIF to the environment
-    pub fn add_nif(&mut self, path: &AbsPath, t: N) -> Result<NifId, EnvironmentError> {
-        let nif_id = self.nifs.next_id();
-        if let Some(id) = self.symbols.get(&path) {
-            return Err(EnvironmentError::DuplicateSymbol(path.clone(), id));
-        }
-
-        self.symbols
-            .insert(&path, EnvironmentId::Nif(nif_id))
-            .map_err(|e| EnvironmentError::SymbolInsertError(e))?;
-
-        let id = self.nifs.push(t);
-        assert_eq!(nif_id, id);
-
-        Ok(nif_id)
+    /// Get the arity of a NIF of the environment, if it has been specified
+    pub fn nif_arity(&self, nif: NifId) -> Option<CallArity> {
+        self.nifs_arity.get(nif).copied().flatten()
     }
 
     /// Add global to the environment
@@ -73,7 +65,7 @@ impl<N, G> Environment<N, G> {
         }
 
         self.symbols
-            .insert(&path, EnvironmentId::Global(global_id))
+            .insert(path, EnvironmentId::Global(global_id), Privacy::Public)
             .map_err(|e| EnvironmentError::SymbolInsertError(e))?;
 
         let id = self.globals.push(p);
@@ -82,6 +74,46 @@ impl<N, G> Environment<N, G> {
         Ok(global_id)
     }
 
+    /// Add NIF to the environment
+    ///
+    /// The arity of the NIF is not known, so the calls to it are not checked at compilation,
+    /// which is necessary for a NIF taking any number of arguments
+    pub fn add_nif(&mut self, path: &AbsPath, t: N) -> Result<NifId, EnvironmentError> {
+        self.insert_nif(path, t, None)
+    }
+
+    /// Add NIF to the environment, with the arity that its direct calls are checked against at compilation
+    pub fn add_nif_with_arity(
+        &mut self,
+        path: &AbsPath,
+        t: N,
+        arity: CallArity,
+    ) -> Result<NifId, EnvironmentError> {
+        self.insert_nif(path, t, Some(arity))
+    }
+
+    fn insert_nif(
+        &mut self,
+        path: &AbsPath,
+        t: N,
+        arity: Option<CallArity>,
+    ) -> Result<NifId, EnvironmentError> {
+        let nif_id = self.nifs.next_id();
+        if let Some(id) = self.symbols.get(path) {
+            return Err(EnvironmentError::DuplicateSymbol(path.clone(), id));
+        }
+
+        self.symbols
+            .insert(path, EnvironmentId::Nif(nif_id), Privacy::Public)
+            .map_err(EnvironmentError::SymbolInsertError)?;
+
+        let id = self.nifs.push(t);
+        assert_eq!(nif_id, id);
+        self.nifs_arity.push(arity);
+
+        Ok(nif_id)
+    }
+
     /// Finalize the environment and keep only the execution relevant information
     #[must_use]
     pub fn finalize(self) -> (IdVec<GlobalId, G>, IdVec<NifId, N>) {
@@ -1,7 +1,8 @@
-use werbolg_core::{AbsPath, Ident, Literal, Path, Span};
+use werbolg_core::{AbsPath, Ident, Literal, Namespace, Path, Span};
 
 use super::symbols::NamespaceError;
-use alloc::{boxed::Box, format, string::String};
+use super::warnings::CompilationWarning;
+use alloc::{boxed::Box, format, string::String, vec::Vec};
 
 /// Compilation error
 #[derive(Debug)]
@@ -10,10 +11,14 @@ pub enum CompilationError {
     DuplicateSymbolEnv(String, AbsPath),
     /// Duplicate symbol during compilation (e.g. 2 functions with the name)
     DuplicateSymbol(Span, Ident),
-    /// Cannot find the symbol during compilation
-    MissingSymbol(Span, Path),
+    /// Cannot find the symbol during compilation, with the symbols that may have been meant
+    MissingSymbol(Span, Path, SymbolSuggestions),
     /// Multiple symbol found for this symbol during compilation
     MultipleSymbol(Span, Path),
+    /// The symbol is private to another module, and cannot be referenced from here
+    PrivateSymbol(Span, AbsPath),
+    /// Cannot find the namespace imported by a use statement
+    MissingNamespace(Span, Path),
     /// Cannot find the constructor symbol during compilation
     MissingConstructor(Span, Path),
     /// Number of parameters for a functions is above the limit we chose
@@ -26,30 +31,90 @@ pub enum CompilationError {
     ConstructorNotStructure(Span, Path),
     /// The structure specified doesn't have a field of the right name
     StructureFieldNotExistant(Span, Path, Ident),
+    /// The structure field is specified multiple times
+    StructureFieldDuplicate(Span, Path, Ident),
+    /// The structure fields are not all specified
+    StructureFieldsMissing(Span, Path, Vec<Ident>),
+    /// The number of structure fields specified (the latter) doesn't match the structure (the former)
+    StructureFieldsMismatch(Span, Path, usize, usize),
+    /// The number of arguments of the call (the latter) doesn't match the arity of the function (the former)
+    CallArityMismatch(Span, Path, usize, usize),
+    /// The number of elements of the tuple binder (the latter) doesn't match the tuple bound (the former)
+    TupleBinderMismatch(Span, usize, usize),
+    /// The match doesn't have an arm for every possible value
+    MatchNonExhaustive(Span),
+    /// The match arm is never selected, as the previous arms already match all its values
+    MatchArmUnreachable(Span),
+    /// The match arm pattern is not compatible with the patterns of the previous arms
+    /// (e.g. mixing literal and constructor, or constructors of different types)
+    MatchArmIncompatible(Span),
+    /// The module value is read while initialising itself or an earlier value of its module
+    ValueUsedBeforeDefinition(Span, AbsPath),
+    /// The initialiser of the module depends on itself through the values of other modules
+    ModuleInitCycle(Span, Namespace),
+    /// The break is not inside a loop
+    BreakOutsideLoop(Span),
+    /// The continue is not inside a loop
+    ContinueOutsideLoop(Span),
+    /// The return is not inside a function (e.g. a top-level expression of a module)
+    ReturnOutsideFunction(Span),
+    /// The assigned variable is not a local variable (e.g. a parameter or a global)
+    AssignNotLocal(Span, Ident),
     /// Namespace Error
     NamespaceError(NamespaceError),
     /// Too Many argument to call
     CallTooManyArguments(Span, usize),
+    /// Number of values captured by a closure is above the limit we chose
+    ClosureCapturesMoreThanLimit(Span, usize),
+    /// A warning whose code is denied by the compilation parameters
+    DeniedWarning(CompilationWarning),
     /// A recursive compilation with some context added
     Context(String, Box<CompilationError>),
 }
 
+/// Candidates for a symbol that cannot be found
+#[derive(Debug, Clone, Default)]
+pub struct SymbolSuggestions {
+    /// Usable paths with a name close to the missing symbol, the closest first
+    pub similar: Vec<Path>,
+    /// Symbols with the same name in namespaces that are not imported
+    pub not_imported: Vec<AbsPath>,
+}
+
 impl CompilationError {
     /// Get the span of this compilation error
     pub fn span(&self) -> Option<Span> {
         match self {
             CompilationError::DuplicateSymbolEnv(_, _) => None,
             CompilationError::DuplicateSymbol(span, _) => Some(span.clone()),
-            CompilationError::MissingSymbol(span, _) => Some(span.clone()),
+            CompilationError::MissingSymbol(span, _, _) => Some(span.clone()),
             CompilationError::MultipleSymbol(span, _) => Some(span.clone()),
+            CompilationError::PrivateSymbol(span, _) => Some(span.clone()),
+            CompilationError::MissingNamespace(span, _) => Some(span.clone()),
             CompilationError::MissingConstructor(span, _) => Some(span.clone()),
             CompilationError::FunctionParamsMoreThanLimit(span, _) => Some(span.clone()),
             CompilationError::LiteralNotSupported(span, _) => Some(span.clone()),
             CompilationError::SequenceNotSupported(span) => Some(span.clone()),
             CompilationError::ConstructorNotStructure(span, _) => Some(span.clone()),
             CompilationError::StructureFieldNotExistant(span, _, _) => Some(span.clone()),
+            CompilationError::StructureFieldDuplicate(span, _, _) => Some(span.clone()),
+            CompilationError::StructureFieldsMissing(span, _, _) => Some(span.clone()),
+            CompilationError::StructureFieldsMismatch(span, _, _, _) => Some(span.clone()),
+            CompilationError::CallArityMismatch(span, _, _, _) => Some(span.clone()),
+            CompilationError::TupleBinderMismatch(span, _, _) => Some(span.clone()),
+            CompilationError::MatchNonExhaustive(span) => Some(span.clone()),
+            CompilationError::MatchArmUnreachable(span) => Some(span.clone()),
+            CompilationError::MatchArmIncompatible(span) => Some(span.clone()),
+            CompilationError::ValueUsedBeforeDefinition(span, _) => Some(span.clone()),
+            CompilationError::ModuleInitCycle(span, _) => Some(span.clone()),
+            CompilationError::BreakOutsideLoop(span) => Some(span.clone()),
+            CompilationError::ContinueOutsideLoop(span) => Some(span.clone()),
+            CompilationError::ReturnOutsideFunction(span) => Some(span.clone()),
+            CompilationError::AssignNotLocal(span, _) => Some(span.clone()),
             CompilationError::NamespaceError(_) => None,
             CompilationError::CallTooManyArguments(span, _) => Some(span.clone()),
+            CompilationError::ClosureCapturesMoreThanLimit(span, _) => Some(span.clone()),
+            CompilationError::DeniedWarning(warning) => Some(warning.span.clone()),
             CompilationError::Context(_, e) => e.span(),
         }
     }
@@ -69,6 +134,14 @@ impl CompilationError {
             _ => CompilationError::Context(context, Box::new(self)),
         }
     }
+
+    /// Split a compilation error into its context, if any, and the error itself
+    pub fn split_context(&self) -> (Option<&str>, &CompilationError) {
+        match self {
+            Self::Context(msg, e) => (Some(msg.as_str()), e.as_ref()),
+            _ => (None, self),
+        }
+    }
 }
 
 /*
@@ -0,0 +1,81 @@
+//! Free variables analysis of function bodies
+//!
+//! A free variable is a local path (e.g. `x`) used in a function body that
+//! is not bound by the function itself (parameters, let bindings or parameters
+//! of nested lambdas). When the function is a lambda, those are the candidate
+//! to capture from the enclosing function.
+
+use alloc::vec::Vec;
+use werbolg_core as ir;
+use werbolg_core::visit::{self, Visit};
+use werbolg_core::Ident;
+
+/// Return the free variables of a function implementation, in order of first use
+pub(crate) fn free_variables(funimpl: &ir::FunImpl) -> Vec<Ident> {
+    let mut fv = FreeVars {
+        bound: Vec::new(),
+        free: Vec::new(),
+    };
+    fv.visit_funimpl(funimpl);
+    fv.free
+}
+
+struct FreeVars {
+    bound: Vec<Ident>,
+    free: Vec<Ident>,
+}
+
+impl FreeVars {
+    fn use_ident(&mut self, ident: &Ident) {
+        if !self.bound.contains(ident) && !self.free.contains(ident) {
+            self.free.push(ident.clone())
+        }
+    }
+}
+
+impl Visit for FreeVars {
+    fn visit_funimpl(&mut self, funimpl: &ir::FunImpl) {
+        let scope = self.bound.len();
+        for var in funimpl.vars.iter() {
+            self.bound.push(var.0.inner.clone());
+        }
+        self.visit_expr(&funimpl.body);
+        self.bound.truncate(scope);
+    }
+
+    fn visit_binder(&mut self, binder: &ir::Binder) {
+        match binder {
+            ir::Binder::Ident(ident) => self.bound.push(ident.clone()),
+            _ => visit::walk_binder(self, binder),
+        }
+    }
+
+    fn visit_match_arm(&mut self, arm: &ir::MatchArm) {
+        let scope = self.bound.len();
+        visit::walk_match_arm(self, arm);
+        self.bound.truncate(scope);
+    }
+
+    fn visit_expr(&mut self, expr: &ir::Expr) {
+        match expr {
+            ir::Expr::Path(_, path) => {
+                if let Some(ident) = path.get_local() {
+                    self.use_ident(ident)
+                }
+            }
+            ir::Expr::Let(binder, body, in_expr) => {
+                // the binder is only in scope after the bound expression
+                self.visit_expr(body);
+                let scope = self.bound.len();
+                self.visit_binder(binder);
+                self.visit_expr(in_expr);
+                self.bound.truncate(scope);
+            }
+            ir::Expr::Assign(_, ident, expr) => {
+                self.use_ident(&ident.inner);
+                self.visit_expr(expr)
+            }
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+}
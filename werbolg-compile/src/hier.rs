@@ -4,6 +4,7 @@ use hashbrown::{hash_map, HashMap};
 use werbolg_core::{AbsPath, Ident, Namespace};
 
 /// A hierarchical T with recursives namespaces as Ident
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hier<T> {
     current: T,
     ns: HashMap<Ident, Hier<T>>,
@@ -0,0 +1,309 @@
+//! Ordering of the module initialisers
+//!
+//! Each module initialiser evaluates the top-level items of its module in order,
+//! and some of those items read values defined by other modules. Those values need
+//! to be initialised first, so the initialisers are ordered by their dependencies.
+//!
+//! The values read by an item are found from its compiled code, following all the
+//! functions that it references (called or not), which makes the analysis conservative:
+//! a function only referenced (e.g. stored in a closure) also counts as a dependency.
+
+use crate::instructions::Instruction;
+use crate::{FunDef, InstructionAddress};
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+use hashbrown::{HashMap, HashSet};
+use werbolg_core::id::IdF;
+use werbolg_core::idvec::IdVec;
+use werbolg_core::{FunId, GlobalId, Namespace, Span};
+
+/// A top-level item compiled in a module initialiser
+pub(crate) struct InitItemCode {
+    /// Span of the item
+    pub span: Span,
+    /// The global defined by this item, if this is a value definition
+    pub global: Option<GlobalId>,
+    /// The instructions evaluating this item
+    pub code: Range<InstructionAddress>,
+}
+
+/// A compiled module initialiser
+pub(crate) struct ModuleInitCode {
+    pub namespace: Namespace,
+    pub fun: FunId,
+    pub items: Vec<InitItemCode>,
+}
+
+/// Error in the dependencies between the initialisers
+#[derive(Debug)]
+pub(crate) enum InitError {
+    /// The item at span reads the global before the module defines it
+    UsedBeforeDefinition(Span, GlobalId),
+    /// The module at index depends on itself through other modules
+    Cycle(usize),
+}
+
+/// Return the order (as indices in `modules`) to execute the module initialisers
+///
+/// Modules without dependencies between them keep their relative order
+pub(crate) fn init_order(
+    code: &IdVec<InstructionAddress, Instruction>,
+    funs: &IdVec<FunId, FunDef>,
+    modules: &[ModuleInitCode],
+) -> Result<Vec<usize>, InitError> {
+    let refs = References::new(code, funs);
+
+    // the module and item index defining each global
+    let mut owners = HashMap::new();
+    for (module_index, module) in modules.iter().enumerate() {
+        for (item_index, item) in module.items.iter().enumerate() {
+            if let Some(global) = item.global {
+                owners.insert(global, (module_index, item_index));
+            }
+        }
+    }
+
+    let mut dependencies = vec![Vec::new(); modules.len()];
+    for (module_index, module) in modules.iter().enumerate() {
+        for (item_index, item) in module.items.iter().enumerate() {
+            for global in refs.globals_read(&item.code) {
+                let Some((owner_module, owner_item)) = owners.get(&global) else {
+                    // global from the environment, always available
+                    continue;
+                };
+                if *owner_module == module_index {
+                    if *owner_item >= item_index {
+                        return Err(InitError::UsedBeforeDefinition(item.span.clone(), global));
+                    }
+                } else if !dependencies[module_index].contains(owner_module) {
+                    dependencies[module_index].push(*owner_module)
+                }
+            }
+        }
+    }
+
+    let mut sorter = Sorter {
+        dependencies,
+        state: vec![Visit::No; modules.len()],
+        order: Vec::new(),
+    };
+    for module_index in 0..modules.len() {
+        sorter.visit(module_index)?;
+    }
+    Ok(sorter.order)
+}
+
+/// Globals read by the code, and by the functions it references
+struct References<'a> {
+    code: &'a IdVec<InstructionAddress, Instruction>,
+    /// The strongly connected component of each function in the references graph
+    fun_components: HashMap<FunId, usize>,
+    /// The globals read by the functions of each component,
+    /// directly or through the functions they reference
+    components: Vec<Vec<GlobalId>>,
+}
+
+/// Globals read and functions referenced directly by some code, in order of first appearance
+#[derive(Default)]
+struct DirectReferences {
+    globals: Vec<GlobalId>,
+    funs: Vec<FunId>,
+}
+
+impl<'a> References<'a> {
+    fn new(
+        code: &'a IdVec<InstructionAddress, Instruction>,
+        fundefs: &IdVec<FunId, FunDef>,
+    ) -> Self {
+        // functions are laid out contiguously, so each one ends where the next one starts
+        let mut positions = fundefs
+            .iter()
+            .map(|(funid, fundef)| (fundef.code_pos, funid))
+            .collect::<Vec<_>>();
+        positions.sort_by_key(|(code_pos, _)| *code_pos);
+
+        let code_end = code.next_id();
+        let mut direct = HashMap::new();
+        for (i, (code_pos, funid)) in positions.iter().enumerate() {
+            let end = positions.get(i + 1).map_or(code_end, |(next, _)| *next);
+            direct.insert(*funid, direct_references(code, &(*code_pos..end)));
+        }
+
+        // the globals of each function are computed once, the functions referencing each
+        // other (e.g. recursive functions) sharing the same globals
+        let mut components = Components {
+            direct: &direct,
+            visit_index: HashMap::new(),
+            lowlink: HashMap::new(),
+            stack: Vec::new(),
+            fun_components: HashMap::new(),
+            components: Vec::new(),
+        };
+        for (funid, _) in fundefs.iter() {
+            if !components.visit_index.contains_key(&funid) && direct.contains_key(&funid) {
+                components.visit(funid);
+            }
+        }
+
+        Self {
+            code,
+            fun_components: components.fun_components,
+            components: components.components,
+        }
+    }
+
+    /// All the globals read by the code, directly or through the functions it references
+    fn globals_read(&self, range: &Range<InstructionAddress>) -> Vec<GlobalId> {
+        let direct = direct_references(self.code, range);
+        let mut globals = direct.globals;
+        let mut seen = globals.iter().copied().collect::<HashSet<_>>();
+        for funid in direct.funs {
+            if let Some(component) = self.fun_components.get(&funid) {
+                for global in &self.components[*component] {
+                    if seen.insert(*global) {
+                        globals.push(*global)
+                    }
+                }
+            }
+        }
+        globals
+    }
+}
+
+fn direct_references(
+    code: &IdVec<InstructionAddress, Instruction>,
+    range: &Range<InstructionAddress>,
+) -> DirectReferences {
+    let mut direct = DirectReferences::default();
+    let instructions = code
+        .iter()
+        .skip(range.start.as_index())
+        .take(range.end.as_index() - range.start.as_index());
+    for (_, instruction) in instructions {
+        match instruction {
+            Instruction::FetchGlobal(global) if !direct.globals.contains(global) => {
+                direct.globals.push(*global)
+            }
+            Instruction::FetchFun(funid)
+            | Instruction::CallFun(funid, _, _)
+            | Instruction::MakeClosure(funid, _)
+                if !direct.funs.contains(funid) =>
+            {
+                direct.funs.push(*funid)
+            }
+            _ => {}
+        }
+    }
+    direct
+}
+
+/// Strongly connected components of the functions references graph, by Tarjan's algorithm
+///
+/// The components are completed after all the components they reference, so the globals
+/// of a component are the union of the direct globals of its functions and of the globals
+/// of the components referenced
+struct Components<'a> {
+    direct: &'a HashMap<FunId, DirectReferences>,
+    visit_index: HashMap<FunId, usize>,
+    lowlink: HashMap<FunId, usize>,
+    stack: Vec<FunId>,
+    fun_components: HashMap<FunId, usize>,
+    components: Vec<Vec<GlobalId>>,
+}
+
+impl<'a> Components<'a> {
+    fn visit(&mut self, funid: FunId) {
+        let direct = self.direct;
+        let index = self.visit_index.len();
+        self.visit_index.insert(funid, index);
+        self.lowlink.insert(funid, index);
+        self.stack.push(funid);
+
+        for callee in &direct[&funid].funs {
+            if !direct.contains_key(callee) || self.fun_components.contains_key(callee) {
+                continue;
+            }
+            let callee_low = match self.visit_index.get(callee) {
+                // on the stack, part of the component in progress
+                Some(callee_index) => *callee_index,
+                None => {
+                    self.visit(*callee);
+                    self.lowlink[callee]
+                }
+            };
+            let low = self.lowlink.get_mut(&funid).unwrap();
+            *low = (*low).min(callee_low);
+        }
+
+        if self.lowlink[&funid] != index {
+            return;
+        }
+
+        let mut members = Vec::new();
+        while let Some(member) = self.stack.pop() {
+            members.push(member);
+            if member == funid {
+                break;
+            }
+        }
+
+        let mut globals = Vec::new();
+        let mut seen = HashSet::new();
+        for member in &members {
+            for global in &direct[member].globals {
+                if seen.insert(*global) {
+                    globals.push(*global)
+                }
+            }
+        }
+        for member in &members {
+            for callee in &direct[member].funs {
+                // the members of this component are not assigned yet
+                if let Some(component) = self.fun_components.get(callee) {
+                    for global in &self.components[*component] {
+                        if seen.insert(*global) {
+                            globals.push(*global)
+                        }
+                    }
+                }
+            }
+        }
+
+        let component = self.components.len();
+        self.components.push(globals);
+        for member in members {
+            self.fun_components.insert(member, component);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    No,
+    InProgress,
+    Done,
+}
+
+/// Topological sort of the modules, by depth first search
+struct Sorter {
+    dependencies: Vec<Vec<usize>>,
+    state: Vec<Visit>,
+    order: Vec<usize>,
+}
+
+impl Sorter {
+    fn visit(&mut self, module_index: usize) -> Result<(), InitError> {
+        match self.state[module_index] {
+            Visit::Done => return Ok(()),
+            Visit::InProgress => return Err(InitError::Cycle(module_index)),
+            Visit::No => {}
+        }
+        self.state[module_index] = Visit::InProgress;
+        for dependency in self.dependencies[module_index].clone() {
+            self.visit(dependency)?;
+        }
+        self.state[module_index] = Visit::Done;
+        self.order.push(module_index);
+        Ok(())
+    }
+}
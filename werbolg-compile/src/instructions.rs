@@ -3,9 +3,12 @@ use werbolg_core::{ConstrId, FunId, GlobalId, LitId, NifId};
 
 /// Instruction for execution
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Instruction {
     /// Push a literal value on the stack
     PushLiteral(LitId),
+    /// Push a dummy value on the stack, for expressions without a value (e.g. a loop ending normally)
+    PushDummy,
     /// Fetch from the global values array
     FetchGlobal(GlobalId),
     /// Fetch from the nifs array
@@ -16,21 +19,72 @@ pub enum Instruction {
     FetchStackParam(ParamBindIndex),
     /// Fetch from the localstack values (which is relative and after SP)
     FetchStackLocal(LocalBindIndex),
+    /// Fetch from the values captured by the closure currently executing
+    FetchCaptured(CaptureBindIndex),
     /// Access a field in a structure value as stack\[top\]
     AccessField(ConstrId, StructFieldIndex),
+    /// Access an element in a tuple value as stack\[top\]
+    AccessTuple(TupleFieldIndex),
+    /// Check that the value as stack\[top\] is a tuple with N elements, leaving the value on the stack
+    ///
+    /// The execution fails otherwise, so the elements of a tuple are never silently ignored
+    CheckTuple(CallArity),
+    /// Access an element of the container value as stack\[top-1\] at the index value as stack\[top\]
+    ///
+    /// replacing both values by the element
+    Index,
+    /// Access an element of the container value as stack\[top\] at a constant index
+    IndexConst(u32),
+    /// Create a closure of the function with the N values captured
+    ///
+    /// expecting N values on the value stack, in capture order
+    MakeClosure(FunId, CallArity),
+    /// Create a structure value of the constructor with N fields
+    ///
+    /// expecting N values on the value stack, in field order
+    MakeStruct(ConstrId, CallArity),
+    /// Create a tuple value with N elements
+    ///
+    /// expecting N values on the value stack, in element order
+    MakeTuple(CallArity),
+    /// Test if the structure value as stack\[top\] is built with the constructor
+    ///
+    /// replacing the value by the conditional result of the test
+    TestConstr(ConstrId),
+    /// Test if the value as stack\[top\] is equal to the literal
+    ///
+    /// replacing the value by the conditional result of the test
+    TestLiteral(LitId),
     /// Bind Locally a value
     LocalBind(LocalBindIndex),
+    /// Store the value at stack\[top\] in an already bound local, replacing its value
+    StoreLocal(LocalBindIndex),
+    /// Create a reference cell containing the value at stack\[top\]
+    MakeRef,
+    /// Replace the reference cell at stack\[top\] by the value it contains
+    RefGet,
+    /// Set the value at stack\[top\] in the reference cell at stack\[top-1\], consuming both
+    RefSet,
+    /// Store the value at stack\[top\] in a global defined by a module
+    StoreGlobal(GlobalId),
     /// Ignore a value from the stack
     IgnoreOne,
     /// Call the function on the stack with the N value in arguments.
     ///
     /// expecting N+1 value on the value stack
     Call(TailCall, CallArity),
+    /// Call the function specified in the variant with the N value in arguments.
+    ///
+    /// expecting N value on the value stack, as the FunId is embedded in the instruction.
+    /// The arity of the function is checked at compilation, and no function value is
+    /// pushed on the stack for this call
+    CallFun(FunId, CallArity, TailCall),
     /// Call the Nif function specified in the variant with the N value in arguments.
     ///
-    /// expecting N value on the value stack, as the NifId is embedded in the instruction
+    /// expecting N value on the value stack, as the NifId is embedded in the instruction.
+    /// The arity of the NIF is checked, unless it takes any number of arguments (e.g. the sequence constructor)
     CallNif(NifId, CallArity),
-    /// Jump by N instructions
+    /// Jump by N instructions, N can be negative to jump backward
     Jump(InstructionDiff),
     /// Jump by N instructions if stack\[top\] is true
     CondJump(InstructionDiff),
@@ -40,6 +94,7 @@ pub enum Instruction {
 
 /// Whether or not the call is at the tail of a block and can be optimised
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TailCall {
     /// The call is at the end of a function block
     Yes,
@@ -50,23 +105,41 @@ pub enum TailCall {
 /// The index of locally (in the context of a function) bind value
 ///
 /// This is limited (arbitrarily) to a maximum of 65535 values
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LocalBindIndex(pub u16);
 
 /// the index of function parameter
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParamBindIndex(pub u8);
 
+/// the index of a value captured by a closure
+///
+/// This is limited (arbitrarily) to a maximum of 255 values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CaptureBindIndex(pub u8);
+
 /// A field in a structured indexed by its order in the structure
 ///
 /// This is limited (arbitrarily) to a maximum of 255
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StructFieldIndex(pub u8);
 
+/// An element in a tuple indexed by its position in the tuple
+///
+/// This is limited (arbitrarily) to a maximum of 255
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TupleFieldIndex(pub u8);
+
 /// The arity (number of parameter) of a function.
 ///
 /// This is limited (arbitrarily) to a maximum of 255
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CallArity(pub u8);
 
 impl TryFrom<usize> for CallArity {
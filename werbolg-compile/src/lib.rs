@@ -6,30 +6,43 @@ extern crate alloc;
 extern crate std;
 
 mod bindings;
+mod bytecode;
 mod code;
 mod compile;
 mod defs;
 mod environ;
 mod errors;
+mod freevars;
 mod hier;
+mod init;
 mod instructions;
+mod matching;
 mod params;
 mod prepare;
 mod resolver;
+mod sourcemap;
+mod suggest;
 mod symbols;
+mod warnings;
 
+pub use bytecode::{
+    read_bytecode, write_bytecode, BytecodeError, LiteralCodec, BYTECODE_MAGIC, BYTECODE_VERSION,
+};
 pub use code::{InstructionAddress, InstructionDiff};
 pub use instructions::{
-    CallArity, Instruction, LocalBindIndex, ParamBindIndex, StructFieldIndex, TailCall,
+    CallArity, CaptureBindIndex, Instruction, LocalBindIndex, ParamBindIndex, StructFieldIndex,
+    TailCall, TupleFieldIndex,
 };
 pub use params::CompilationParams;
+pub use sourcemap::SourceMap;
+pub use warnings::{CompilationWarning, WarningCode, WarningKind, WarningLevel, WarningLevels};
 
 pub use defs::*;
 use werbolg_core as ir;
-use werbolg_core::{ConstrId, FunId, LitId, Namespace};
+use werbolg_core::{AbsPath, ConstrId, FunId, GlobalId, LitId, Namespace, Privacy, Span};
 
 pub use environ::Environment;
-pub use errors::CompilationError;
+pub use errors::{CompilationError, SymbolSuggestions};
 pub use prepare::CompilationState;
 use symbols::{IdVec, SymbolsTable, SymbolsTableData};
 
@@ -40,6 +53,7 @@ use core::fmt::Write;
 ///
 /// The L type parameter is the compilation-level literal type that the user wants
 /// to compile to.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CompilationUnit<L> {
     /// Table of literal indexed by their LitId
     pub lits: IdVec<LitId, L>,
@@ -49,25 +63,120 @@ pub struct CompilationUnit<L> {
     pub funs_tbl: SymbolsTable<FunId>,
     /// Table of function indexed by their FunId
     pub funs: IdVec<FunId, FunDef>,
+    /// Symbol table of the values defined by modules { Ident => GlobalId }
+    ///
+    /// Those globals are after the globals defined in the environment
+    pub globals_tbl: SymbolsTable<GlobalId>,
+    /// The module initialisers, in the order they need to be executed
+    pub inits: Vec<ModuleInit>,
     /// A sequence of instructions of all the code, indexed by InstructionAddress
     pub code: IdVec<InstructionAddress, Instruction>,
+    /// The spans and namespaces of the instructions of the code
+    pub source_map: SourceMap,
+}
+
+impl<L> CompilationUnit<L> {
+    /// Get the function at this path, only if it is public
+    ///
+    /// This is the lookup to use to find an entry point from outside the compiled modules,
+    /// as private functions are only meant to be used by their own module
+    pub fn get_public_fun(&self, path: &AbsPath) -> Option<FunId> {
+        match self.funs_tbl.get_with_privacy(path) {
+            Some((funid, Privacy::Public)) => Some(funid),
+            _ => None,
+        }
+    }
+
+    /// Get the global defined by a module value at this path, only if it is public
+    pub fn get_public_global(&self, path: &AbsPath) -> Option<GlobalId> {
+        match self.globals_tbl.get_with_privacy(path) {
+            Some((global_id, Privacy::Public)) => Some(global_id),
+            _ => None,
+        }
+    }
+
+    /// Get the span of the expression that generated the instruction at this address
+    pub fn instruction_span(&self, ia: InstructionAddress) -> Option<&Span> {
+        self.source_map.span(ia)
+    }
+
+    /// Get the function containing the instruction at this address
+    ///
+    /// The functions are laid out contiguously, so this is the function with
+    /// the closest entry point before the address
+    pub fn fun_at(&self, ia: InstructionAddress) -> Option<FunId> {
+        self.code.get(ia)?;
+        self.funs
+            .iter()
+            .filter(|(_, fundef)| fundef.code_pos <= ia)
+            .max_by_key(|(_, fundef)| fundef.code_pos)
+            .map(|(funid, _)| funid)
+    }
+
+    /// Get the namespace of the module defining the function
+    pub fn fun_namespace(&self, funid: FunId) -> Option<&Namespace> {
+        let fundef = self.funs.get(funid)?;
+        self.source_map.namespace(fundef.code_pos)
+    }
+
+    /// Iterate over all the public functions with their path
+    pub fn public_funs(&self) -> impl Iterator<Item = (AbsPath, FunId)> + '_ {
+        self.funs_tbl
+            .iter()
+            .filter(|(path, _)| self.get_public_fun(path).is_some())
+    }
 }
 
 /// Compile a IR Module into an optimised-for-execution `CompilationUnit`
+///
+/// A module in error doesn't stop the compilation of the next modules, and on failure
+/// all the errors found in the modules are returned. The warnings are not reported,
+/// except the denied ones which are errors, use [`compile_with_warnings`] to get them
 pub fn compile<'a, L: Clone + Eq + core::hash::Hash, N, G>(
     params: &'a CompilationParams<L>,
     modules: Vec<(Namespace, ir::Module)>,
     environ: &mut Environment<N, G>,
-) -> Result<CompilationUnit<L>, CompilationError> {
+) -> Result<CompilationUnit<L>, Vec<CompilationError>> {
+    compile_with_warnings(params, modules, environ, &mut Vec::new())
+}
+
+/// Compile like [`compile`], adding the warnings found to `warnings`
+///
+/// The warnings are filtered by their level in the compilation parameters: the allowed
+/// warnings are dropped, and the denied warnings are returned as errors
+pub fn compile_with_warnings<L: Clone + Eq + core::hash::Hash, N, G>(
+    params: &CompilationParams<L>,
+    modules: Vec<(Namespace, ir::Module)>,
+    environ: &mut Environment<N, G>,
+    warnings: &mut Vec<CompilationWarning>,
+) -> Result<CompilationUnit<L>, Vec<CompilationError>> {
     let mut compiler = CompilationState::new(params.clone());
+    let mut errors = Vec::new();
     for (ns, module) in modules.into_iter() {
-        compiler
-            .add_module(&ns, module)
-            .map_err(|e| e.context(format!("compiling module {:?}", ns)))?;
+        if let Err(es) = compiler.add_module(&ns, module) {
+            let context = format!("compiling module {:?}", ns);
+            errors.extend(es.into_iter().map(|e| e.context(context.clone())));
+        }
+    }
+    let mut found = Vec::new();
+    let unit = match compiler.finalize(environ, &mut found) {
+        Ok(unit) => Some(unit),
+        Err(es) => {
+            errors.extend(es.into_iter().map(|e| e.context("Finalizing".into())));
+            None
+        }
+    };
+    for warning in found {
+        match params.warning_levels.get(warning.code()) {
+            WarningLevel::Allow => {}
+            WarningLevel::Warn => warnings.push(warning),
+            WarningLevel::Deny => errors.push(CompilationError::DeniedWarning(warning)),
+        }
+    }
+    match unit {
+        Some(unit) if errors.is_empty() => Ok(unit),
+        _ => Err(errors),
     }
-    compiler
-        .finalize(environ)
-        .map_err(|e| e.context(format!("Finalizing")))
 }
 
 /// Dump the instructions to a buffer
@@ -0,0 +1,382 @@
+//! Compilation of match arms into a decision tree
+//!
+//! The patterns of the arms are organised as a matrix, with one row per arm and
+//! one column per value to test (called occurrence). The matrix is then split one
+//! column at a time, on the constructors or literals found in this column,
+//! until the first row only contains wildcards and is selected.
+//!
+//! Each value tested is reached from the matched value by a chain of field accesses,
+//! and only get extracted once per occurrence. The decision tree never fail at runtime:
+//! reaching a point where no arm match is reported as non exhaustive when building
+//! the tree, and arms that never get selected are reported as unreachable. The only
+//! check done at runtime is the number of elements of a matched tuple, which fails the
+//! execution when it differs from the tuple patterns.
+
+use crate::instructions::{CallArity, StructFieldIndex};
+use alloc::{boxed::Box, vec, vec::Vec};
+use hashbrown::HashMap;
+use werbolg_core::{ConstrId, Ident, LitId};
+
+/// A pattern element, resolved against the constructors and literals
+#[derive(Clone, Debug)]
+pub(crate) enum Cell {
+    /// Match anything without binding
+    Wild,
+    /// Match anything and bind the value to the ident
+    Var(Ident),
+    /// Match a constructor, with a pattern for each of the constructor fields
+    Constr(ConstrId, Vec<Cell>),
+    /// Match a tuple, with a pattern for each of the elements of the tuple
+    Tuple(Vec<Cell>),
+    /// Match a value equal to the literal
+    Literal(LitId),
+}
+
+/// Index of an occurrence, a value reachable from the matched value
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Occurrence(pub usize);
+
+impl Occurrence {
+    /// The matched value itself
+    pub(crate) const ROOT: Self = Occurrence(0);
+}
+
+/// Test done on an occurrence in a switch case
+#[derive(Clone, Debug)]
+pub(crate) enum Test {
+    /// The value is built with this constructor
+    Constr(ConstrId),
+    /// The value is equal to this literal
+    Literal(LitId),
+    /// The value is a tuple with this number of elements
+    ///
+    /// All the tuple patterns at the same position have the same number of elements, so
+    /// the tuple is never tested, but its number of elements is checked at execution
+    Tuple(CallArity),
+}
+
+/// A branch of a switch, taken when the test is successful
+#[derive(Debug)]
+pub(crate) struct Case {
+    /// Test to do on the switch occurrence
+    pub test: Test,
+    /// Fields of the constructor (or elements of the tuple) to extract into their own occurrences
+    pub fields: Vec<(StructFieldIndex, Occurrence)>,
+    /// Decision to take next
+    pub decision: Decision,
+}
+
+/// Decision tree
+#[derive(Debug)]
+pub(crate) enum Decision {
+    /// Select the arm, binding the idents to the occurrences
+    Leaf(usize, Vec<(Ident, Occurrence)>),
+    /// Test the occurrence with each case in order, and otherwise take the default decision
+    ///
+    /// When there's no default decision, the cases cover all the possibilities,
+    /// and the last case doesn't need to be tested
+    Switch {
+        occurrence: Occurrence,
+        cases: Vec<Case>,
+        default: Option<Box<Decision>>,
+    },
+}
+
+/// Error when building a decision tree
+#[derive(Debug)]
+pub(crate) enum MatchError {
+    /// Some values are not matched by any arm
+    NonExhaustive,
+    /// The arm is never selected
+    Unreachable(usize),
+    /// The arm has a pattern incompatible with the previous arms patterns at the same position
+    /// (e.g. mixing literal and constructor, constructors of different types, or tuples of
+    /// different lengths)
+    Incompatible(usize),
+}
+
+#[derive(Clone)]
+struct Row {
+    cells: Vec<Cell>,
+    arm: usize,
+    bindings: Vec<(Ident, Occurrence)>,
+}
+
+struct Builder<'a> {
+    siblings: &'a dyn Fn(ConstrId) -> Vec<ConstrId>,
+    occurrences: HashMap<(Occurrence, Option<ConstrId>, u8), Occurrence>,
+    selected: Vec<bool>,
+}
+
+/// Build the decision tree for the patterns of each arms, in order
+///
+/// `siblings` returns all the constructors of the type of a constructor (e.g. all the
+/// variants of an enumeration), which is used to check if a switch is exhaustive
+pub(crate) fn decision_tree(
+    patterns: Vec<Cell>,
+    siblings: &dyn Fn(ConstrId) -> Vec<ConstrId>,
+) -> Result<Decision, MatchError> {
+    let nb_arms = patterns.len();
+    let rows = patterns
+        .into_iter()
+        .enumerate()
+        .map(|(arm, cell)| Row {
+            cells: vec![cell],
+            arm,
+            bindings: Vec::new(),
+        })
+        .collect();
+    let mut builder = Builder {
+        siblings,
+        occurrences: HashMap::new(),
+        selected: vec![false; nb_arms],
+    };
+    let decision = builder.build(rows, vec![Occurrence::ROOT])?;
+    if let Some(arm) = builder.selected.iter().position(|selected| !selected) {
+        return Err(MatchError::Unreachable(arm));
+    }
+    Ok(decision)
+}
+
+impl<'a> Builder<'a> {
+    fn field_occurrence(
+        &mut self,
+        parent: Occurrence,
+        constr: Option<ConstrId>,
+        index: u8,
+    ) -> Occurrence {
+        let next = Occurrence(self.occurrences.len() + 1);
+        *self
+            .occurrences
+            .entry((parent, constr, index))
+            .or_insert(next)
+    }
+
+    fn build(
+        &mut self,
+        mut rows: Vec<Row>,
+        occurrences: Vec<Occurrence>,
+    ) -> Result<Decision, MatchError> {
+        if rows.is_empty() {
+            return Err(MatchError::NonExhaustive);
+        }
+
+        // variables match anything, so they only record the binding of their occurrence
+        for row in rows.iter_mut() {
+            for (cell, occurrence) in row.cells.iter_mut().zip(occurrences.iter()) {
+                if let Cell::Var(ident) = cell {
+                    row.bindings.push((ident.clone(), *occurrence));
+                    *cell = Cell::Wild;
+                }
+            }
+        }
+
+        let Some(column) = rows[0].cells.iter().position(|c| !matches!(c, Cell::Wild)) else {
+            let first = rows.swap_remove(0);
+            self.selected[first.arm] = true;
+            return Ok(Decision::Leaf(first.arm, first.bindings));
+        };
+        let occurrence = occurrences[column];
+
+        match &rows[0].cells[column] {
+            Cell::Constr(constr, _) => {
+                let siblings = (self.siblings)(*constr);
+
+                // constructors present in the column, in order of appearance
+                let mut heads: Vec<(ConstrId, usize)> = Vec::new();
+                for row in rows.iter() {
+                    match &row.cells[column] {
+                        Cell::Wild | Cell::Var(_) => {}
+                        Cell::Constr(c, fields) if siblings.contains(c) => {
+                            if !heads.iter().any(|(h, _)| h == c) {
+                                heads.push((*c, fields.len()))
+                            }
+                        }
+                        Cell::Constr(_, _) | Cell::Literal(_) | Cell::Tuple(_) => {
+                            return Err(MatchError::Incompatible(row.arm));
+                        }
+                    }
+                }
+
+                let mut cases = Vec::new();
+                for (constr, arity) in heads.iter() {
+                    let case = self.specialize(
+                        &rows,
+                        &occurrences,
+                        column,
+                        Test::Constr(*constr),
+                        *arity,
+                    )?;
+                    cases.push(case);
+                }
+
+                let complete = siblings.iter().all(|s| heads.iter().any(|(h, _)| h == s));
+                let default = if complete {
+                    None
+                } else {
+                    Some(Box::new(self.default(rows, occurrences, column)?))
+                };
+                Ok(Decision::Switch {
+                    occurrence,
+                    cases,
+                    default,
+                })
+            }
+            Cell::Literal(_) => {
+                let mut heads: Vec<LitId> = Vec::new();
+                for row in rows.iter() {
+                    match &row.cells[column] {
+                        Cell::Wild | Cell::Var(_) => {}
+                        Cell::Literal(lit) => {
+                            if !heads.contains(lit) {
+                                heads.push(*lit)
+                            }
+                        }
+                        Cell::Constr(_, _) | Cell::Tuple(_) => {
+                            return Err(MatchError::Incompatible(row.arm));
+                        }
+                    }
+                }
+
+                let mut cases = Vec::new();
+                for lit in heads {
+                    let specialized = rows
+                        .iter()
+                        .filter(|row| match &row.cells[column] {
+                            Cell::Literal(l) => *l == lit,
+                            _ => true,
+                        })
+                        .map(|row| remove_column(row, column))
+                        .collect();
+                    let decision =
+                        self.build(specialized, remove_occurrence(&occurrences, column))?;
+                    cases.push(Case {
+                        test: Test::Literal(lit),
+                        fields: Vec::new(),
+                        decision,
+                    });
+                }
+
+                // literals are never considered exhaustive, a wildcard is always needed
+                let default = self.default(rows, occurrences, column)?;
+                Ok(Decision::Switch {
+                    occurrence,
+                    cases,
+                    default: Some(Box::new(default)),
+                })
+            }
+            Cell::Tuple(first) => {
+                // a tuple pattern only match tuples of its length, and all the patterns
+                // need to have the same length, so there's always a single complete case
+                let arity = first.len();
+                for row in rows.iter() {
+                    match &row.cells[column] {
+                        Cell::Wild | Cell::Var(_) => {}
+                        Cell::Tuple(elements) if elements.len() == arity => {}
+                        Cell::Tuple(_) | Cell::Constr(_, _) | Cell::Literal(_) => {
+                            return Err(MatchError::Incompatible(row.arm));
+                        }
+                    }
+                }
+                let test = Test::Tuple(CallArity(arity as u8));
+                let case = self.specialize(&rows, &occurrences, column, test, arity)?;
+                Ok(Decision::Switch {
+                    occurrence,
+                    cases: vec![case],
+                    default: None,
+                })
+            }
+            Cell::Wild | Cell::Var(_) => unreachable!(),
+        }
+    }
+
+    /// Keep the rows matching the constructor (or tuple) test in the column, and replace the column by the constructor fields
+    ///
+    /// The fields that are not tested or bound by any row are not extracted
+    fn specialize(
+        &mut self,
+        rows: &[Row],
+        occurrences: &[Occurrence],
+        column: usize,
+        test: Test,
+        arity: usize,
+    ) -> Result<Case, MatchError> {
+        let occurrences_parent = occurrences[column];
+        let constr = match test {
+            Test::Constr(constr) => Some(constr),
+            _ => None,
+        };
+        // rows matching the test, with the patterns for each of the fields
+        let mut specialized = Vec::new();
+        for row in rows.iter() {
+            let fields = match &row.cells[column] {
+                Cell::Constr(c, fields) if Some(*c) == constr => fields.clone(),
+                Cell::Constr(_, _) => continue,
+                Cell::Tuple(elements) => elements.clone(),
+                _ => vec![Cell::Wild; arity],
+            };
+            specialized.push((remove_column(row, column), fields));
+        }
+
+        let used = (0..arity)
+            .filter(|i| {
+                specialized
+                    .iter()
+                    .any(|(_, fields)| !matches!(fields[*i], Cell::Wild))
+            })
+            .collect::<Vec<_>>();
+
+        let mut fields = Vec::new();
+        let mut occurrences = remove_occurrence(occurrences, column);
+        for (n, i) in used.iter().enumerate() {
+            let occurrence = self.field_occurrence(occurrences_parent, constr, *i as u8);
+            fields.push((StructFieldIndex(*i as u8), occurrence));
+            occurrences.insert(column + n, occurrence);
+        }
+
+        let specialized = specialized
+            .into_iter()
+            .map(|(mut row, mut sub)| {
+                for (n, i) in used.iter().enumerate() {
+                    let cell = core::mem::replace(&mut sub[*i], Cell::Wild);
+                    row.cells.insert(column + n, cell);
+                }
+                row
+            })
+            .collect();
+
+        let decision = self.build(specialized, occurrences)?;
+        Ok(Case {
+            test,
+            fields,
+            decision,
+        })
+    }
+
+    /// Keep the rows matching anything in the column, and remove the column
+    fn default(
+        &mut self,
+        rows: Vec<Row>,
+        occurrences: Vec<Occurrence>,
+        column: usize,
+    ) -> Result<Decision, MatchError> {
+        let rows = rows
+            .iter()
+            .filter(|row| matches!(row.cells[column], Cell::Wild))
+            .map(|row| remove_column(row, column))
+            .collect();
+        self.build(rows, remove_occurrence(&occurrences, column))
+    }
+}
+
+fn remove_column(row: &Row, column: usize) -> Row {
+    let mut row = row.clone();
+    row.cells.remove(column);
+    row
+}
+
+fn remove_occurrence(occurrences: &[Occurrence], column: usize) -> Vec<Occurrence> {
+    let mut occurrences = occurrences.to_vec();
+    occurrences.remove(column);
+    occurrences
+}
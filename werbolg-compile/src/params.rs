@@ -1,3 +1,4 @@
+use super::warnings::WarningLevels;
 use super::CompilationError;
 use werbolg_core::{Literal, NifId, Span};
 
@@ -8,6 +9,9 @@ pub struct CompilationParams<L: Clone + Eq + core::hash::Hash> {
     pub literal_mapper: fn(Span, Literal) -> Result<L, CompilationError>,
 
     /// Constructor for a possible sequence of expression (list or array), that
-    /// take usize argument from the stack
+    /// take all the elements of the sequence as arguments, whatever the number of elements
     pub sequence_constructor: Option<NifId>,
+
+    /// Level of each warning, to allow or deny some of the warnings
+    pub warning_levels: WarningLevels,
 }
@@ -2,24 +2,32 @@ pub use crate::params::CompilationParams;
 
 use crate::compile::{self, *};
 pub use crate::defs::*;
+use crate::instructions::{CallArity, Instruction};
 use crate::resolver::SymbolResolver;
 use crate::CompilationUnit;
 use werbolg_core as ir;
-use werbolg_core::{AbsPath, ConstrId, FunId, Namespace};
+use werbolg_core::id::IdF;
+use werbolg_core::{AbsPath, ConstrId, FunId, GlobalId, Namespace, Privacy, Span};
 
 use crate::bindings::{BindingType, GlobalBindings};
 pub use crate::environ::Environment;
 pub use crate::errors::CompilationError;
-use crate::symbols::{self, IdVecAfter, SymbolsTableData};
+use crate::init::{self, InitError, ModuleInitCode};
+use crate::symbols::{self, IdVec, IdVecAfter, SymbolsTableData};
+use crate::warnings::{CompilationWarning, WarningKind};
 
-use alloc::{format, string::String, vec::Vec};
-use hashbrown::HashMap;
+use alloc::{format, string::String, vec, vec::Vec};
+use hashbrown::{HashMap, HashSet};
 
 /// State of compilation
 pub struct CompilationState<L: Clone + Eq + core::hash::Hash> {
     params: CompilationParams<L>,
-    funs: SymbolsTableData<FunId, (Namespace, ir::FunDef, ir::FunImpl)>,
+    funs: SymbolsTableData<FunId, (Namespace, Span, ir::FunDef, ir::FunImpl)>,
     constrs: SymbolsTableData<ConstrId, ConstrDef>,
+    /// Values defined by modules, the GlobalId are relative to the first module global
+    values: SymbolsTableData<GlobalId, Span>,
+    /// Top-level items to evaluate for each module, in the order the modules are added
+    inits: Vec<(Namespace, Vec<InitItem>)>,
     namespaces: HashMap<Namespace, SymbolResolver>,
 }
 
@@ -30,49 +38,40 @@ impl<L: Clone + Eq + core::hash::Hash> CompilationState<L> {
             params,
             funs: SymbolsTableData::new(),
             constrs: SymbolsTableData::new(),
+            values: SymbolsTableData::new(),
+            inits: Vec::new(),
             namespaces: HashMap::new(),
         }
     }
 
     /// Add a ir::module to the compilation state
+    ///
+    /// A statement in error doesn't prevent adding the next statements, so that all
+    /// the errors of the module are returned
     pub fn add_module(
         &mut self,
         namespace: &Namespace,
         module: ir::Module,
-    ) -> Result<(), CompilationError> {
+    ) -> Result<(), Vec<CompilationError>> {
         let mut uses = Vec::new();
-        self.funs.create_namespace(namespace.clone())?;
-        self.constrs.create_namespace(namespace.clone())?;
+        let mut inits = Vec::new();
+        let mut errors = Vec::new();
+        self.funs
+            .create_namespace(namespace.clone())
+            .and_then(|()| self.constrs.create_namespace(namespace.clone()))
+            .and_then(|()| self.values.create_namespace(namespace.clone()))
+            .map_err(|e| vec![e.into()])?;
 
         for stmt in module.statements.into_iter() {
-            match stmt {
-                ir::Statement::Use(u) => {
-                    uses.push(u);
-                }
-                ir::Statement::Function(span, fundef, funimpl) => {
-                    let ident = fundef.name.clone();
-                    let path = AbsPath::new(namespace, &ident);
-                    let _funid = self
-                        .funs
-                        .add(&path, (namespace.clone(), fundef, funimpl))
-                        .ok_or_else(|| CompilationError::DuplicateSymbol(span, ident))?;
-                    ()
-                }
-                ir::Statement::Struct(span, structdef) => {
-                    let stru = StructDef {
-                        name: structdef.name.unspan(),
-                        fields: structdef.fields.into_iter().map(|v| v.unspan()).collect(),
-                    };
-                    let name = stru.name.clone();
-                    let path = AbsPath::new(namespace, &name);
-                    self.constrs
-                        .add(&path, ConstrDef::Struct(stru))
-                        .ok_or_else(|| CompilationError::DuplicateSymbol(span, name))?;
-                }
-                ir::Statement::Expr(_) => (),
+            if let Err(e) = self.add_statement(namespace, stmt, &mut uses, &mut inits) {
+                errors.push(e)
             }
         }
 
+        if !inits.is_empty() {
+            self.inits.push((namespace.clone(), inits));
+        }
+
         if self
             .namespaces
             .insert(
@@ -81,20 +80,150 @@ impl<L: Clone + Eq + core::hash::Hash> CompilationState<L> {
             )
             .is_some()
         {
-            return Err(CompilationError::NamespaceError(
+            errors.push(CompilationError::NamespaceError(
                 symbols::NamespaceError::Duplicate(namespace.clone()),
             ));
         }
 
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Add a statement of the module, with the use statements and top-level items
+    /// collected to be processed once the module is complete
+    fn add_statement(
+        &mut self,
+        namespace: &Namespace,
+        stmt: ir::Statement,
+        uses: &mut Vec<(Span, ir::Use)>,
+        inits: &mut Vec<InitItem>,
+    ) -> Result<(), CompilationError> {
+        match stmt {
+            ir::Statement::Use(span, u) => {
+                uses.push((span, u));
+            }
+            ir::Statement::Function(span, fundef, funimpl) => {
+                let ident = fundef.name.clone();
+                let path = AbsPath::new(namespace, &ident);
+                let privacy = fundef.privacy;
+                if self.values.get(&path).is_some() {
+                    return Err(CompilationError::DuplicateSymbol(span, ident));
+                }
+                let _funid = self
+                    .funs
+                    .add(
+                        &path,
+                        privacy,
+                        (namespace.clone(), span.clone(), fundef, funimpl),
+                    )
+                    .ok_or(CompilationError::DuplicateSymbol(span, ident))?;
+            }
+            ir::Statement::Value(span, valuedef, expr) => {
+                let ident = valuedef.name;
+                let path = AbsPath::new(namespace, &ident);
+                if self.funs.get(&path).is_some() {
+                    return Err(CompilationError::DuplicateSymbol(span, ident));
+                }
+                let global = self
+                    .values
+                    .add(&path, valuedef.privacy, span.clone())
+                    .ok_or_else(|| CompilationError::DuplicateSymbol(span.clone(), ident))?;
+                inits.push(InitItem {
+                    span,
+                    global: Some(global),
+                    expr,
+                });
+            }
+            ir::Statement::Struct(span, structdef) => {
+                let stru = StructDef {
+                    name: structdef.name.unspan(),
+                    fields: structdef.fields.into_iter().map(|v| v.unspan()).collect(),
+                };
+                let name = stru.name.clone();
+                let path = AbsPath::new(namespace, &name);
+                self.constrs
+                    .add(&path, structdef.privacy, ConstrDef::Struct(stru))
+                    .ok_or(CompilationError::DuplicateSymbol(span, name))?;
+            }
+            ir::Statement::Enum(span, enumdef) => {
+                self.add_enum(namespace, span, enumdef)?;
+            }
+            ir::Statement::Expr(expr) => {
+                inits.push(InitItem {
+                    span: expr.span(),
+                    global: None,
+                    expr,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Add an enumeration to the constructors
+    ///
+    /// The enumeration is defined as `namespace::Enum`, and each variant is defined
+    /// as its own constructor `namespace::Enum::Variant`
+    fn add_enum(
+        &mut self,
+        namespace: &Namespace,
+        span: Span,
+        enumdef: ir::EnumDef,
+    ) -> Result<(), CompilationError> {
+        let name = enumdef.name.unspan();
+        let path = AbsPath::new(namespace, &name);
+        if self.constrs.get(&path).is_some() {
+            return Err(CompilationError::DuplicateSymbol(span, name));
+        }
+
+        let enum_namespace = namespace.clone().append(name.clone());
+        self.constrs.create_namespace(enum_namespace.clone())?;
+
+        let mut variants = Vec::new();
+        for ir::Variant(structdef) in enumdef.variants.into_iter() {
+            let stru = StructDef {
+                name: structdef.name.inner.clone(),
+                fields: structdef.fields.into_iter().map(|v| v.unspan()).collect(),
+            };
+            let variant_name = stru.name.clone();
+            let variant_path = AbsPath::new(&enum_namespace, &variant_name);
+            let constr = self
+                .constrs
+                .add(&variant_path, enumdef.privacy, ConstrDef::Struct(stru))
+                .ok_or_else(|| {
+                    CompilationError::DuplicateSymbol(structdef.name.span, variant_name.clone())
+                })?;
+            variants.push(Variant {
+                name: variant_name,
+                constr,
+            });
+        }
+
+        // duplicate of the enumeration symbol has already been checked above
+        self.constrs
+            .add(
+                &path,
+                enumdef.privacy,
+                ConstrDef::Enum(EnumDef { name, variants }),
+            )
+            .unwrap();
         Ok(())
     }
 
     /// Finalize compilation and return a CompilationUnit containing all the modules compiled in the state
+    ///
+    /// The code of every function and module initialiser is generated even when some are in error,
+    /// and all the errors found are returned. The warnings found are added to `warnings`,
+    /// whether the compilation succeeds or not
     pub fn finalize<N, G>(
         self,
         environ: &mut Environment<N, G>,
-    ) -> Result<CompilationUnit<L>, CompilationError> {
+        warnings: &mut Vec<CompilationWarning>,
+    ) -> Result<CompilationUnit<L>, Vec<CompilationError>> {
         let SymbolsTableData { table, vecdata } = self.funs;
+        let mut errors = Vec::new();
 
         /*
         for (p, _id) in table.to_vec(Namespace::root()) {
@@ -104,6 +233,13 @@ impl<L: Clone + Eq + core::hash::Hash> CompilationState<L> {
 
         let mut root_bindings = GlobalBindings::new();
 
+        // module values are stored after the globals of the environment
+        let globals_ofs = environ.globals.next_id();
+        let globals_tbl = self
+            .values
+            .table
+            .remap(|global| GlobalId::remap(global, globals_ofs));
+
         for (path, id) in environ.symbols.iter() {
             // unwrap is ok here, the environment should check for duplicate symbol and
             // missing namespace
@@ -118,51 +254,197 @@ impl<L: Clone + Eq + core::hash::Hash> CompilationState<L> {
         }
 
         for (path, fun_id) in table.iter() {
-            root_bindings
-                .add(path.clone(), BindingType::Fun(fun_id))
-                .map_err(|()| {
-                    CompilationError::DuplicateSymbolEnv(String::from("Fun"), path.clone())
-                })?
+            if let Err(()) = root_bindings.add(path.clone(), BindingType::Fun(fun_id)) {
+                errors.push(CompilationError::DuplicateSymbolEnv(
+                    String::from("Fun"),
+                    path.clone(),
+                ))
+            }
+        }
+
+        for (path, global_id) in globals_tbl.iter() {
+            if let Err(()) = root_bindings.add(path.clone(), BindingType::Global(global_id)) {
+                errors.push(CompilationError::DuplicateSymbolEnv(
+                    String::from("Value"),
+                    path.clone(),
+                ))
+            }
+        }
+
+        // now that all the modules are known, check that the imported namespaces exist
+        for resolver in self.namespaces.values() {
+            for (span, path) in resolver.imported_namespaces() {
+                let namespace = Namespace::from_path(path);
+                if !table.0.namespace_exist(namespace.clone())
+                    && !self.constrs.table.0.namespace_exist(namespace.clone())
+                    && !root_bindings.0.namespace_exist(namespace)
+                {
+                    errors.push(CompilationError::MissingNamespace(
+                        span.clone(),
+                        path.clone(),
+                    ));
+                }
+            }
         }
 
         // all modules share this compilation state
-        let shared = CompilationSharedState {};
+        let mut shared = CompilationSharedState {
+            funs_arity: IdVec::new(),
+            nifs_arity: IdVec::new(),
+        };
+        for (_, (_, _, _, funimpl)) in vecdata.iter() {
+            let arity = funimpl.vars.len().try_into().ok().map(CallArity);
+            shared.funs_arity.push(arity);
+        }
+        for (_, arity) in environ.nifs_arity.iter() {
+            shared.nifs_arity.push(*arity);
+        }
 
         let mut state = compile::CodeBuilder::new(
             &shared,
             self.params,
             table,
+            globals_tbl,
             IdVecAfter::new(vecdata.next_id()),
+            self.constrs,
             root_bindings,
         );
 
-        for (funid, (namespace, fundef, funimpl)) in vecdata.into_iter() {
+        let mut private_funs = Vec::new();
+        for (funid, (namespace, span, fundef, funimpl)) in vecdata.into_iter() {
+            if fundef.privacy == Privacy::Private {
+                private_funs.push((funid, namespace.clone(), span, fundef.name.clone()));
+            }
             let Some(uses) = self.namespaces.get(&namespace) else {
                 panic!("internal error: namespace not defined");
             };
             state.set_module_resolver(uses);
 
             let fun_name = fundef.name.clone();
-            let lirdef = compile::generate_func_code(&mut state, &namespace, Some(fundef), funimpl)
-                .map_err(|e| {
-                    e.context(format!(
+            let lirdef = match compile::generate_func_code(
+                &mut state,
+                &namespace,
+                Some(fundef),
+                &[],
+                funimpl,
+            ) {
+                Ok(lirdef) => lirdef,
+                Err(e) => {
+                    errors.push(e.context(format!(
                         "namespace {:?} function code {:?}",
                         namespace, fun_name
-                    ))
-                })?;
+                    )));
+                    state.discard_lambdas();
+                    // the function is still defined, to keep the next function ids in sync
+                    FunDef {
+                        name: Some(fun_name),
+                        arity: CallArity::try_from(0usize).unwrap(),
+                        stack_size: LocalStackSize(0),
+                        code_pos: state.main_code.position(),
+                    }
+                }
+            };
             let lirid = state.funs_vec.push(lirdef);
             assert_eq!(funid, lirid)
         }
 
+        let mut inits_code = Vec::new();
+        for (namespace, mut items) in self.inits.into_iter() {
+            let Some(uses) = self.namespaces.get(&namespace) else {
+                panic!("internal error: namespace not defined");
+            };
+            state.set_module_resolver(uses);
+
+            for item in items.iter_mut() {
+                item.global = item
+                    .global
+                    .map(|global| GlobalId::remap(global, globals_ofs));
+            }
+            let (lirdef, items) =
+                match compile::generate_init_code(&mut state, &namespace, items) {
+                    Ok(code) => code,
+                    Err(es) => {
+                        errors.extend(es.into_iter().map(|e| {
+                            e.context(format!("namespace {:?} initialiser code", namespace))
+                        }));
+                        continue;
+                    }
+                };
+            // initialisers are anonymous functions, like the lambdas
+            let fun = state.lambdas_vec.push(lirdef);
+            inits_code.push(ModuleInitCode {
+                namespace,
+                fun,
+                items,
+            });
+        }
+
+        warnings.append(&mut state.warnings);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
         // merge the lambdas vec with the main fun vec
         state.funs_vec.concat(&mut state.lambdas_vec);
+        let (code, source_map) = state.main_code.finalize();
+
+        // the private functions can only be referenced from the code of their module
+        let referenced = code
+            .iter()
+            .filter_map(|(_, instruction)| match instruction {
+                Instruction::FetchFun(funid) | Instruction::CallFun(funid, _, _) => Some(*funid),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        for (funid, namespace, span, name) in private_funs {
+            if !referenced.contains(&funid) {
+                warnings.push(CompilationWarning {
+                    namespace,
+                    span,
+                    kind: WarningKind::UnusedFunction(name),
+                });
+            }
+        }
+
+        let order = init::init_order(&code, &state.funs_vec, &inits_code).map_err(|e| match e {
+            InitError::UsedBeforeDefinition(span, global_id) => {
+                let path = state
+                    .globals_tbl
+                    .iter()
+                    .find_map(|(path, id)| (id == global_id).then_some(path))
+                    .expect("module value defined");
+                CompilationError::ValueUsedBeforeDefinition(span, path)
+            }
+            InitError::Cycle(index) => {
+                let module = &inits_code[index];
+                CompilationError::ModuleInitCycle(
+                    module.items[0].span.clone(),
+                    module.namespace.clone(),
+                )
+            }
+        });
+        let order = order.map_err(|e| vec![e])?;
+        let inits = order
+            .into_iter()
+            .map(|index| {
+                let module = &inits_code[index];
+                ModuleInit {
+                    namespace: module.namespace.clone(),
+                    fun: module.fun,
+                    globals: module.items.iter().filter_map(|item| item.global).collect(),
+                }
+            })
+            .collect();
 
         Ok(CompilationUnit {
             lits: state.lits.finalize(),
             constrs: state.constrs,
             funs: state.funs_vec,
             funs_tbl: state.funs_tbl,
-            code: state.main_code.finalize(),
+            globals_tbl: state.globals_tbl,
+            inits,
+            code,
+            source_map,
         })
     }
 }
@@ -1,18 +1,54 @@
 use alloc::vec::Vec;
-use werbolg_core::Namespace;
+use werbolg_core::{Namespace, Path, PathType, Span, Use};
 
 /// Symbol Resolver
 #[derive(Clone)]
 pub struct SymbolResolver {
     #[allow(unused)]
     pub(crate) current: Namespace,
-    #[allow(unused)]
-    pub(crate) uses: Vec<werbolg_core::Use>,
+    pub(crate) uses: Vec<(Span, Use)>,
 }
 
 impl SymbolResolver {
     /// Create a resolver from
-    pub fn new(current: Namespace, uses: Vec<werbolg_core::Use>) -> Self {
+    pub fn new(current: Namespace, uses: Vec<(Span, Use)>) -> Self {
         Self { current, uses }
     }
+
+    /// Get the namespaces and the relative paths to lookup in those namespaces,
+    /// for a relative path that may refer to imported symbols
+    ///
+    /// The first component of the path is the symbol imported, which could be
+    /// hidden or renamed by the use statement
+    pub fn imported_paths(&self, path: &Path) -> Vec<(Namespace, Path)> {
+        let mut idents = path.idents();
+        let first = idents[0].clone();
+
+        let mut out = Vec::new();
+        for (_, u) in self.uses.iter() {
+            if u.hiding.contains(&first) {
+                continue;
+            }
+            let imported = if let Some((original, _)) = u.renames.iter().find(|(_, r)| *r == first)
+            {
+                original.clone()
+            } else if u.renames.iter().any(|(original, _)| *original == first) {
+                // a renamed symbol is only reachable through its new name
+                continue;
+            } else {
+                first.clone()
+            };
+            idents[0] = imported;
+            out.push((
+                Namespace::from_path(&u.namespace),
+                Path::new_raw(PathType::Relative, idents.clone()),
+            ));
+        }
+        out
+    }
+
+    /// Iterate over the namespaces imported, with the span of their use statement
+    pub fn imported_namespaces(&self) -> impl Iterator<Item = (&Span, &Path)> {
+        self.uses.iter().map(|(span, u)| (span, &u.namespace))
+    }
 }
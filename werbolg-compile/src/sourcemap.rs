@@ -0,0 +1,75 @@
+use super::code::InstructionAddress;
+use alloc::vec::Vec;
+use werbolg_core::{Namespace, Span};
+
+/// Map from the instructions back to the source they are compiled from
+///
+/// Consecutive instructions generated from the same expression share an entry, which
+/// start at the address of the first instruction, and cover all the instructions up
+/// to the next entry. The span of an instruction is the span of the innermost expression
+/// generating it, so a call instruction is mapped to the whole call expression, while
+/// the code evaluating its arguments is mapped to each argument.
+///
+/// The spans are relative to the source of the module of the instruction, given by the
+/// namespace of the instruction.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourceMap {
+    spans: Vec<(InstructionAddress, Span)>,
+    namespaces: Vec<(InstructionAddress, Namespace)>,
+}
+
+impl SourceMap {
+    /// Create a new empty source map, where no instruction has a span
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the span of the instructions starting at this address
+    ///
+    /// The addresses need to be added in increasing order
+    pub fn add_span(&mut self, ia: InstructionAddress, span: Span) {
+        add_entry(&mut self.spans, ia, span)
+    }
+
+    /// Set the namespace of the instructions starting at this address
+    ///
+    /// The addresses need to be added in increasing order
+    pub fn add_namespace(&mut self, ia: InstructionAddress, namespace: Namespace) {
+        add_entry(&mut self.namespaces, ia, namespace)
+    }
+
+    /// Get the span of the expression that generated the instruction at this address
+    pub fn span(&self, ia: InstructionAddress) -> Option<&Span> {
+        find_entry(&self.spans, ia)
+    }
+
+    /// Get the namespace of the module containing the instruction at this address
+    pub fn namespace(&self, ia: InstructionAddress) -> Option<&Namespace> {
+        find_entry(&self.namespaces, ia)
+    }
+
+    /// Return whether the source map doesn't have any information
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty() && self.namespaces.is_empty()
+    }
+}
+
+fn add_entry<T: PartialEq>(
+    entries: &mut Vec<(InstructionAddress, T)>,
+    ia: InstructionAddress,
+    t: T,
+) {
+    match entries.last_mut() {
+        Some((_, last)) if *last == t => {}
+        // the previous entry doesn't cover any instruction, so it is replaced
+        Some((last_ia, last)) if *last_ia == ia => *last = t,
+        _ => entries.push((ia, t)),
+    }
+}
+
+fn find_entry<T>(entries: &[(InstructionAddress, T)], ia: InstructionAddress) -> Option<&T> {
+    // the entries are ordered by address, so the entry covering ia is the last one starting before it
+    let index = entries.partition_point(|(start, _)| *start <= ia);
+    index.checked_sub(1).map(|index| &entries[index].1)
+}
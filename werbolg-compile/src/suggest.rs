@@ -0,0 +1,72 @@
+use alloc::{vec, vec::Vec};
+use werbolg_core::Ident;
+
+/// Number of edits to go from a to b, where an edit is the insertion, deletion or substitution
+/// of a character, or the transposition of two adjacent characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    // distances from the 2 last prefixes of a processed to every prefix of b
+    let mut previous2 = Vec::new();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let substitution = previous[j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            row[j] = substitution.min(previous[j] + 1).min(row[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(previous2[j - 2] + 1);
+            }
+        }
+        previous2 = core::mem::replace(&mut previous, row);
+    }
+    previous[b.len()]
+}
+
+/// Find the candidates close enough to the ident to be a typo, the closest first
+///
+/// The candidates at the same distance keep their order, and the ident itself is never returned
+pub fn similar<'a, I: IntoIterator<Item = &'a Ident>>(
+    ident: &Ident,
+    candidates: I,
+) -> Vec<&'a Ident> {
+    // allow one edit for every 3 characters, so short names only match on a single edit
+    let max_distance = core::cmp::max(ident.0.chars().count(), 3) / 3;
+    let mut found = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(&ident.0, &candidate.0);
+            (distance > 0 && distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect::<Vec<_>>();
+    found.sort_by_key(|(distance, _)| *distance);
+    found.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("lenght", "length"), 1);
+        assert_eq!(edit_distance("valeu", "value"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("sum", "sums"), 1);
+    }
+
+    #[test]
+    fn closest_first() {
+        let candidates = [
+            Ident::from("prin"),
+            Ident::from("print"),
+            Ident::from("unrelated"),
+            Ident::from("printl"),
+        ];
+        let found = similar(&Ident::from("printn"), candidates.iter());
+        assert_eq!(found, [&candidates[1], &candidates[3], &candidates[0]]);
+    }
+}
@@ -5,14 +5,15 @@ use core::marker::PhantomData;
 use hashbrown::{hash_map, HashMap};
 use werbolg_core::id::IdF;
 pub use werbolg_core::idvec::{IdVec, IdVecAfter};
-use werbolg_core::{AbsPath, Ident, Namespace};
+use werbolg_core::{AbsPath, Ident, Namespace, Privacy};
 
-/// A simple lookup table from Ident to ID
+/// A simple lookup table from Ident to ID, also keeping the privacy of each symbol
 ///
 /// this is a flat table (only use 1 Ident for lookup/insertion),
 /// for hierarchical table use `SymbolsTable`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SymbolsTableFlat<ID> {
-    pub(crate) tbl: HashMap<Ident, ID>,
+    pub(crate) tbl: HashMap<Ident, (ID, Privacy)>,
     phantom: PhantomData<ID>,
 }
 
@@ -30,17 +31,26 @@ impl<ID: Copy> SymbolsTableFlat<ID> {
         }
     }
 
-    pub fn insert(&mut self, ident: Ident, id: ID) -> Result<(), SymbolInsertFlatError> {
+    pub fn insert(
+        &mut self,
+        ident: Ident,
+        id: ID,
+        privacy: Privacy,
+    ) -> Result<(), SymbolInsertFlatError> {
         if self.tbl.get(&ident).is_some() {
             Err(SymbolInsertFlatError { ident })
         } else {
-            self.tbl.insert(ident, id);
+            self.tbl.insert(ident, (id, privacy));
             Ok(())
         }
     }
 
     pub fn get(&self, ident: &Ident) -> Option<ID> {
-        self.tbl.get(ident).map(|i| *i)
+        self.tbl.get(ident).map(|(i, _)| *i)
+    }
+
+    pub fn get_with_privacy(&self, ident: &Ident) -> Option<(ID, Privacy)> {
+        self.tbl.get(ident).copied()
     }
 
     pub fn iter<'a>(&'a self) -> SymbolsTableFlatIterator<'a, ID> {
@@ -48,16 +58,17 @@ impl<ID: Copy> SymbolsTableFlat<ID> {
     }
 }
 
-pub struct SymbolsTableFlatIterator<'a, ID>(hash_map::Iter<'a, Ident, ID>);
+pub struct SymbolsTableFlatIterator<'a, ID>(hash_map::Iter<'a, Ident, (ID, Privacy)>);
 
 impl<'a, ID> Iterator for SymbolsTableFlatIterator<'a, ID> {
     type Item = (&'a Ident, &'a ID);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        self.0.next().map(|(ident, (id, _))| (ident, id))
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SymbolsTable<ID>(pub(crate) Hier<SymbolsTableFlat<ID>>);
 
 #[derive(Clone, Debug)]
@@ -108,9 +119,14 @@ impl<ID: Copy> SymbolsTable<ID> {
             .map_err(|()| NamespaceError::DuplicateLeaf(namespace))
     }
 
-    pub fn insert(&mut self, path: &AbsPath, id: ID) -> Result<(), SymbolInsertError> {
+    pub fn insert(
+        &mut self,
+        path: &AbsPath,
+        id: ID,
+        privacy: Privacy,
+    ) -> Result<(), SymbolInsertError> {
         let (namespace, ident) = path.split();
-        match self.on_flat_table_mut(&namespace, |table| table.insert(ident.clone(), id)) {
+        match self.on_flat_table_mut(&namespace, |table| table.insert(ident.clone(), id, privacy)) {
             Ok(()) => Ok(()),
             Err(e) => {
                 if let Some(err) = e.err {
@@ -123,14 +139,31 @@ impl<ID: Copy> SymbolsTable<ID> {
     }
 
     pub fn get(&self, path: &AbsPath) -> Option<ID> {
+        self.get_with_privacy(path).map(|(id, _)| id)
+    }
+
+    pub fn get_with_privacy(&self, path: &AbsPath) -> Option<(ID, Privacy)> {
         let (namespace, ident) = path.split();
         if let Ok(tbl) = self.0.get(&namespace) {
-            tbl.get(&ident)
+            tbl.get_with_privacy(&ident)
         } else {
             None
         }
     }
 
+    /// Create a table with the same symbols and privacy, with each ID mapped by f
+    pub fn remap<F: Fn(ID) -> ID>(&self, f: F) -> Self {
+        let mut table = Self::new();
+        for (path, id) in self.iter() {
+            let (namespace, _) = path.split();
+            let (_, privacy) = self.get_with_privacy(&path).unwrap();
+            // the namespaces and symbols are unique in the original table
+            table.create_namespace(namespace).unwrap();
+            table.insert(&path, f(id), privacy).unwrap();
+        }
+        table
+    }
+
     fn dump_path(&self, current: Namespace, vec: &mut Vec<(AbsPath, ID)>) {
         let mut ts = Vec::new();
         self.0.dump(current, &mut ts);
@@ -159,6 +192,7 @@ impl<ID: Copy> SymbolsTable<ID> {
 }
 
 /// Symbol Table Data maps Ident to ID and store the ID to T
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SymbolsTableData<ID, T> {
     pub table: SymbolsTable<ID>,
     pub vecdata: IdVec<ID, T>,
@@ -176,12 +210,12 @@ impl<ID: IdF, T> SymbolsTableData<ID, T> {
         self.table.create_namespace(namespace)
     }
 
-    pub fn add(&mut self, path: &AbsPath, v: T) -> Option<ID> {
+    pub fn add(&mut self, path: &AbsPath, privacy: Privacy, v: T) -> Option<ID> {
         if self.table.get(&path).is_some() {
             return None;
         }
         let id = self.vecdata.push(v);
-        self.table.insert(path, id).unwrap();
+        self.table.insert(path, id, privacy).unwrap();
         Some(id)
     }
 
@@ -0,0 +1,131 @@
+use werbolg_core::{Ident, Namespace, Span};
+
+/// Compilation Warning
+///
+/// A warning doesn't prevent the compilation, unless its code is denied
+/// in the [`WarningLevels`] of the compilation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompilationWarning {
+    /// The namespace of the module the warning is found in
+    pub namespace: Namespace,
+    /// The span of the warning, relative to the source of the module
+    pub span: Span,
+    /// What the warning is about
+    pub kind: WarningKind,
+}
+
+/// The kind of compilation warning
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarningKind {
+    /// A local binding is never used
+    UnusedBinding(Ident),
+    /// A parameter of a function is never used
+    UnusedParam(Ident),
+    /// A private function is never referenced
+    UnusedFunction(Ident),
+    /// A local binding shadows a parameter of the function
+    ShadowedParam(Ident),
+    /// A literal is bound to `_`, which has no effect
+    IgnoredLiteral,
+}
+
+/// Stable code of a compilation warning, used to allow or deny the warning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarningCode {
+    /// Code of [`WarningKind::UnusedBinding`]
+    UnusedBinding,
+    /// Code of [`WarningKind::UnusedParam`]
+    UnusedParam,
+    /// Code of [`WarningKind::UnusedFunction`]
+    UnusedFunction,
+    /// Code of [`WarningKind::ShadowedParam`]
+    ShadowedParam,
+    /// Code of [`WarningKind::IgnoredLiteral`]
+    IgnoredLiteral,
+}
+
+impl WarningCode {
+    /// All the warning codes
+    pub const ALL: [WarningCode; 5] = [
+        WarningCode::UnusedBinding,
+        WarningCode::UnusedParam,
+        WarningCode::UnusedFunction,
+        WarningCode::ShadowedParam,
+        WarningCode::IgnoredLiteral,
+    ];
+
+    /// Get the name of the warning code, which doesn't change between versions
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WarningCode::UnusedBinding => "unused-binding",
+            WarningCode::UnusedParam => "unused-param",
+            WarningCode::UnusedFunction => "unused-function",
+            WarningCode::ShadowedParam => "shadowed-param",
+            WarningCode::IgnoredLiteral => "ignored-literal",
+        }
+    }
+
+    /// Get the warning code from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|code| code.as_str() == name)
+    }
+}
+
+impl core::fmt::Display for WarningCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl WarningKind {
+    /// Get the stable code of this kind of warning
+    pub fn code(&self) -> WarningCode {
+        match self {
+            WarningKind::UnusedBinding(_) => WarningCode::UnusedBinding,
+            WarningKind::UnusedParam(_) => WarningCode::UnusedParam,
+            WarningKind::UnusedFunction(_) => WarningCode::UnusedFunction,
+            WarningKind::ShadowedParam(_) => WarningCode::ShadowedParam,
+            WarningKind::IgnoredLiteral => WarningCode::IgnoredLiteral,
+        }
+    }
+}
+
+impl CompilationWarning {
+    /// Get the stable code of this warning
+    pub fn code(&self) -> WarningCode {
+        self.kind.code()
+    }
+}
+
+/// What to do with a warning found during compilation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningLevel {
+    /// The warning is not reported
+    Allow,
+    /// The warning is reported, and the compilation succeeds
+    Warn,
+    /// The warning is reported as a compilation error
+    Deny,
+}
+
+/// The level of each warning code, all the warnings are reported by default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarningLevels([WarningLevel; WarningCode::ALL.len()]);
+
+impl Default for WarningLevels {
+    fn default() -> Self {
+        Self([WarningLevel::Warn; WarningCode::ALL.len()])
+    }
+}
+
+impl WarningLevels {
+    /// Set the level of a warning code
+    pub fn set(&mut self, code: WarningCode, level: WarningLevel) {
+        self.0[code as usize] = level
+    }
+
+    /// Get the level of a warning code
+    pub fn get(&self, code: WarningCode) -> WarningLevel {
+        self.0[code as usize]
+    }
+}
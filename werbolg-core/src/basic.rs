@@ -6,6 +6,7 @@ use core::num::NonZeroUsize;
 /// Note that the ident can contains pretty much anything the frontend wants.
 /// For example, Space or '::' could be inside the ident
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ident(pub String);
 
 impl core::fmt::Debug for Ident {
@@ -37,12 +38,14 @@ impl Ident {
 ///
 /// The path cannot be empty
 #[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Path(PathType, Vec<Ident>);
 
 /// The type of path (Absolute or Relative)
 ///
 /// e.g. `::core::a` vs `core::a`
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PathType {
     /// Absolute path to use to specify according to the toplevel of all modules
     Absolute,
@@ -184,6 +187,7 @@ impl<'a> Iterator for PathDecomposer<'a> {
 ///
 /// The path cannot be empty
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AbsPath(Vec<Ident>);
 
 impl core::fmt::Debug for AbsPath {
@@ -235,6 +239,7 @@ impl AbsPath {
 
 /// A namespace specifier
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Namespace(Vec<Ident>);
 
 impl Namespace {
@@ -243,11 +248,21 @@ impl Namespace {
         Namespace(vec![])
     }
 
+    /// Create a namespace from all the components of a path
+    pub fn from_path(path: &Path) -> Self {
+        Namespace(path.1.clone())
+    }
+
     /// Check if this is the root namespace
     pub fn is_root(&self) -> bool {
         self.0.is_empty()
     }
 
+    /// Check if this namespace is the other namespace or one of its descendants
+    pub fn is_within(&self, other: &Namespace) -> bool {
+        self.0.starts_with(&other.0)
+    }
+
     /// Append a namespace to this namespace and create a new namespace
     pub fn append(mut self, ident: Ident) -> Self {
         self.0.push(ident);
@@ -319,6 +334,7 @@ impl Namespace {
 
 /// Core Literal
 #[derive(Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Literal {
     /// Bool
     Bool(Box<str>),
@@ -32,6 +32,7 @@ macro_rules! define_id_remapper {
     ($constr:ident, $bt:ident, $n:literal, $c:expr) => {
         /// Id
         #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $constr($bt);
 
         impl IdF for $constr {
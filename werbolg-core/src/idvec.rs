@@ -7,6 +7,8 @@ use core::marker::PhantomData;
 /// A Vector Indexed by a specific ID
 ///
 /// Note that it can be dereferenced using the array syntax `idec[id]`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct IdVec<ID, T> {
     vec: Vec<T>,
     phantom: PhantomData<ID>,
@@ -16,6 +16,7 @@ use alloc::{boxed::Box, vec::Vec};
 
 /// AST for a module / source code unit
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Module {
     /// Statement in this module
     pub statements: Vec<Statement>,
@@ -27,24 +28,32 @@ pub struct Module {
 ///
 /// * Use statement for namespace manipulation
 /// * Function definition
+/// * Value definition
 /// * Struct definition
+/// * Enum definition
 /// * Naked expression
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Statement {
     /// Use statement
-    Use(Use),
+    Use(Span, Use),
     /// Function definition
     Function(Span, FunDef, FunImpl),
+    /// Value definition, evaluated when the module is initialised
+    Value(Span, ValueDef, Expr),
     /// Struct definition
     Struct(Span, StructDef),
-    /// A naked Expression
+    /// Enum definition
+    Enum(Span, EnumDef),
+    /// A naked Expression, evaluated when the module is initialised
     Expr(Expr),
 }
 
 /// AST Use/Import
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Use {
-    /// the name of the namespace to import
+    /// the name of the namespace to import, always starting from the root namespace
     pub namespace: Path,
     /// hiding of symbols
     pub hiding: Vec<Ident>,
@@ -53,11 +62,12 @@ pub struct Use {
 }
 
 /// AST for symbol privacy (public / private)
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Privacy {
-    /// Public privacy allow to define a function that will be reachable by other modules
+    /// Public privacy allow to define a symbol that will be reachable by other modules
     Public,
-    /// Private privacy keeps the function not reachable to other modules
+    /// Private privacy keeps the symbol not reachable to other modules
     Private,
 }
 
@@ -70,6 +80,7 @@ pub enum Privacy {
 /// ```
 ///
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunDef {
     /// The privacy associated with this function definition
     pub privacy: Privacy,
@@ -77,6 +88,23 @@ pub struct FunDef {
     pub name: Ident,
 }
 
+/// AST for value definition
+///
+/// Value definitions are something like:
+///
+/// ```text
+/// [pub] value $name = $expr
+/// ```
+///
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValueDef {
+    /// The privacy associated with this value definition
+    pub privacy: Privacy,
+    /// The name of this value
+    pub name: Ident,
+}
+
 /// AST for function implementation
 ///
 /// Function implementation are the variable + body of a function
@@ -92,6 +120,7 @@ pub struct FunDef {
 /// ```
 ///
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunImpl {
     /// The function parameters associated with this function
     pub vars: Vec<Variable>,
@@ -104,11 +133,16 @@ pub struct FunImpl {
 /// Structure definitions are something like
 ///
 /// ```text
-/// struct $name { $fields }
+/// [pub] struct $name { $fields }
 /// ```
 ///
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StructDef {
+    /// The privacy associated with this structure definition
+    ///
+    /// For an enumeration variant, this is ignored and the privacy of the enumeration is used
+    pub privacy: Privacy,
     /// Name of the structure
     pub name: Spanned<Ident>,
     /// Fields of the structure
@@ -120,11 +154,14 @@ pub struct StructDef {
 /// Enum definitions are something like
 ///
 /// ```text
-/// enum $name { $variants }
+/// [pub] enum $name { $variants }
 /// ```
 ///
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnumDef {
+    /// The privacy associated with this enumeration definition, and all its variants
+    pub privacy: Privacy,
     /// Name of the enumeration
     pub name: Spanned<Ident>,
     /// Variants for this enumeration
@@ -132,11 +169,16 @@ pub struct EnumDef {
 }
 
 /// Define a variant for a enumeration
+///
+/// A variant has the same shape as a structure, and is named by the name
+/// of its structure definition
 #[derive(Clone, Debug)]
-pub struct Variant(StructDef);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Variant(pub StructDef);
 
 /// A pattern "matching" for a let
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Binder {
     /// equivalent of `let () = ...`
     Unit,
@@ -146,10 +188,16 @@ pub enum Binder {
     Ident(Ident),
     /// equivalent of `let <path> (<sequential-binder>) = ...` or `let <path> {<named-binder>} = ...`
     Deconstruct(Path, FieldsBinder),
+    /// equivalent of `let ($binder, $binder, ...) = ...`, binding the elements of a tuple by position
+    ///
+    /// The tuple bound need to have the same number of elements as the binder, which is
+    /// checked at compilation for a tuple expression, and otherwise at execution
+    Tuple(Vec<Binder>),
 }
 
 /// Fields to bind inside a struct or enum
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FieldsBinder {
     /// Sequential fields binder, where the fields are bind sequentially in order of declaration
     Sequential(Vec<Binder>, BindEllipsis),
@@ -159,6 +207,7 @@ pub enum FieldsBinder {
 
 /// Whether to only allow partially bind for struct/enum
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BindEllipsis {
     /// Expect all the fields of a struct/enum to be matched
     No,
@@ -166,8 +215,51 @@ pub enum BindEllipsis {
     Yes,
 }
 
+/// A pattern to match a value against in a match arm
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Pattern {
+    /// Literal pattern, e.g. `1` or `"abc"`, matching a value equal to the literal
+    Literal(Span, Literal),
+    /// Binder pattern, e.g. `_`, `x` or `Shape::Rect { w, .. }`
+    Binder(Binder),
+}
+
+/// An arm of a match expression of the form `$pattern => $expr`
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchArm {
+    /// Span of the arm
+    pub span: Span,
+    /// Pattern to match the value against
+    pub pattern: Pattern,
+    /// Expression to evaluate when the pattern is matching
+    pub expr: Expr,
+}
+
+/// Fields values of a structure construction
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FieldsExpr {
+    /// Sequential fields, where the values are given in order of declaration
+    Sequential(Vec<Expr>),
+    /// Named fields, where the values are given by field name in any order
+    Named(Vec<(Spanned<Ident>, Expr)>),
+}
+
+/// Index of an element in a container
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IndexExpr {
+    /// Index known by the frontend, e.g. `(nth a 2)`
+    Static(u32),
+    /// Index computed by an expression, e.g. `a[i]`
+    Dynamic(Box<Expr>),
+}
+
 /// Expression
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr {
     /// Literal, e.g. 1, or "abc"
     Literal(Span, Literal),
@@ -180,14 +272,33 @@ pub enum Expr {
     /// either by disambiguating at the frontend level by adding explicit struct name
     /// or by other methods
     Field(Box<Expr>, Spanned<Path>, Spanned<Ident>),
+    /// Structure construction, e.g. `$struct-name(a, b)` or `$struct-name { x: a, y: b }`
+    ///
+    /// The path can also name an enumeration variant, e.g. `Shape::Rect { w: 1, h: 2 }`
+    Struct(Span, Spanned<Path>, FieldsExpr),
     /// A Sequence of expressions
     Sequence(Span, Vec<Expr>),
+    /// A Tuple of expressions, e.g. `(a, b)`
+    Tuple(Span, Vec<Expr>),
+    /// Access an element of a container (e.g. a sequence) by its index, e.g. `a[i]`
+    Index(Span, Box<Expr>, IndexExpr),
     /// A Let binding of the form `let $binder = $expr in $expr`
     Let(Binder, Box<Expr>, Box<Expr>),
     /// An anonymous function definition expression, e.g. `|a| ...` or `\x -> ...`
     Lambda(Span, Box<FunImpl>),
     /// A function call, e.g. `print("hello", "werbolg")`
     Call(Span, Vec<Expr>),
+    /// A Match expression `match $expr { $pattern => $expr, ... }`
+    ///
+    /// The arms are tried in order, and the first arm matching is evaluated
+    Match {
+        /// Span of the match
+        span: Span,
+        /// Expression to match against
+        expr: Box<Expr>,
+        /// Arms of the match
+        arms: Vec<MatchArm>,
+    },
     /// An If expression `if $cond { $then_expr } else { $else_expr }`
     If {
         /// Span of the if
@@ -199,8 +310,81 @@ pub enum Expr {
         /// Else expression, to run if the conditional does not hold
         else_expr: Box<Spanned<Expr>>,
     },
+    /// An infinite loop `loop { $body }`, only exited by a `break`
+    ///
+    /// The value of the loop is the value given to the `break`
+    Loop(Span, Box<Expr>),
+    /// A conditional loop `while $cond { $body }`
+    ///
+    /// The value of the loop is the value given to the `break`, or a dummy value
+    /// when the loop ends because the condition doesn't hold anymore
+    While {
+        /// Span of the while
+        span: Span,
+        /// Condition expression, evaluated before each iteration
+        cond: Box<Spanned<Expr>>,
+        /// Body of the loop
+        body: Box<Spanned<Expr>>,
+    },
+    /// Exit the innermost loop, with an optional value for the loop
+    Break(Span, Option<Box<Expr>>),
+    /// Skip to the next iteration of the innermost loop
+    Continue(Span),
+    /// Return early from the function, with an optional value
+    Return(Span, Option<Box<Expr>>),
+    /// Assign a new value to a local variable `$ident = $expr`
+    ///
+    /// The value of the assignment is a dummy value
+    Assign(Span, Spanned<Ident>, Box<Expr>),
+    /// Create a mutable reference cell containing the value of the expression
+    ///
+    /// The cell is shared by all the copies of the reference (e.g. captured by closures)
+    RefNew(Span, Box<Expr>),
+    /// Get the value contained in a reference cell
+    RefGet(Span, Box<Expr>),
+    /// Replace the value contained in a reference cell (the first expression) by the
+    /// value of the second expression
+    ///
+    /// The value of the update is a dummy value
+    RefSet(Span, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Get the span of this expression
+    ///
+    /// For the expressions without a span of their own (e.g. `Let`), the span
+    /// covers all the inner expressions
+    pub fn span(&self) -> Span {
+        fn cover(start: Span, end: Span) -> Span {
+            core::cmp::min(start.start, end.start)..core::cmp::max(start.end, end.end)
+        }
+        match self {
+            Expr::Literal(span, _)
+            | Expr::Path(span, _)
+            | Expr::Struct(span, _, _)
+            | Expr::Sequence(span, _)
+            | Expr::Tuple(span, _)
+            | Expr::Index(span, _, _)
+            | Expr::Lambda(span, _)
+            | Expr::Call(span, _)
+            | Expr::Match { span, .. }
+            | Expr::If { span, .. }
+            | Expr::Loop(span, _)
+            | Expr::While { span, .. }
+            | Expr::Break(span, _)
+            | Expr::Continue(span)
+            | Expr::Return(span, _)
+            | Expr::Assign(span, _, _)
+            | Expr::RefNew(span, _)
+            | Expr::RefGet(span, _)
+            | Expr::RefSet(span, _, _) => span.clone(),
+            Expr::Field(expr, _, field) => cover(expr.span(), field.span.clone()),
+            Expr::Let(_, body, in_expr) => cover(body.span(), in_expr.span()),
+        }
+    }
 }
 
 /// A variable (function parameter)
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Variable(pub Spanned<Ident>);
@@ -10,6 +10,9 @@ pub mod id;
 pub mod idvec;
 pub mod ir;
 mod location;
+pub mod text;
+pub mod visit;
+pub mod visit_mut;
 
 pub use basic::*;
 pub use id::{ConstrId, FunId, GlobalId, LitId, NifId, ValueFun};
@@ -41,6 +41,7 @@ where
 /// The Eq instance of Span, doesn't check that the span are equal,
 /// for explicit checking using `span_eq`
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spanned<T> {
     /// The span of T
     pub span: Span,
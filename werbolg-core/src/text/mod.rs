@@ -0,0 +1,57 @@
+//! Textual format of the IR
+//!
+//! The IR is written as s-expressions, with each node written as a list starting with
+//! a keyword, e.g. `(call @10..16 f@10..11 (num @12..13 1))`. All the spans are kept
+//! (written as `@start..end`), so that printing a module and parsing it back give the
+//! exact same module.
+//!
+//! The format is made to write golden files for the frontends, or to write IR by hand:
+//!
+//! ```text
+//! ; a function returning the first element of a pair
+//! (fun @0..30 pub first (p@10..11)
+//!   (let (tuple a (ignore)) p@20..21 a@25..26))
+//! ```
+//!
+//! The words (idents, path components and literal contents) are written as is when
+//! they only contain alphanumeric characters or some symbols (e.g. `+`, `_`, `.`), and
+//! quoted otherwise (e.g. `"hello world"`). A path is written with its components
+//! separated by `::`, and starting with `::` when it's absolute. A `;` starts a comment
+//! up to the end of the line.
+
+mod parse;
+mod print;
+
+pub use parse::{TextError, TextErrorKind, parse_expr, parse_module, parse_statement};
+pub use print::{write_expr, write_module, write_statement};
+
+use alloc::string::String;
+
+/// Check if a character can be written in a word without quoting
+fn is_plain(c: char) -> bool {
+    c.is_alphanumeric() || "_+-*/<>=!?.%&|^~'$".contains(c)
+}
+
+/// Write a word, quoting it if it contains any character that is not plain
+fn word(out: &mut String, s: &str) {
+    if !s.is_empty() && s.chars().all(is_plain) {
+        out.push_str(s);
+        return;
+    }
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                use core::fmt::Write;
+                write!(out, "\\u{{{:x}}}", c as u32).expect("writing to string work")
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
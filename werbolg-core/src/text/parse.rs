@@ -0,0 +1,760 @@
+//! Parser of the textual format into the IR
+
+use super::is_plain;
+use crate::basic::*;
+use crate::ir::*;
+use crate::location::*;
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+/// Error while parsing the textual format
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextError {
+    /// Location of the error in the text
+    pub location: Span,
+    /// The kind of error
+    pub kind: TextErrorKind,
+}
+
+/// Kind of error while parsing the textual format
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextErrorKind {
+    /// A character that cannot start a token
+    UnexpectedChar(char),
+    /// A quoted word without its closing quote
+    UnterminatedString,
+    /// An invalid escape sequence in a quoted word
+    InvalidEscape,
+    /// A span that is not of the form `@start..end`
+    InvalidSpan,
+    /// A list closed without being opened, or not closed
+    UnbalancedList,
+    /// Something else than expected, e.g. a list instead of an ident
+    Expected(&'static str),
+    /// A list starting with a keyword that is not known in this position
+    UnknownKeyword(String),
+    /// Some elements after the last element expected in a list
+    TrailingElements,
+}
+
+/// Parse a module written in the textual format
+pub fn parse_module(text: &str) -> Result<Module, TextError> {
+    let nodes = nodes(text)?;
+    let statements = nodes.iter().map(statement).collect::<Result<_, _>>()?;
+    Ok(Module { statements })
+}
+
+/// Parse a single statement written in the textual format
+pub fn parse_statement(text: &str) -> Result<Statement, TextError> {
+    let nodes = nodes(text)?;
+    single(text, &nodes).and_then(statement)
+}
+
+/// Parse a single expression written in the textual format
+pub fn parse_expr(text: &str) -> Result<Expr, TextError> {
+    let nodes = nodes(text)?;
+    let node = single(text, &nodes)?;
+    let mut items = Items::new(node.location(), core::slice::from_ref(node));
+    let e = items.expr()?;
+    items.end()?;
+    Ok(e)
+}
+
+fn single<'a>(text: &str, nodes: &'a [Node]) -> Result<&'a Node, TextError> {
+    match nodes {
+        [node] => Ok(node),
+        [] => Err(TextError {
+            location: text.len()..text.len(),
+            kind: TextErrorKind::Expected("an element"),
+        }),
+        [_, node, ..] => Err(TextError {
+            location: node.location(),
+            kind: TextErrorKind::TrailingElements,
+        }),
+    }
+}
+
+/// A word, possibly a path of multiple components
+struct Word {
+    absolute: bool,
+    components: Vec<String>,
+}
+
+/// Tree of the tokens, with their location in the text
+enum Node {
+    Word(Span, Word),
+    At(Span, Span),
+    List(Span, Vec<Node>),
+}
+
+impl Node {
+    fn location(&self) -> Span {
+        match self {
+            Node::Word(location, _) | Node::At(location, _) | Node::List(location, _) => {
+                location.clone()
+            }
+        }
+    }
+}
+
+/// Split the text in nodes
+fn nodes(text: &str) -> Result<Vec<Node>, TextError> {
+    let mut lexer = Lexer { text, pos: 0 };
+    // the lists being built, with the location of their opening
+    let mut stack: Vec<(usize, Vec<Node>)> = Vec::new();
+    let mut top = Vec::new();
+    loop {
+        lexer.skip_blanks();
+        let start = lexer.pos;
+        let Some(c) = lexer.peek() else {
+            break;
+        };
+        let node = match c {
+            '(' => {
+                lexer.bump();
+                stack.push((start, core::mem::take(&mut top)));
+                continue;
+            }
+            ')' => {
+                lexer.bump();
+                let Some((list_start, parent)) = stack.pop() else {
+                    return Err(lexer.error(start, TextErrorKind::UnbalancedList));
+                };
+                let elements = core::mem::replace(&mut top, parent);
+                Node::List(list_start..lexer.pos, elements)
+            }
+            '@' => {
+                lexer.bump();
+                let span = lexer.span(start)?;
+                Node::At(start..lexer.pos, span)
+            }
+            _ => {
+                let word = lexer.word(start)?;
+                Node::Word(start..lexer.pos, word)
+            }
+        };
+        top.push(node)
+    }
+    if let Some((list_start, _)) = stack.pop() {
+        return Err(lexer.error(list_start, TextErrorKind::UnbalancedList));
+    }
+    Ok(top)
+}
+
+struct Lexer<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, start: usize, kind: TextErrorKind) -> TextError {
+        TextError {
+            location: start..core::cmp::max(start, self.pos),
+            kind,
+        }
+    }
+
+    /// Skip the whitespaces and the comments
+    fn skip_blanks(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == ';' {
+                while let Some(c) = self.bump() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+        }
+        self.text[start..self.pos].parse().ok()
+    }
+
+    /// Parse the `start..end` of a span, after the `@`
+    fn span(&mut self, start: usize) -> Result<Span, TextError> {
+        let span_start = self.number();
+        let dots = self.text[self.pos..].starts_with("..");
+        if dots {
+            self.pos += 2;
+        }
+        let span_end = self.number();
+        match (span_start, dots, span_end) {
+            (Some(span_start), true, Some(span_end)) => Ok(span_start..span_end),
+            _ => Err(self.error(start, TextErrorKind::InvalidSpan)),
+        }
+    }
+
+    /// Parse a word, or a path with its components separated by `::`
+    fn word(&mut self, start: usize) -> Result<Word, TextError> {
+        let absolute = self.text[self.pos..].starts_with("::");
+        if absolute {
+            self.pos += 2;
+        }
+        let mut components = Vec::new();
+        loop {
+            let component = match self.peek() {
+                Some('"') => self.quoted(self.pos)?,
+                Some(c) if is_plain(c) => {
+                    let component_start = self.pos;
+                    while self.peek().is_some_and(is_plain) {
+                        self.bump();
+                    }
+                    String::from(&self.text[component_start..self.pos])
+                }
+                Some(c) => return Err(self.error(start, TextErrorKind::UnexpectedChar(c))),
+                None => return Err(self.error(start, TextErrorKind::Expected("a word"))),
+            };
+            components.push(component);
+            if self.text[self.pos..].starts_with("::") {
+                self.pos += 2;
+            } else {
+                break;
+            }
+        }
+        Ok(Word {
+            absolute,
+            components,
+        })
+    }
+
+    fn quoted(&mut self, start: usize) -> Result<String, TextError> {
+        self.bump();
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.error(start, TextErrorKind::UnterminatedString)),
+                Some('"') => return Ok(out),
+                Some('\\') => {
+                    let escape_start = self.pos - 1;
+                    let c = match self.bump() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape(escape_start)?,
+                        _ => return Err(self.error(escape_start, TextErrorKind::InvalidEscape)),
+                    };
+                    out.push(c)
+                }
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    /// Parse the `{hex}` of a `\u{hex}` escape
+    fn unicode_escape(&mut self, start: usize) -> Result<char, TextError> {
+        if self.bump() != Some('{') {
+            return Err(self.error(start, TextErrorKind::InvalidEscape));
+        }
+        let hex_start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+            self.bump();
+        }
+        let hex = &self.text[hex_start..self.pos];
+        if self.bump() != Some('}') {
+            return Err(self.error(start, TextErrorKind::InvalidEscape));
+        }
+        u32::from_str_radix(hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error(start, TextErrorKind::InvalidEscape))
+    }
+}
+
+/// Cursor over the elements of a list
+struct Items<'a> {
+    location: Span,
+    nodes: &'a [Node],
+}
+
+impl<'a> Items<'a> {
+    fn new(location: Span, nodes: &'a [Node]) -> Self {
+        Self { location, nodes }
+    }
+
+    fn error(&self, kind: TextErrorKind) -> TextError {
+        let location = match self.nodes.first() {
+            Some(node) => node.location(),
+            None => self.location.end.saturating_sub(1)..self.location.end,
+        };
+        TextError { location, kind }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn peek(&self) -> Option<&'a Node> {
+        self.nodes.first()
+    }
+
+    /// Check that all the elements have been consumed
+    fn end(&self) -> Result<(), TextError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error(TextErrorKind::TrailingElements))
+        }
+    }
+
+    fn word(&mut self, expected: &'static str) -> Result<&'a Word, TextError> {
+        match self.peek() {
+            Some(Node::Word(_, word)) => {
+                self.nodes = &self.nodes[1..];
+                Ok(word)
+            }
+            _ => Err(self.error(TextErrorKind::Expected(expected))),
+        }
+    }
+
+    /// A word made of a single component
+    fn simple_word(&mut self, expected: &'static str) -> Result<&'a str, TextError> {
+        let location = self.peek().map(|node| node.location());
+        let word = self.word(expected)?;
+        match word.components.as_slice() {
+            [component] if !word.absolute => Ok(component),
+            _ => Err(TextError {
+                location: location.unwrap_or(self.location.clone()),
+                kind: TextErrorKind::Expected(expected),
+            }),
+        }
+    }
+
+    fn ident(&mut self) -> Result<Ident, TextError> {
+        self.simple_word("an ident").map(Ident::from)
+    }
+
+    fn path(&mut self) -> Result<Path, TextError> {
+        let word = self.word("a path")?;
+        let path_type = if word.absolute {
+            PathType::Absolute
+        } else {
+            PathType::Relative
+        };
+        let idents = word
+            .components
+            .iter()
+            .map(|c| Ident::from(c.as_str()))
+            .collect();
+        Ok(Path::new_raw(path_type, idents))
+    }
+
+    fn span(&mut self) -> Result<Span, TextError> {
+        match self.peek() {
+            Some(Node::At(_, span)) => {
+                self.nodes = &self.nodes[1..];
+                Ok(span.clone())
+            }
+            _ => Err(self.error(TextErrorKind::Expected("a span"))),
+        }
+    }
+
+    fn spanned_ident(&mut self) -> Result<Spanned<Ident>, TextError> {
+        let ident = self.ident()?;
+        let span = self.span()?;
+        Ok(Spanned::new(span, ident))
+    }
+
+    fn spanned_path(&mut self) -> Result<Spanned<Path>, TextError> {
+        let path = self.path()?;
+        let span = self.span()?;
+        Ok(Spanned::new(span, path))
+    }
+
+    /// A list, returning a cursor on its elements
+    fn list(&mut self, expected: &'static str) -> Result<Items<'a>, TextError> {
+        match self.peek() {
+            Some(Node::List(location, nodes)) => {
+                self.nodes = &self.nodes[1..];
+                Ok(Items::new(location.clone(), nodes))
+            }
+            _ => Err(self.error(TextErrorKind::Expected(expected))),
+        }
+    }
+
+    /// A list starting with a keyword, returning the keyword and a cursor on the other elements
+    fn form(&mut self, expected: &'static str) -> Result<(&'a str, Items<'a>), TextError> {
+        let mut items = self.list(expected)?;
+        let keyword = items.simple_word("a keyword")?;
+        Ok((keyword, items))
+    }
+
+    fn privacy(&mut self) -> Result<Privacy, TextError> {
+        match self.simple_word("a privacy")? {
+            "pub" => Ok(Privacy::Public),
+            "priv" => Ok(Privacy::Private),
+            _ => Err(TextError {
+                location: self.location.clone(),
+                kind: TextErrorKind::Expected("`pub` or `priv`"),
+            }),
+        }
+    }
+
+    fn params(&mut self) -> Result<Vec<Variable>, TextError> {
+        let mut items = self.list("a list of parameters")?;
+        let mut vars = Vec::new();
+        while !items.is_empty() {
+            vars.push(Variable(items.spanned_ident()?))
+        }
+        Ok(vars)
+    }
+
+    fn structdef(&mut self) -> Result<StructDef, TextError> {
+        let privacy = self.privacy()?;
+        let name = self.spanned_ident()?;
+        let mut items = self.list("a list of fields")?;
+        let mut fields = Vec::new();
+        while !items.is_empty() {
+            fields.push(items.spanned_ident()?)
+        }
+        Ok(StructDef {
+            privacy,
+            name,
+            fields,
+        })
+    }
+
+    fn exprs(&mut self) -> Result<Vec<Expr>, TextError> {
+        let mut exprs = Vec::new();
+        while !self.is_empty() {
+            exprs.push(self.expr()?)
+        }
+        Ok(exprs)
+    }
+
+    fn boxed_expr(&mut self) -> Result<Box<Expr>, TextError> {
+        self.expr().map(Box::new)
+    }
+
+    fn optional_expr(&mut self) -> Result<Option<Box<Expr>>, TextError> {
+        if self.is_empty() {
+            Ok(None)
+        } else {
+            self.boxed_expr().map(Some)
+        }
+    }
+
+    fn spanned_expr(&mut self) -> Result<Box<Spanned<Expr>>, TextError> {
+        let span = self.span()?;
+        let expr = self.expr()?;
+        Ok(Box::new(Spanned::new(span, expr)))
+    }
+
+    fn literal(&mut self, keyword: &str) -> Result<Literal, TextError> {
+        let content = self.simple_word("a literal")?;
+        let literal = match keyword {
+            "bool" => Literal::Bool(content.into()),
+            "str" => Literal::String(content.into()),
+            "num" => Literal::Number(content.into()),
+            "dec" => Literal::Decimal(content.into()),
+            "bytes" => {
+                let digits = content.as_bytes();
+                if digits.len() % 2 != 0 {
+                    return Err(self.error(TextErrorKind::Expected("hexadecimal bytes")));
+                }
+                let bytes = digits
+                    .chunks(2)
+                    .map(|pair| {
+                        core::str::from_utf8(pair)
+                            .ok()
+                            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    })
+                    .collect::<Option<Vec<u8>>>()
+                    .ok_or_else(|| self.error(TextErrorKind::Expected("hexadecimal bytes")))?;
+                Literal::Bytes(bytes.into_boxed_slice())
+            }
+            _ => unreachable!(),
+        };
+        Ok(literal)
+    }
+
+    fn binder(&mut self) -> Result<Binder, TextError> {
+        if let Some(Node::Word(_, _)) = self.peek() {
+            return self.ident().map(Binder::Ident);
+        }
+        let (keyword, mut items) = self.form("a binder")?;
+        let binder = match keyword {
+            "unit" => Binder::Unit,
+            "ignore" => Binder::Ignore,
+            "tuple" => {
+                let mut binders = Vec::new();
+                while !items.is_empty() {
+                    binders.push(items.binder()?)
+                }
+                Binder::Tuple(binders)
+            }
+            "destruct" => {
+                let path = items.path()?;
+                let fields = match items.simple_word("`seq` or `named`")? {
+                    "seq" => FieldsBinder::Sequential(items.binders()?, BindEllipsis::No),
+                    "seq.." => FieldsBinder::Sequential(items.binders()?, BindEllipsis::Yes),
+                    "named" => FieldsBinder::Named(items.named_binders()?, BindEllipsis::No),
+                    "named.." => FieldsBinder::Named(items.named_binders()?, BindEllipsis::Yes),
+                    _ => return Err(items.error(TextErrorKind::Expected("`seq` or `named`"))),
+                };
+                Binder::Deconstruct(path, fields)
+            }
+            _ => return Err(unknown_keyword(&items, keyword)),
+        };
+        items.end()?;
+        Ok(binder)
+    }
+
+    fn binders(&mut self) -> Result<Vec<Binder>, TextError> {
+        let mut binders = Vec::new();
+        while !self.is_empty() {
+            binders.push(self.binder()?)
+        }
+        Ok(binders)
+    }
+
+    fn named_binders(&mut self) -> Result<Vec<(Ident, Binder)>, TextError> {
+        let mut binders = Vec::new();
+        while !self.is_empty() {
+            let mut items = self.list("a named field binder")?;
+            let field = items.ident()?;
+            let binder = items.binder()?;
+            items.end()?;
+            binders.push((field, binder))
+        }
+        Ok(binders)
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, TextError> {
+        if let Some(Node::List(_, nodes)) = self.peek()
+            && let Some(Node::Word(_, word)) = nodes.first()
+            && let [keyword] = word.components.as_slice()
+            && is_literal_keyword(keyword)
+            && !word.absolute
+        {
+            let (keyword, mut items) = self.form("a literal")?;
+            let span = items.span()?;
+            let literal = items.literal(keyword)?;
+            items.end()?;
+            return Ok(Pattern::Literal(span, literal));
+        }
+        self.binder().map(Pattern::Binder)
+    }
+
+    fn expr(&mut self) -> Result<Expr, TextError> {
+        if let Some(Node::Word(_, _)) = self.peek() {
+            let path = self.spanned_path()?;
+            return Ok(Expr::Path(path.span, path.inner));
+        }
+        let (keyword, mut items) = self.form("an expression")?;
+        let expr = match keyword {
+            k if is_literal_keyword(k) => {
+                let span = items.span()?;
+                Expr::Literal(span, items.literal(k)?)
+            }
+            "field" => {
+                let expr = items.boxed_expr()?;
+                let struct_path = items.spanned_path()?;
+                let field = items.spanned_ident()?;
+                Expr::Field(expr, struct_path, field)
+            }
+            "make" => {
+                let span = items.span()?;
+                let struct_path = items.spanned_path()?;
+                Expr::Struct(span, struct_path, FieldsExpr::Sequential(items.exprs()?))
+            }
+            "make-named" => {
+                let span = items.span()?;
+                let struct_path = items.spanned_path()?;
+                let mut fields = Vec::new();
+                while !items.is_empty() {
+                    let mut field_items = items.list("a named field")?;
+                    let field = field_items.spanned_ident()?;
+                    let expr = field_items.expr()?;
+                    field_items.end()?;
+                    fields.push((field, expr))
+                }
+                Expr::Struct(span, struct_path, FieldsExpr::Named(fields))
+            }
+            "seq" => Expr::Sequence(items.span()?, items.exprs()?),
+            "tuple" => Expr::Tuple(items.span()?, items.exprs()?),
+            "call" => Expr::Call(items.span()?, items.exprs()?),
+            "nth" => {
+                let span = items.span()?;
+                let expr = items.boxed_expr()?;
+                let index = items
+                    .simple_word("a static index")?
+                    .parse()
+                    .map_err(|_| items.error(TextErrorKind::Expected("a static index")))?;
+                Expr::Index(span, expr, IndexExpr::Static(index))
+            }
+            "index" => {
+                let span = items.span()?;
+                let expr = items.boxed_expr()?;
+                let index = items.boxed_expr()?;
+                Expr::Index(span, expr, IndexExpr::Dynamic(index))
+            }
+            "let" => {
+                let binder = items.binder()?;
+                let body = items.boxed_expr()?;
+                let in_expr = items.boxed_expr()?;
+                Expr::Let(binder, body, in_expr)
+            }
+            "lambda" => {
+                let span = items.span()?;
+                let vars = items.params()?;
+                let body = items.expr()?;
+                Expr::Lambda(span, Box::new(FunImpl { vars, body }))
+            }
+            "match" => {
+                let span = items.span()?;
+                let expr = items.boxed_expr()?;
+                let mut arms = Vec::new();
+                while !items.is_empty() {
+                    let (keyword, mut arm_items) = items.form("a match arm")?;
+                    if keyword != "arm" {
+                        return Err(unknown_keyword(&arm_items, keyword));
+                    }
+                    let span = arm_items.span()?;
+                    let pattern = arm_items.pattern()?;
+                    let expr = arm_items.expr()?;
+                    arm_items.end()?;
+                    arms.push(MatchArm {
+                        span,
+                        pattern,
+                        expr,
+                    })
+                }
+                Expr::Match { span, expr, arms }
+            }
+            "if" => Expr::If {
+                span: items.span()?,
+                cond: items.spanned_expr()?,
+                then_expr: items.spanned_expr()?,
+                else_expr: items.spanned_expr()?,
+            },
+            "loop" => Expr::Loop(items.span()?, items.boxed_expr()?),
+            "while" => Expr::While {
+                span: items.span()?,
+                cond: items.spanned_expr()?,
+                body: items.spanned_expr()?,
+            },
+            "break" => Expr::Break(items.span()?, items.optional_expr()?),
+            "continue" => Expr::Continue(items.span()?),
+            "return" => Expr::Return(items.span()?, items.optional_expr()?),
+            "assign" => Expr::Assign(items.span()?, items.spanned_ident()?, items.boxed_expr()?),
+            "ref" => Expr::RefNew(items.span()?, items.boxed_expr()?),
+            "deref" => Expr::RefGet(items.span()?, items.boxed_expr()?),
+            "ref-set" => Expr::RefSet(items.span()?, items.boxed_expr()?, items.boxed_expr()?),
+            _ => return Err(unknown_keyword(&items, keyword)),
+        };
+        items.end()?;
+        Ok(expr)
+    }
+}
+
+fn is_literal_keyword(keyword: &str) -> bool {
+    matches!(keyword, "bool" | "str" | "num" | "dec" | "bytes")
+}
+
+fn unknown_keyword(items: &Items, keyword: &str) -> TextError {
+    TextError {
+        location: items.location.clone(),
+        kind: TextErrorKind::UnknownKeyword(String::from(keyword)),
+    }
+}
+
+fn statement(node: &Node) -> Result<Statement, TextError> {
+    let mut top = Items::new(node.location(), core::slice::from_ref(node));
+    let (keyword, mut items) = top.form("a statement")?;
+    let statement = match keyword {
+        "use" => {
+            let span = items.span()?;
+            let namespace = items.path()?;
+            let (keyword, mut hiding_items) = items.form("a hiding list")?;
+            if keyword != "hiding" {
+                return Err(unknown_keyword(&hiding_items, keyword));
+            }
+            let mut hiding = Vec::new();
+            while !hiding_items.is_empty() {
+                hiding.push(hiding_items.ident()?)
+            }
+            let (keyword, mut rename_items) = items.form("a rename list")?;
+            if keyword != "rename" {
+                return Err(unknown_keyword(&rename_items, keyword));
+            }
+            let mut renames = Vec::new();
+            while !rename_items.is_empty() {
+                let mut pair = rename_items.list("a renaming pair")?;
+                let from = pair.ident()?;
+                let to = pair.ident()?;
+                pair.end()?;
+                renames.push((from, to))
+            }
+            Statement::Use(
+                span,
+                Use {
+                    namespace,
+                    hiding,
+                    renames,
+                },
+            )
+        }
+        "fun" => {
+            let span = items.span()?;
+            let privacy = items.privacy()?;
+            let name = items.ident()?;
+            let vars = items.params()?;
+            let body = items.expr()?;
+            Statement::Function(span, FunDef { privacy, name }, FunImpl { vars, body })
+        }
+        "value" => {
+            let span = items.span()?;
+            let privacy = items.privacy()?;
+            let name = items.ident()?;
+            let expr = items.expr()?;
+            Statement::Value(span, ValueDef { privacy, name }, expr)
+        }
+        "struct" => Statement::Struct(items.span()?, items.structdef()?),
+        "enum" => {
+            let span = items.span()?;
+            let privacy = items.privacy()?;
+            let name = items.spanned_ident()?;
+            let mut variants = Vec::new();
+            while !items.is_empty() {
+                let (keyword, mut variant_items) = items.form("a variant")?;
+                if keyword != "variant" {
+                    return Err(unknown_keyword(&variant_items, keyword));
+                }
+                variants.push(Variant(variant_items.structdef()?));
+                variant_items.end()?;
+            }
+            Statement::Enum(
+                span,
+                EnumDef {
+                    privacy,
+                    name,
+                    variants,
+                },
+            )
+        }
+        "expr" => Statement::Expr(items.expr()?),
+        _ => return Err(unknown_keyword(&items, keyword)),
+    };
+    items.end()?;
+    Ok(statement)
+}
@@ -0,0 +1,408 @@
+//! Printer of the IR to the textual format
+
+use super::word;
+use crate::basic::*;
+use crate::ir::*;
+use crate::location::*;
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+use core::fmt::Write;
+
+/// Maximum width of a line before the lists get split on multiple lines
+const WIDTH: usize = 100;
+
+/// Write a module in the textual format, one statement after the other
+pub fn write_module<W: Write>(out: &mut W, module: &Module) -> core::fmt::Result {
+    for statement in module.statements.iter() {
+        write_statement(out, statement)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Write a statement in the textual format
+pub fn write_statement<W: Write>(out: &mut W, statement: &Statement) -> core::fmt::Result {
+    render(out, &statement_doc(statement), 0)
+}
+
+/// Write an expression in the textual format
+pub fn write_expr<W: Write>(out: &mut W, expr: &Expr) -> core::fmt::Result {
+    render(out, &expr_doc(expr), 0)
+}
+
+/// A document to render, either a token or a list of documents
+enum Doc {
+    Atom(String),
+    List(Vec<Doc>),
+    /// A document kept on the first line with the leading tokens of its list (e.g. parameters)
+    Head(Box<Doc>),
+}
+
+impl Doc {
+    fn keyword(keyword: &str) -> Self {
+        Doc::Atom(String::from(keyword))
+    }
+
+    fn flat(&self, out: &mut String) {
+        match self {
+            Doc::Atom(s) => out.push_str(s),
+            Doc::Head(doc) => doc.flat(out),
+            Doc::List(docs) => {
+                out.push('(');
+                for (i, doc) in docs.iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ')
+                    }
+                    doc.flat(out)
+                }
+                out.push(')')
+            }
+        }
+    }
+}
+
+/// Render the document on a single line if it fits, otherwise keep the leading
+/// tokens of the list on the first line and write each other element on its own line,
+/// with a span followed by an element staying on the same line as the element
+fn render<W: Write>(out: &mut W, doc: &Doc, indent: usize) -> core::fmt::Result {
+    let mut flat = String::new();
+    doc.flat(&mut flat);
+    let docs = match doc {
+        Doc::List(docs) if indent + flat.len() > WIDTH => docs,
+        _ => return out.write_str(&flat),
+    };
+
+    let leading = docs
+        .iter()
+        .take_while(|doc| matches!(doc, Doc::Atom(_) | Doc::Head(_)))
+        .count();
+    out.write_char('(')?;
+    for (i, doc) in docs[..leading].iter().enumerate() {
+        if i > 0 {
+            out.write_char(' ')?
+        }
+        render(out, doc, indent)?
+    }
+    let mut tail = docs[leading..].iter().peekable();
+    while let Some(doc) = tail.next() {
+        writeln!(out)?;
+        write!(out, "{:width$}", "", width = indent + 2)?;
+        match doc {
+            Doc::Atom(span) if span.starts_with('@') && tail.peek().is_some() => {
+                write!(out, "{} ", span)?;
+                let next = tail.next().expect("peeked element");
+                render(out, next, indent + 3 + span.len())?
+            }
+            _ => render(out, doc, indent + 2)?,
+        }
+    }
+    out.write_char(')')
+}
+
+fn atom_word(s: &str) -> Doc {
+    let mut out = String::new();
+    word(&mut out, s);
+    Doc::Atom(out)
+}
+
+fn atom_span(span: &Span) -> Doc {
+    Doc::Atom(format!("@{}..{}", span.start, span.end))
+}
+
+fn path_string(path: &Path) -> String {
+    let mut out = String::new();
+    if path.path_type() == PathType::Absolute {
+        out.push_str("::")
+    }
+    for (i, (ident, _)) in path.components().enumerate() {
+        if i > 0 {
+            out.push_str("::")
+        }
+        word(&mut out, &ident.0)
+    }
+    out
+}
+
+fn atom_ident(ident: &Ident) -> Doc {
+    atom_word(&ident.0)
+}
+
+fn atom_spanned_ident(ident: &Spanned<Ident>) -> Doc {
+    let mut out = String::new();
+    word(&mut out, &ident.inner.0);
+    Doc::Atom(format!("{}@{}..{}", out, ident.span.start, ident.span.end))
+}
+
+fn atom_spanned_path(span: &Span, path: &Path) -> Doc {
+    Doc::Atom(format!(
+        "{}@{}..{}",
+        path_string(path),
+        span.start,
+        span.end
+    ))
+}
+
+fn atom_privacy(privacy: Privacy) -> Doc {
+    match privacy {
+        Privacy::Public => Doc::keyword("pub"),
+        Privacy::Private => Doc::keyword("priv"),
+    }
+}
+
+fn statement_doc(statement: &Statement) -> Doc {
+    match statement {
+        Statement::Use(span, use_) => Doc::List(vec![
+            Doc::keyword("use"),
+            atom_span(span),
+            Doc::Atom(path_string(&use_.namespace)),
+            Doc::List(
+                [Doc::keyword("hiding")]
+                    .into_iter()
+                    .chain(use_.hiding.iter().map(atom_ident))
+                    .collect(),
+            ),
+            Doc::List(
+                [Doc::keyword("rename")]
+                    .into_iter()
+                    .chain(
+                        use_.renames
+                            .iter()
+                            .map(|(from, to)| Doc::List(vec![atom_ident(from), atom_ident(to)])),
+                    )
+                    .collect(),
+            ),
+        ]),
+        Statement::Function(span, fundef, funimpl) => Doc::List(vec![
+            Doc::keyword("fun"),
+            atom_span(span),
+            atom_privacy(fundef.privacy),
+            atom_ident(&fundef.name),
+            Doc::Head(Box::new(params_doc(&funimpl.vars))),
+            expr_doc(&funimpl.body),
+        ]),
+        Statement::Value(span, valuedef, expr) => Doc::List(vec![
+            Doc::keyword("value"),
+            atom_span(span),
+            atom_privacy(valuedef.privacy),
+            atom_ident(&valuedef.name),
+            expr_doc(expr),
+        ]),
+        Statement::Struct(span, structdef) => {
+            let mut docs = vec![Doc::keyword("struct"), atom_span(span)];
+            structdef_docs(&mut docs, structdef);
+            Doc::List(docs)
+        }
+        Statement::Enum(span, enumdef) => {
+            let mut docs = vec![
+                Doc::keyword("enum"),
+                atom_span(span),
+                atom_privacy(enumdef.privacy),
+                atom_spanned_ident(&enumdef.name),
+            ];
+            for variant in enumdef.variants.iter() {
+                let mut variant_docs = vec![Doc::keyword("variant")];
+                structdef_docs(&mut variant_docs, &variant.0);
+                docs.push(Doc::List(variant_docs))
+            }
+            Doc::List(docs)
+        }
+        Statement::Expr(expr) => Doc::List(vec![Doc::keyword("expr"), expr_doc(expr)]),
+    }
+}
+
+fn structdef_docs(docs: &mut Vec<Doc>, structdef: &StructDef) {
+    docs.push(atom_privacy(structdef.privacy));
+    docs.push(atom_spanned_ident(&structdef.name));
+    docs.push(Doc::List(
+        structdef.fields.iter().map(atom_spanned_ident).collect(),
+    ));
+}
+
+fn params_doc(vars: &[Variable]) -> Doc {
+    Doc::List(vars.iter().map(|var| atom_spanned_ident(&var.0)).collect())
+}
+
+fn literal_doc(span: &Span, literal: &Literal) -> Doc {
+    let (keyword, content) = match literal {
+        Literal::Bool(s) => ("bool", String::from(s.as_ref())),
+        Literal::String(s) => ("str", String::from(s.as_ref())),
+        Literal::Number(s) => ("num", String::from(s.as_ref())),
+        Literal::Decimal(s) => ("dec", String::from(s.as_ref())),
+        Literal::Bytes(bytes) => {
+            let mut hex = String::new();
+            for b in bytes.iter() {
+                write!(hex, "{:02x}", b).expect("writing to string work");
+            }
+            ("bytes", hex)
+        }
+    };
+    Doc::List(vec![
+        Doc::keyword(keyword),
+        atom_span(span),
+        atom_word(&content),
+    ])
+}
+
+fn binder_doc(binder: &Binder) -> Doc {
+    match binder {
+        Binder::Unit => Doc::List(vec![Doc::keyword("unit")]),
+        Binder::Ignore => Doc::List(vec![Doc::keyword("ignore")]),
+        Binder::Ident(ident) => atom_ident(ident),
+        Binder::Deconstruct(path, fields) => {
+            let (keyword, ellipsis, docs) = match fields {
+                FieldsBinder::Sequential(binders, ellipsis) => {
+                    ("seq", ellipsis, binders.iter().map(binder_doc).collect())
+                }
+                FieldsBinder::Named(fields, ellipsis) => (
+                    "named",
+                    ellipsis,
+                    fields
+                        .iter()
+                        .map(|(field, binder)| {
+                            Doc::List(vec![atom_ident(field), binder_doc(binder)])
+                        })
+                        .collect::<Vec<_>>(),
+                ),
+            };
+            let keyword = match ellipsis {
+                BindEllipsis::No => String::from(keyword),
+                BindEllipsis::Yes => format!("{}..", keyword),
+            };
+            let mut list = vec![
+                Doc::keyword("destruct"),
+                Doc::Atom(path_string(path)),
+                Doc::Atom(keyword),
+            ];
+            list.extend(docs);
+            Doc::List(list)
+        }
+        Binder::Tuple(binders) => Doc::List(
+            [Doc::keyword("tuple")]
+                .into_iter()
+                .chain(binders.iter().map(binder_doc))
+                .collect(),
+        ),
+    }
+}
+
+fn pattern_doc(pattern: &Pattern) -> Doc {
+    match pattern {
+        Pattern::Literal(span, literal) => literal_doc(span, literal),
+        Pattern::Binder(binder) => binder_doc(binder),
+    }
+}
+
+/// A list starting with the keyword and the span, followed by the expressions
+fn exprs_doc<'a>(keyword: &str, span: &Span, exprs: impl Iterator<Item = &'a Expr>) -> Doc {
+    let mut docs = vec![Doc::keyword(keyword), atom_span(span)];
+    docs.extend(exprs.map(expr_doc));
+    Doc::List(docs)
+}
+
+fn spanned_expr_docs(docs: &mut Vec<Doc>, expr: &Spanned<Expr>) {
+    docs.push(atom_span(&expr.span));
+    docs.push(expr_doc(&expr.inner));
+}
+
+fn expr_doc(expr: &Expr) -> Doc {
+    match expr {
+        Expr::Literal(span, literal) => literal_doc(span, literal),
+        Expr::Path(span, path) => atom_spanned_path(span, path),
+        Expr::Field(expr, struct_path, field) => Doc::List(vec![
+            Doc::keyword("field"),
+            expr_doc(expr),
+            atom_spanned_path(&struct_path.span, &struct_path.inner),
+            atom_spanned_ident(field),
+        ]),
+        Expr::Struct(span, struct_path, FieldsExpr::Sequential(exprs)) => {
+            let mut docs = vec![
+                Doc::keyword("make"),
+                atom_span(span),
+                atom_spanned_path(&struct_path.span, &struct_path.inner),
+            ];
+            docs.extend(exprs.iter().map(expr_doc));
+            Doc::List(docs)
+        }
+        Expr::Struct(span, struct_path, FieldsExpr::Named(fields)) => {
+            let mut docs = vec![
+                Doc::keyword("make-named"),
+                atom_span(span),
+                atom_spanned_path(&struct_path.span, &struct_path.inner),
+            ];
+            docs.extend(
+                fields.iter().map(|(field, expr)| {
+                    Doc::List(vec![atom_spanned_ident(field), expr_doc(expr)])
+                }),
+            );
+            Doc::List(docs)
+        }
+        Expr::Sequence(span, exprs) => exprs_doc("seq", span, exprs.iter()),
+        Expr::Tuple(span, exprs) => exprs_doc("tuple", span, exprs.iter()),
+        Expr::Call(span, exprs) => exprs_doc("call", span, exprs.iter()),
+        Expr::Index(span, expr, IndexExpr::Static(index)) => Doc::List(vec![
+            Doc::keyword("nth"),
+            atom_span(span),
+            expr_doc(expr),
+            Doc::Atom(format!("{}", index)),
+        ]),
+        Expr::Index(span, expr, IndexExpr::Dynamic(index)) => {
+            exprs_doc("index", span, [expr.as_ref(), index.as_ref()].into_iter())
+        }
+        Expr::Let(binder, body, in_expr) => Doc::List(vec![
+            Doc::keyword("let"),
+            Doc::Head(Box::new(binder_doc(binder))),
+            expr_doc(body),
+            expr_doc(in_expr),
+        ]),
+        Expr::Lambda(span, funimpl) => Doc::List(vec![
+            Doc::keyword("lambda"),
+            atom_span(span),
+            Doc::Head(Box::new(params_doc(&funimpl.vars))),
+            expr_doc(&funimpl.body),
+        ]),
+        Expr::Match { span, expr, arms } => {
+            let mut docs = vec![Doc::keyword("match"), atom_span(span), expr_doc(expr)];
+            for arm in arms.iter() {
+                docs.push(Doc::List(vec![
+                    Doc::keyword("arm"),
+                    atom_span(&arm.span),
+                    pattern_doc(&arm.pattern),
+                    expr_doc(&arm.expr),
+                ]))
+            }
+            Doc::List(docs)
+        }
+        Expr::If {
+            span,
+            cond,
+            then_expr,
+            else_expr,
+        } => {
+            let mut docs = vec![Doc::keyword("if"), atom_span(span)];
+            spanned_expr_docs(&mut docs, cond);
+            spanned_expr_docs(&mut docs, then_expr);
+            spanned_expr_docs(&mut docs, else_expr);
+            Doc::List(docs)
+        }
+        Expr::Loop(span, body) => exprs_doc("loop", span, [body.as_ref()].into_iter()),
+        Expr::While { span, cond, body } => {
+            let mut docs = vec![Doc::keyword("while"), atom_span(span)];
+            spanned_expr_docs(&mut docs, cond);
+            spanned_expr_docs(&mut docs, body);
+            Doc::List(docs)
+        }
+        Expr::Break(span, expr) => exprs_doc("break", span, expr.iter().map(|e| e.as_ref())),
+        Expr::Continue(span) => exprs_doc("continue", span, [].into_iter()),
+        Expr::Return(span, expr) => exprs_doc("return", span, expr.iter().map(|e| e.as_ref())),
+        Expr::Assign(span, ident, expr) => Doc::List(vec![
+            Doc::keyword("assign"),
+            atom_span(span),
+            atom_spanned_ident(ident),
+            expr_doc(expr),
+        ]),
+        Expr::RefNew(span, expr) => exprs_doc("ref", span, [expr.as_ref()].into_iter()),
+        Expr::RefGet(span, expr) => exprs_doc("deref", span, [expr.as_ref()].into_iter()),
+        Expr::RefSet(span, cell, expr) => {
+            exprs_doc("ref-set", span, [cell.as_ref(), expr.as_ref()].into_iter())
+        }
+    }
+}
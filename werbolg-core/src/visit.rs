@@ -0,0 +1,349 @@
+//! Read-only traversal of the IR
+//!
+//! This is useful for analyses of a module, e.g. collecting the free variables of a function,
+//! or finding all the calls to some function.
+//!
+//! Each `visit_*` method of the trait defaults to the matching `walk_*` function,
+//! which visits all the children of the node in source order, including the spans
+//! and idents. An implementation only overrides the methods of the nodes it's
+//! interested in, and calls the `walk_*` function to keep descending into the children.
+
+use super::basic::*;
+use super::ir::*;
+use super::location::*;
+
+/// Visitor of the IR nodes by reference
+pub trait Visit {
+    /// Visit a module
+    fn visit_module(&mut self, module: &Module) {
+        walk_module(self, module)
+    }
+
+    /// Visit a module level statement
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement)
+    }
+
+    /// Visit a use statement
+    fn visit_use(&mut self, use_: &Use) {
+        walk_use(self, use_)
+    }
+
+    /// Visit a function definition
+    fn visit_fundef(&mut self, fundef: &FunDef) {
+        walk_fundef(self, fundef)
+    }
+
+    /// Visit a value definition
+    fn visit_valuedef(&mut self, valuedef: &ValueDef) {
+        walk_valuedef(self, valuedef)
+    }
+
+    /// Visit a function implementation, of a function or a lambda
+    fn visit_funimpl(&mut self, funimpl: &FunImpl) {
+        walk_funimpl(self, funimpl)
+    }
+
+    /// Visit a function parameter
+    fn visit_variable(&mut self, variable: &Variable) {
+        walk_variable(self, variable)
+    }
+
+    /// Visit a structure definition, or an enumeration variant
+    fn visit_structdef(&mut self, structdef: &StructDef) {
+        walk_structdef(self, structdef)
+    }
+
+    /// Visit an enumeration definition
+    fn visit_enumdef(&mut self, enumdef: &EnumDef) {
+        walk_enumdef(self, enumdef)
+    }
+
+    /// Visit a binder
+    fn visit_binder(&mut self, binder: &Binder) {
+        walk_binder(self, binder)
+    }
+
+    /// Visit a pattern of a match arm
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        walk_pattern(self, pattern)
+    }
+
+    /// Visit a match arm
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        walk_match_arm(self, arm)
+    }
+
+    /// Visit an expression
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr)
+    }
+
+    /// Visit a span, this is a leaf of the IR
+    fn visit_span(&mut self, _span: &Span) {}
+
+    /// Visit an ident, this is a leaf of the IR
+    fn visit_ident(&mut self, _ident: &Ident) {}
+
+    /// Visit a path, this is a leaf of the IR
+    fn visit_path(&mut self, _path: &Path) {}
+
+    /// Visit a literal, this is a leaf of the IR
+    fn visit_literal(&mut self, _literal: &Literal) {}
+}
+
+/// Visit all the statements of a module
+pub fn walk_module<V: Visit + ?Sized>(v: &mut V, module: &Module) {
+    for statement in module.statements.iter() {
+        v.visit_statement(statement)
+    }
+}
+
+/// Visit the children of a statement
+pub fn walk_statement<V: Visit + ?Sized>(v: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Use(span, use_) => {
+            v.visit_span(span);
+            v.visit_use(use_)
+        }
+        Statement::Function(span, fundef, funimpl) => {
+            v.visit_span(span);
+            v.visit_fundef(fundef);
+            v.visit_funimpl(funimpl)
+        }
+        Statement::Value(span, valuedef, expr) => {
+            v.visit_span(span);
+            v.visit_valuedef(valuedef);
+            v.visit_expr(expr)
+        }
+        Statement::Struct(span, structdef) => {
+            v.visit_span(span);
+            v.visit_structdef(structdef)
+        }
+        Statement::Enum(span, enumdef) => {
+            v.visit_span(span);
+            v.visit_enumdef(enumdef)
+        }
+        Statement::Expr(expr) => v.visit_expr(expr),
+    }
+}
+
+/// Visit the imported namespace, and the hidden and renamed idents of a use statement
+pub fn walk_use<V: Visit + ?Sized>(v: &mut V, use_: &Use) {
+    v.visit_path(&use_.namespace);
+    for ident in use_.hiding.iter() {
+        v.visit_ident(ident)
+    }
+    for (ident, renamed) in use_.renames.iter() {
+        v.visit_ident(ident);
+        v.visit_ident(renamed)
+    }
+}
+
+/// Visit the name of a function definition
+pub fn walk_fundef<V: Visit + ?Sized>(v: &mut V, fundef: &FunDef) {
+    v.visit_ident(&fundef.name)
+}
+
+/// Visit the name of a value definition
+pub fn walk_valuedef<V: Visit + ?Sized>(v: &mut V, valuedef: &ValueDef) {
+    v.visit_ident(&valuedef.name)
+}
+
+/// Visit the parameters then the body of a function implementation
+pub fn walk_funimpl<V: Visit + ?Sized>(v: &mut V, funimpl: &FunImpl) {
+    for variable in funimpl.vars.iter() {
+        v.visit_variable(variable)
+    }
+    v.visit_expr(&funimpl.body)
+}
+
+/// Visit the name of a function parameter
+pub fn walk_variable<V: Visit + ?Sized>(v: &mut V, variable: &Variable) {
+    v.visit_span(&variable.0.span);
+    v.visit_ident(&variable.0.inner)
+}
+
+/// Visit the name then the fields names of a structure definition
+pub fn walk_structdef<V: Visit + ?Sized>(v: &mut V, structdef: &StructDef) {
+    v.visit_span(&structdef.name.span);
+    v.visit_ident(&structdef.name.inner);
+    for field in structdef.fields.iter() {
+        v.visit_span(&field.span);
+        v.visit_ident(&field.inner)
+    }
+}
+
+/// Visit the name then the variants of an enumeration definition
+pub fn walk_enumdef<V: Visit + ?Sized>(v: &mut V, enumdef: &EnumDef) {
+    v.visit_span(&enumdef.name.span);
+    v.visit_ident(&enumdef.name.inner);
+    for variant in enumdef.variants.iter() {
+        v.visit_structdef(&variant.0)
+    }
+}
+
+/// Visit the children of a binder
+///
+/// For the named fields of a deconstruction, the name of the field is visited
+/// before its binder
+pub fn walk_binder<V: Visit + ?Sized>(v: &mut V, binder: &Binder) {
+    match binder {
+        Binder::Unit | Binder::Ignore => {}
+        Binder::Ident(ident) => v.visit_ident(ident),
+        Binder::Deconstruct(path, FieldsBinder::Sequential(binders, _)) => {
+            v.visit_path(path);
+            for binder in binders.iter() {
+                v.visit_binder(binder)
+            }
+        }
+        Binder::Deconstruct(path, FieldsBinder::Named(fields, _)) => {
+            v.visit_path(path);
+            for (field, binder) in fields.iter() {
+                v.visit_ident(field);
+                v.visit_binder(binder)
+            }
+        }
+        Binder::Tuple(binders) => {
+            for binder in binders.iter() {
+                v.visit_binder(binder)
+            }
+        }
+    }
+}
+
+/// Visit the children of a pattern
+pub fn walk_pattern<V: Visit + ?Sized>(v: &mut V, pattern: &Pattern) {
+    match pattern {
+        Pattern::Literal(span, literal) => {
+            v.visit_span(span);
+            v.visit_literal(literal)
+        }
+        Pattern::Binder(binder) => v.visit_binder(binder),
+    }
+}
+
+/// Visit the pattern then the expression of a match arm
+pub fn walk_match_arm<V: Visit + ?Sized>(v: &mut V, arm: &MatchArm) {
+    v.visit_span(&arm.span);
+    v.visit_pattern(&arm.pattern);
+    v.visit_expr(&arm.expr)
+}
+
+/// Visit the children of an expression
+///
+/// The children are visited in source order, e.g. the binder of a `let` is visited
+/// before the bound expression and the expression using the binding
+pub fn walk_expr<V: Visit + ?Sized>(v: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Literal(span, literal) => {
+            v.visit_span(span);
+            v.visit_literal(literal)
+        }
+        Expr::Path(span, path) => {
+            v.visit_span(span);
+            v.visit_path(path)
+        }
+        Expr::Field(expr, struct_path, field) => {
+            v.visit_expr(expr);
+            v.visit_span(&struct_path.span);
+            v.visit_path(&struct_path.inner);
+            v.visit_span(&field.span);
+            v.visit_ident(&field.inner)
+        }
+        Expr::Struct(span, struct_path, fields) => {
+            v.visit_span(span);
+            v.visit_span(&struct_path.span);
+            v.visit_path(&struct_path.inner);
+            match fields {
+                FieldsExpr::Sequential(exprs) => {
+                    for expr in exprs.iter() {
+                        v.visit_expr(expr)
+                    }
+                }
+                FieldsExpr::Named(fields) => {
+                    for (field, expr) in fields.iter() {
+                        v.visit_span(&field.span);
+                        v.visit_ident(&field.inner);
+                        v.visit_expr(expr)
+                    }
+                }
+            }
+        }
+        Expr::Sequence(span, exprs) | Expr::Tuple(span, exprs) | Expr::Call(span, exprs) => {
+            v.visit_span(span);
+            for expr in exprs.iter() {
+                v.visit_expr(expr)
+            }
+        }
+        Expr::Index(span, expr, index) => {
+            v.visit_span(span);
+            v.visit_expr(expr);
+            match index {
+                IndexExpr::Static(_) => {}
+                IndexExpr::Dynamic(index) => v.visit_expr(index),
+            }
+        }
+        Expr::Let(binder, body, in_expr) => {
+            v.visit_binder(binder);
+            v.visit_expr(body);
+            v.visit_expr(in_expr)
+        }
+        Expr::Lambda(span, funimpl) => {
+            v.visit_span(span);
+            v.visit_funimpl(funimpl)
+        }
+        Expr::Match { span, expr, arms } => {
+            v.visit_span(span);
+            v.visit_expr(expr);
+            for arm in arms.iter() {
+                v.visit_match_arm(arm)
+            }
+        }
+        Expr::If {
+            span,
+            cond,
+            then_expr,
+            else_expr,
+        } => {
+            v.visit_span(span);
+            for spanned in [cond, then_expr, else_expr] {
+                v.visit_span(&spanned.span);
+                v.visit_expr(&spanned.inner)
+            }
+        }
+        Expr::Loop(span, body) => {
+            v.visit_span(span);
+            v.visit_expr(body)
+        }
+        Expr::While { span, cond, body } => {
+            v.visit_span(span);
+            for spanned in [cond, body] {
+                v.visit_span(&spanned.span);
+                v.visit_expr(&spanned.inner)
+            }
+        }
+        Expr::Break(span, expr) | Expr::Return(span, expr) => {
+            v.visit_span(span);
+            if let Some(expr) = expr {
+                v.visit_expr(expr)
+            }
+        }
+        Expr::Continue(span) => v.visit_span(span),
+        Expr::Assign(span, ident, expr) => {
+            v.visit_span(span);
+            v.visit_span(&ident.span);
+            v.visit_ident(&ident.inner);
+            v.visit_expr(expr)
+        }
+        Expr::RefNew(span, expr) | Expr::RefGet(span, expr) => {
+            v.visit_span(span);
+            v.visit_expr(expr)
+        }
+        Expr::RefSet(span, cell, expr) => {
+            v.visit_span(span);
+            v.visit_expr(cell);
+            v.visit_expr(expr)
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use super::ExecutionMachine;
+use alloc::vec::Vec;
+use werbolg_compile::InstructionAddress;
+use werbolg_core::id::IdF;
+use werbolg_core::{FunId, Ident};
+
+/// A call frame of a backtrace
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// The function executing in this frame, if the instruction is part of a function
+    pub fun: Option<FunId>,
+    /// The name of the function, None for anonymous functions
+    pub name: Option<Ident>,
+    /// The instruction executing in this frame
+    ///
+    /// For the callers, this is the call instruction waiting for the callee to return
+    pub ip: InstructionAddress,
+    /// The number of tail calls done by this frame
+    ///
+    /// Each tail call replaces the function of the frame by the callee, so the functions
+    /// leading to the function of this frame are missing from the backtrace
+    pub tail_calls: usize,
+}
+
+impl<A, L, T, V> ExecutionMachine<A, L, T, V> {
+    /// Get the backtrace of the execution, from the current frame to the outermost caller
+    ///
+    /// This is typically used after `step` returns an error, to know where the error happened
+    pub fn backtrace(&self) -> Vec<Frame> {
+        let current = (self.ip, self.tail_calls);
+        // the callers are saved with the address to return to, which follows the call
+        let callers = self.rets.iter().rev().map(|save| {
+            let call_ip = InstructionAddress::from_collection_len(save.ip.as_index() - 1);
+            (call_ip, save.tail_calls)
+        });
+        core::iter::once(current)
+            .chain(callers)
+            .map(|(ip, tail_calls)| {
+                let fun = self.module.fun_at(ip);
+                let name = fun.and_then(|funid| self.module.funs[funid].name.clone());
+                Frame {
+                    fun,
+                    name,
+                    ip,
+                    tail_calls,
+                }
+            })
+            .collect()
+    }
+}
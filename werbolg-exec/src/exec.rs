@@ -10,7 +10,10 @@
 //!
 //! ## Call Frames
 //!
-//!
+//! A call saves the context of the caller (the instruction to return to, its SP and arity)
+//! in a `CallSave`, which is restored when the callee returns. A tail call doesn't save
+//! anything, and replaces the current function in place, only counting the number of
+//! tail calls done by the frame, so that a backtrace can note the missing functions.
 //!
 //! ## Stack of Value
 //!
@@ -38,6 +41,23 @@
 //!                        SP                    Stack top
 //! ```
 //!
+//! When `Fun` is a closure, the values it captured are not copied on the stack,
+//! but are fetched from the `Fun` value itself.
+//!
+//! A function known at compilation is called directly with a 'CallFun' operation,
+//! which doesn't push the `Fun` value, and the parameters immediately follow `X`:
+//!
+//! ```text
+//!  ──┬─┬────┬────┬────┬──────┬──┬──────┐
+//!  ..│X│Val1│Val2│Val3│Local1│..│LocalN│
+//!  ──┴─┴────┴────┴────┼──────┴──┴──────┤
+//!                     ▼                ▼
+//!                    SP             Stack top
+//! ```
+//!
+//! Each call frame records whether its `Fun` value is on the stack, so that a 'Ret'
+//! operation or a tail call removes the right number of values.
+//!
 //! After a 'Ret' operation:
 //!
 //! ```text
@@ -69,8 +89,8 @@ use werbolg_core::{NifId, ValueFun};
 pub struct NIF<A, L, T, V> {
     /// name of the NIF
     pub name: &'static str,
-    /// arity of the call
-    pub arity: CallArity,
+    /// arity of the call, or None when the NIF takes any number of arguments
+    pub arity: Option<CallArity>,
     /// the call itself
     pub call: NIFCall<A, L, T, V>,
 }
@@ -91,7 +111,16 @@ impl<A, L, T, V> NIFCall<A, L, T, V> {
     pub fn info(self, name: &'static str, arity: CallArity) -> NIF<A, L, T, V> {
         NIF {
             name,
-            arity,
+            arity: Some(arity),
+            call: self,
+        }
+    }
+
+    /// Create a NIF taking any number of arguments from a NIFCall
+    pub fn info_variadic(self, name: &'static str) -> NIF<A, L, T, V> {
+        NIF {
+            name,
+            arity: None,
             call: self,
         }
     }
@@ -113,10 +142,45 @@ pub fn exec<A: WAllocator<Value = V>, L, T, V: Valuable>(
 
 /// Initialize the execution machine with a call to the specified function (by FunId)
 /// and the arguments to this function as values
+///
+/// The module initialisers are executed first, if they haven't been already
 pub fn initialize<A: WAllocator<Value = V>, L, T, V: Valuable>(
     em: &mut ExecutionMachine<A, L, T, V>,
     call: ir::FunId,
     args: &[V],
+) -> Result<Option<V>, ExecutionError> {
+    initialize_modules(em)?;
+    initialize_call(em, call, args)
+}
+
+/// Execute the initialisers of all the modules in order, setting the globals defined by the modules
+///
+/// This is done only once, and any further call does nothing
+pub fn initialize_modules<A: WAllocator<Value = V>, L, T, V: Valuable>(
+    em: &mut ExecutionMachine<A, L, T, V>,
+) -> Result<(), ExecutionError> {
+    if em.modules_initialized {
+        return Ok(());
+    }
+    let inits = em
+        .module
+        .inits
+        .iter()
+        .map(|init| init.fun)
+        .collect::<alloc::vec::Vec<_>>();
+    for fun in inits {
+        if initialize_call(em, fun, &[])?.is_none() {
+            exec_loop(em)?;
+        }
+    }
+    em.modules_initialized = true;
+    Ok(())
+}
+
+fn initialize_call<A: WAllocator<Value = V>, L, T, V: Valuable>(
+    em: &mut ExecutionMachine<A, L, T, V>,
+    call: ir::FunId,
+    args: &[V],
 ) -> Result<Option<V>, ExecutionError> {
     let arity = args
         .len()
@@ -127,6 +191,8 @@ pub fn initialize<A: WAllocator<Value = V>, L, T, V: Valuable>(
     // truncate the stack of values and rets if any
     em.stack.truncate(0);
     em.rets.truncate(0);
+    em.tail_calls = 0;
+    em.current_fun_slot = true;
 
     em.stack.push_call(V::make_fun(ValueFun::Fun(call)), args);
 
@@ -184,8 +250,12 @@ pub fn step<A: WAllocator<Value = V>, L, T, V: Valuable>(
             em.stack.push_value((em.params.literal_to_value)(literal));
             em.ip_next();
         }
+        Instruction::PushDummy => {
+            em.stack.push_value(V::make_dummy());
+            em.ip_next();
+        }
         Instruction::FetchGlobal(global_id) => {
-            em.sp_push_value_from_global(global_id);
+            em.sp_push_value_from_global(global_id)?;
             em.ip_next();
         }
         Instruction::FetchNif(nif_id) => {
@@ -204,6 +274,10 @@ pub fn step<A: WAllocator<Value = V>, L, T, V: Valuable>(
             em.sp_push_value_from_param(param_bind);
             em.ip_next()
         }
+        Instruction::FetchCaptured(capture_bind) => {
+            em.sp_push_value_from_captured(capture_bind)?;
+            em.ip_next()
+        }
         Instruction::AccessField(expected_cid, idx) => {
             let val = em.stack.pop_value();
             let Some((got_cid, inner)) = val.structure() else {
@@ -228,11 +302,123 @@ pub fn step<A: WAllocator<Value = V>, L, T, V: Valuable>(
             em.stack.push_value(inner[idx.0 as usize].clone());
             em.ip_next()
         }
+        Instruction::AccessTuple(idx) => {
+            let val = em.stack.pop_value();
+            let Some(element) = val.index(idx.0 as usize) else {
+                return Err(ExecutionError::TupleFieldOutOfBound {
+                    field_index: idx,
+                    value_is: val.descriptor(),
+                });
+            };
+            em.stack.push_value(element.clone());
+            em.ip_next()
+        }
+        Instruction::CheckTuple(nb_elements) => {
+            let val = em.stack.pop_value();
+            // a tuple is only accessed by its elements indices, so it has N elements
+            // when the last one exists and nothing follows it
+            let len = nb_elements.0 as usize;
+            let last_exists = len == 0 || val.index(len - 1).is_some();
+            if !last_exists || val.index(len).is_some() {
+                return Err(ExecutionError::TupleLengthMismatch {
+                    expected: nb_elements,
+                    value_is: val.descriptor(),
+                });
+            }
+            em.stack.push_value(val);
+            em.ip_next()
+        }
+        Instruction::Index => {
+            let index_val = em.stack.pop_value();
+            let val = em.stack.pop_value();
+            let Some(index) = index_val.as_index() else {
+                return Err(ExecutionError::ValueNotIndex {
+                    value_is: index_val.descriptor(),
+                });
+            };
+            do_index(em, val, index)?;
+            em.ip_next()
+        }
+        Instruction::IndexConst(index) => {
+            let val = em.stack.pop_value();
+            do_index(em, val, index as usize)?;
+            em.ip_next()
+        }
+        Instruction::MakeClosure(fun_id, nb_captured) => {
+            let captured = em.stack.pop_values(nb_captured.0 as usize);
+            em.stack.push_value(V::make_closure(fun_id, captured));
+            em.ip_next()
+        }
+        Instruction::MakeStruct(constr_id, nb_fields) => {
+            let fields = em.stack.pop_values(nb_fields.0 as usize);
+            em.stack.push_value(V::make_struct(constr_id, fields));
+            em.ip_next()
+        }
+        Instruction::MakeTuple(nb_elements) => {
+            let elements = em.stack.pop_values(nb_elements.0 as usize);
+            em.stack.push_value(V::make_tuple(elements));
+            em.ip_next()
+        }
+        Instruction::TestConstr(expected_cid) => {
+            let val = em.stack.pop_value();
+            let Some((got_cid, _)) = val.structure() else {
+                return Err(ExecutionError::ValueNotStruct {
+                    value_is: val.descriptor(),
+                });
+            };
+            em.stack
+                .push_value(V::make_conditional(got_cid == expected_cid));
+            em.ip_next()
+        }
+        Instruction::TestLiteral(lit) => {
+            let val = em.stack.pop_value();
+            let literal = (em.params.literal_to_value)(&em.module.lits[lit]);
+            em.stack
+                .push_value(V::make_conditional(val.literal_eq(&literal)));
+            em.ip_next()
+        }
         Instruction::LocalBind(local_bind) => {
             let val = em.stack.pop_value();
             em.sp_set_local_value_at(local_bind, val);
             em.ip_next();
         }
+        Instruction::StoreLocal(local_bind) => {
+            let val = em.stack.pop_value();
+            em.sp_set_local_value_at(local_bind, val);
+            em.ip_next();
+        }
+        Instruction::MakeRef => {
+            let val = em.stack.pop_value();
+            em.stack.push_value(V::make_ref(val));
+            em.ip_next();
+        }
+        Instruction::RefGet => {
+            let val = em.stack.pop_value();
+            let Some(cell) = val.reference() else {
+                return Err(ExecutionError::ValueNotRef {
+                    value_is: val.descriptor(),
+                });
+            };
+            let inner = cell.borrow().clone();
+            em.stack.push_value(inner);
+            em.ip_next();
+        }
+        Instruction::RefSet => {
+            let val = em.stack.pop_value();
+            let cell_val = em.stack.pop_value();
+            let Some(cell) = cell_val.reference() else {
+                return Err(ExecutionError::ValueNotRef {
+                    value_is: cell_val.descriptor(),
+                });
+            };
+            *cell.borrow_mut() = val;
+            em.ip_next();
+        }
+        Instruction::StoreGlobal(global_id) => {
+            let val = em.stack.pop_value();
+            em.set_module_global(global_id, val)?;
+            em.ip_next();
+        }
         Instruction::IgnoreOne => {
             let _ = em.stack.pop_value();
             em.ip_next();
@@ -247,23 +433,7 @@ pub fn step<A: WAllocator<Value = V>, L, T, V: Valuable>(
             let val = process_call(em, arity)?;
             match val {
                 CallResult::Jump(fun_ip, local_stack_size) => {
-                    if tc == TailCall::Yes {
-                        // if we have a tail call, we don't need to save the current call frame
-                        // we just shift the values to replace the call stack and
-                        // replace the current state (sp, ip, current_arity)
-                        em.sp_move_rel(arity, em.current_arity, local_stack_size);
-                        em.current_arity = arity;
-                        em.ip_set(fun_ip);
-                    } else {
-                        em.rets.push(CallSave {
-                            ip: em.ip.next(),
-                            sp: em.sp,
-                            arity: em.current_arity,
-                        });
-                        em.current_arity = arity;
-                        em.sp_set(local_stack_size);
-                        em.ip_set(fun_ip);
-                    }
+                    do_call(em, tc, arity, true, fun_ip, local_stack_size)
                 }
                 CallResult::Value(nif_val) => {
                     em.stack.pop_call(arity);
@@ -280,6 +450,12 @@ pub fn step<A: WAllocator<Value = V>, L, T, V: Valuable>(
                 }
             }
         }
+        Instruction::CallFun(funid, arity, tc) => {
+            // the arity is checked at compilation, and the verifier checks the funid
+            let call_def = &em.module.funs[funid];
+            let (fun_ip, local_stack_size) = (call_def.code_pos, call_def.stack_size);
+            do_call(em, tc, arity, false, fun_ip, local_stack_size)
+        }
         Instruction::Jump(d) => em.ip_jump(d),
         Instruction::CondJump(d) => {
             let val = em.stack.pop_value();
@@ -302,23 +478,83 @@ pub fn step<A: WAllocator<Value = V>, L, T, V: Valuable>(
     Ok(None)
 }
 
+fn do_call<A: WAllocator, L, T, V: Valuable>(
+    em: &mut ExecutionMachine<A, L, T, V>,
+    tc: TailCall,
+    arity: CallArity,
+    fun_slot: bool,
+    fun_ip: InstructionAddress,
+    local_stack_size: LocalStackSize,
+) {
+    if tc == TailCall::Yes {
+        // if we have a tail call, we don't need to save the current call frame
+        // we just shift the values to replace the call stack and
+        // replace the current state (sp, ip, current_arity)
+        em.sp_move_rel(arity, fun_slot, local_stack_size);
+        em.current_arity = arity;
+        em.current_fun_slot = fun_slot;
+        em.tail_calls += 1;
+        em.ip_set(fun_ip);
+    } else {
+        em.rets.push(CallSave {
+            ip: em.ip.next(),
+            sp: em.sp,
+            arity: em.current_arity,
+            fun_slot: em.current_fun_slot,
+            tail_calls: em.tail_calls,
+        });
+        em.current_arity = arity;
+        em.current_fun_slot = fun_slot;
+        em.tail_calls = 0;
+        em.sp_set(local_stack_size);
+        em.ip_set(fun_ip);
+    }
+}
+
 fn do_ret<A: WAllocator, L, T, V: Valuable>(
     em: &mut ExecutionMachine<A, L, T, V>,
-    CallSave { ip, sp, arity }: CallSave,
+    CallSave {
+        ip,
+        sp,
+        arity,
+        fun_slot,
+        tail_calls,
+    }: CallSave,
     value: V,
 ) {
     // remove any value after the current stack pointer (remove all local and temp values)
     em.stack.truncate(em.sp.0);
     // pop the calls from the stack
-    em.stack.pop_call(em.current_arity);
+    if em.current_fun_slot {
+        em.stack.pop_call(em.current_arity);
+    } else {
+        em.stack.pop_call_nofun(em.current_arity);
+    }
     // restore state of the caller
     em.current_arity = arity;
+    em.current_fun_slot = fun_slot;
+    em.tail_calls = tail_calls;
     em.sp = sp;
     em.ip_set(ip);
     // push the return value to replace
     em.stack.push_value(value);
 }
 
+fn do_index<A: WAllocator, L, T, V: Valuable>(
+    em: &mut ExecutionMachine<A, L, T, V>,
+    container: V,
+    index: usize,
+) -> Result<(), ExecutionError> {
+    let Some(element) = container.index(index) else {
+        return Err(ExecutionError::IndexOutOfBound {
+            index,
+            value_is: container.descriptor(),
+        });
+    };
+    em.stack.push_value(element.clone());
+    Ok(())
+}
+
 enum CallResult<V> {
     Jump(InstructionAddress, LocalStackSize),
     Value(V),
@@ -363,10 +599,12 @@ fn process_nif_call<A: WAllocator, L, T, V: Valuable>(
     let Some(nif) = &em.environ.nifs.get(nifid) else {
         return Err(ExecutionError::NifOutOfBound { nifid });
     };
-    if nif.arity != arity {
+    if let Some(expected) = nif.arity
+        && expected != arity
+    {
         return Err(ExecutionError::ArityError {
             funid: ValueFun::Native(nifid),
-            expected: nif.arity,
+            expected,
             got: arity,
         });
     }
@@ -9,24 +9,30 @@ extern crate std;
 
 use ir::{ConstrId, GlobalId, NifId, ValueFun};
 use werbolg_compile::{
-    CallArity, LocalBindIndex, LocalStackSize, ParamBindIndex, StructFieldIndex,
+    CallArity, CaptureBindIndex, LocalBindIndex, LocalStackSize, ParamBindIndex, StructFieldIndex,
+    TupleFieldIndex,
 };
 use werbolg_compile::{CompilationUnit, InstructionAddress, InstructionDiff};
 use werbolg_core as ir;
+use werbolg_core::id::IdF;
 use werbolg_core::idvec::IdVec;
 
 mod allocator;
+mod backtrace;
 mod exec;
 mod refcount;
 mod valuable;
+mod verify;
 
 use alloc::{string::String, vec::Vec};
 pub use allocator::WAllocator;
+pub use backtrace::Frame;
 pub use valuable::{Valuable, ValueKind};
+pub use verify::{VerifyError, verify};
 
 pub use refcount::WerRefCount;
 
-pub use exec::{NIF, NIFCall, exec, exec_continue, initialize, step};
+pub use exec::{NIF, NIFCall, exec, exec_continue, initialize, initialize_modules, step};
 
 /// Execution environment with index Nifs by their NifId, and global variable with their GlobalId
 pub struct ExecutionEnviron<A, L, T, V> {
@@ -67,12 +73,21 @@ pub struct ExecutionMachine<A, L, T, V> {
     pub rets: Vec<CallSave>,
     /// stack
     pub stack: ValueStack<V>,
+    /// Values of the globals defined by the modules, set by the module initialisers
+    pub module_globals: Vec<Option<V>>,
+    /// Whether the module initialisers have been executed
+    pub modules_initialized: bool,
     /// instruction pointer
     pub ip: InstructionAddress,
     /// stack pointer
     pub sp: StackPointer,
     /// arity current function
     pub current_arity: CallArity,
+    /// whether the value of the current function is on the stack before its parameters,
+    /// which is not the case for the functions called directly
+    pub current_fun_slot: bool,
+    /// number of tail calls done by the current call frame, each replacing the previous function
+    pub tail_calls: usize,
     /// Execution params
     pub params: ExecutionParams<L, V>,
     /// Allocator
@@ -86,12 +101,14 @@ pub struct CallSave {
     ip: InstructionAddress,
     sp: StackPointer,
     arity: CallArity,
+    fun_slot: bool,
+    tail_calls: usize,
 }
 
 /// Execution Stack pointer
 ///
 /// It is the index in the Stack where:
-/// * under you have the function parameters, and the function Value
+/// * under you have the function parameters, and the function Value unless called directly
 /// * over it:
 ///   * the local stack for bounded value for this function
 ///   * then finally stack based (push/pop) values
@@ -191,6 +208,12 @@ impl<V: Valuable> ValueStack<V> {
         self.values.pop().expect("can be popped")
     }
 
+    /// Pop the N values from the top of the stack, returning them in stack order
+    pub fn pop_values(&mut self, n: usize) -> Vec<V> {
+        let top = self.values.len();
+        self.values.split_off(top - n)
+    }
+
     /// Get the call value and associated arguments
     pub fn get_call_and_args(&self, arity: CallArity) -> (&V, &[V]) {
         let top = self.values.len();
@@ -224,10 +247,13 @@ impl<A, L, T, V: Valuable> ExecutionMachine<A, L, T, V> {
         allocator: A,
         userdata: T,
     ) -> Self {
+        let nb_module_globals = module.inits.iter().map(|init| init.globals.len()).sum();
         Self {
             environ,
             module,
             stack: ValueStack::new(),
+            module_globals: alloc::vec![None; nb_module_globals],
+            modules_initialized: false,
             rets: Vec::new(),
             userdata,
             allocator,
@@ -235,6 +261,8 @@ impl<A, L, T, V: Valuable> ExecutionMachine<A, L, T, V> {
             sp: StackPointer::default(),
             params,
             current_arity: CallArity(0),
+            current_fun_slot: true,
+            tail_calls: 0,
             //current_stack_size: LocalStackSize(0),
         }
     }
@@ -270,11 +298,47 @@ impl<A, L, T, V: Valuable> ExecutionMachine<A, L, T, V> {
         self.stack.set_at(index, value);
     }
 
+    /// Get the global value at GlobalId
+    ///
+    /// The globals of the environment are first, followed by the globals defined by the modules
+    pub fn get_global(&self, global: GlobalId) -> Result<V, ExecutionError> {
+        if let Some(value) = self.environ.globals.get(global) {
+            return Ok(value.clone());
+        }
+        match self.module_globals.get(self.module_global_index(global)) {
+            Some(Some(value)) => Ok(value.clone()),
+            Some(None) => Err(ExecutionError::GlobalNotInitialized { global }),
+            None => Err(ExecutionError::GlobalOutOfBound { global }),
+        }
+    }
+
+    /// Set the value of a global defined by a module
+    pub fn set_module_global(&mut self, global: GlobalId, value: V) -> Result<(), ExecutionError> {
+        if self.environ.globals.get(global).is_some() {
+            // the globals of the environment are read-only
+            return Err(ExecutionError::GlobalOutOfBound { global });
+        }
+        let index = self.module_global_index(global);
+        let Some(slot) = self.module_globals.get_mut(index) else {
+            return Err(ExecutionError::GlobalOutOfBound { global });
+        };
+        *slot = Some(value);
+        Ok(())
+    }
+
+    fn module_global_index(&self, global: GlobalId) -> usize {
+        global.as_index() - self.environ.globals.next_id().as_index()
+    }
+
     /// Get the global value at GlobalId and push it to the top of the stack
     #[inline]
-    pub fn sp_push_value_from_global(&mut self, bind_index: GlobalId) {
-        let val = self.environ.globals[bind_index].clone();
+    pub fn sp_push_value_from_global(
+        &mut self,
+        bind_index: GlobalId,
+    ) -> Result<(), ExecutionError> {
+        let val = self.get_global(bind_index)?;
         self.stack.push_value(val);
+        Ok(())
     }
 
     /// Get the local bound value at bind_index and push it to the top of the stack
@@ -291,6 +355,38 @@ impl<A, L, T, V: Valuable> ExecutionMachine<A, L, T, V> {
         self.stack.get_and_push(index);
     }
 
+    /// Get the value captured by the current closure at capture_index and push it to the top of the stack
+    ///
+    /// The closure is the function value of the current call, which is just before the parameters
+    #[inline]
+    pub fn sp_push_value_from_captured(
+        &mut self,
+        capture_index: CaptureBindIndex,
+    ) -> Result<(), ExecutionError> {
+        if !self.current_fun_slot {
+            // a function called directly is never a closure, so it has nothing captured
+            return Err(ExecutionError::CapturedOutOfBound {
+                capture_index,
+                captured_len: 0,
+            });
+        }
+        let index = self.sp - self.current_arity.0 as usize - 1;
+        let closure = self.stack.get_at(index);
+        let Some(captured) = closure.captured() else {
+            return Err(ExecutionError::ValueNotClosure {
+                value_is: closure.descriptor(),
+            });
+        };
+        let Some(value) = captured.get(capture_index.0 as usize) else {
+            return Err(ExecutionError::CapturedOutOfBound {
+                capture_index,
+                captured_len: captured.len(),
+            });
+        };
+        self.stack.push_value(value.clone());
+        Ok(())
+    }
+
     /// Set the stack pointer to the top and push dummy argument for the local stack
     #[inline]
     pub fn sp_set(&mut self, local_stack_size: LocalStackSize) {
@@ -301,16 +397,13 @@ impl<A, L, T, V: Valuable> ExecutionMachine<A, L, T, V> {
         //self.current_stack_size = local_stack_size;
     }
 
-    fn sp_move_rel(
-        &mut self,
-        arity: CallArity,
-        prev_arity: CallArity,
-        local_stack: LocalStackSize,
-    ) {
-        let nb_values_to_move = arity.0 as usize + 1;
+    /// Replace the call of the current function by the call at the top of the stack,
+    /// with or without a function value before its parameters
+    fn sp_move_rel(&mut self, arity: CallArity, fun_slot: bool, local_stack: LocalStackSize) {
+        let nb_values_to_move = arity.0 as usize + usize::from(fun_slot);
         let stack_top = self.stack.top();
         let top_fun = stack_top - nb_values_to_move;
-        let begin = self.sp - (prev_arity.0 as usize) - 1;
+        let begin = self.sp - (self.current_arity.0 as usize) - usize::from(self.current_fun_slot);
 
         for index in 0..nb_values_to_move {
             let v = self.stack.get_at(top_fun + index);
@@ -394,6 +487,34 @@ pub enum ExecutionError {
         /// the actual structure length
         struct_len: usize,
     },
+    /// Trying to access a tuple element that is beyond the number of elements, or the value is not a tuple
+    TupleFieldOutOfBound {
+        /// the element index
+        field_index: TupleFieldIndex,
+        /// The descriptor for the value accessed
+        value_is: ValueKind,
+    },
+    /// The value doesn't have the number of elements expected by a tuple binder or pattern,
+    /// or the value is not a tuple
+    TupleLengthMismatch {
+        /// the number of elements expected
+        expected: CallArity,
+        /// The descriptor for the value checked
+        value_is: ValueKind,
+    },
+    /// Trying to access an element of a container that is beyond its number of elements,
+    /// or the value is not a container
+    IndexOutOfBound {
+        /// the element index
+        index: usize,
+        /// The descriptor for the value accessed
+        value_is: ValueKind,
+    },
+    /// Value is not an index
+    ValueNotIndex {
+        /// The descriptor for the value that was not an index
+        value_is: ValueKind,
+    },
     /// Trying to access a NIF id that doesn't exist
     NifOutOfBound {
         /// Constructor Id of the structure
@@ -404,11 +525,38 @@ pub enum ExecutionError {
         /// the descriptor for the value that was not a fun
         value_is: ValueKind,
     },
+    /// Value is not a closure
+    ValueNotClosure {
+        /// The descriptor for the value that was not a closure
+        value_is: ValueKind,
+    },
+    /// Trying to access a captured value that is beyond the number of values captured by the closure
+    CapturedOutOfBound {
+        /// the captured value index
+        capture_index: CaptureBindIndex,
+        /// the number of values captured by the closure
+        captured_len: usize,
+    },
+    /// Trying to access a global that doesn't exist, or to set a global of the environment
+    GlobalOutOfBound {
+        /// the global id
+        global: GlobalId,
+    },
+    /// Trying to access a global defined by a module that is not initialised yet
+    GlobalNotInitialized {
+        /// the global id
+        global: GlobalId,
+    },
     /// Value is not a struct
     ValueNotStruct {
         /// The descriptor for the value that was not a struct
         value_is: ValueKind,
     },
+    /// Value is not a reference
+    ValueNotRef {
+        /// The descriptor for the value that was not a reference
+        value_is: ValueKind,
+    },
     /// Value is not a valid conditional
     ValueNotConditional {
         /// The descriptor for the value that was not a conditional
@@ -1,4 +1,6 @@
-use werbolg_core::{ConstrId, ValueFun};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use werbolg_core::{ConstrId, FunId, ValueFun};
 
 /// A mostly for error and debug useful descriptor for a type of value
 pub type ValueKind = &'static str;
@@ -11,18 +13,54 @@ pub trait Valuable: Clone {
     /// Get the boolean value of a conditional value, or None if not valid
     fn conditional(&self) -> Option<bool>;
 
+    /// Check if a Valuable object is equal to the value of a literal
+    fn literal_eq(&self, literal: &Self) -> bool;
+
     /// Get the a function value from a Valuable object, or None if not valid
     fn fun(&self) -> Option<ValueFun>;
 
+    /// Get the values captured by a closure Valuable object, or None if not valid
+    fn captured(&self) -> Option<&[Self]>;
+
     /// Get a structure out of a Valuable object, or None if not valid
     fn structure(&self) -> Option<(ConstrId, &[Self])>;
 
     /// Get the elements #index of a Valuable object, or None if not valid
     fn index(&self, index: usize) -> Option<&Self>;
 
+    /// Get the index value (e.g. of a sequence element) from a Valuable object, or None if not valid
+    fn as_index(&self) -> Option<usize>;
+
+    /// Get the mutable cell of a reference Valuable object, or None if not valid
+    fn reference(&self) -> Option<&RefCell<Self>>;
+
+    /// Create a conditional valuable object, e.g. `conditional()` should return `Some(b)`
+    fn make_conditional(b: bool) -> Self;
+
     /// Create a Fun valuable object
     fn make_fun(fun: ValueFun) -> Self;
 
+    /// Create a closure valuable object of the function FunId with its captured values
+    ///
+    /// The closure object need to be callable, e.g. `fun()` should return `Some(ValueFun::Fun(fun))`
+    fn make_closure(fun: FunId, captured: Vec<Self>) -> Self;
+
+    /// Create a structure valuable object of the constructor ConstrId with its fields values
+    ///
+    /// The structure object need to be accessible, e.g. `structure()` should return `Some((constr, fields))`
+    fn make_struct(constr: ConstrId, fields: Vec<Self>) -> Self;
+
+    /// Create a tuple valuable object with its elements values
+    ///
+    /// The tuple object need to be indexable, e.g. `index(i)` should return `Some(&elements[i])`
+    fn make_tuple(elements: Vec<Self>) -> Self;
+
+    /// Create a reference valuable object, a mutable cell containing the value
+    ///
+    /// The cell need to be shared by all the clones of the reference object, and
+    /// `reference()` should return `Some(cell)`
+    fn make_ref(value: Self) -> Self;
+
     /// Create a dummy parameter to push on the stack.
     fn make_dummy() -> Self;
 }
@@ -0,0 +1,485 @@
+//! Verification of the code of a compilation unit before its execution
+//!
+//! The execution machine trusts the instructions it runs: an out of bound local or
+//! parameter index, a jump in the middle of another function, or an unbalanced value
+//! stack would panic or corrupt the stack of the machine. The compiler never generates
+//! such code, but a compilation unit loaded from bytecode or built by hand can contain
+//! anything, so `verify` needs to be called on it before executing it.
+//!
+//! The verification is done function by function, where the code of a function
+//! extends from its entry point to the entry point of the next function (or the end of
+//! the code). Every instruction reachable from the entry point is checked, and the
+//! depth of the value stack above the local stack is tracked on every path, so that
+//! the depth is the same whenever paths join, never goes below zero, and the function
+//! never runs past its last instruction.
+
+use super::ExecutionEnviron;
+use alloc::vec;
+use alloc::vec::Vec;
+use ir::{ConstrId, FunId, GlobalId, LitId, NifId, ValueFun};
+use werbolg_compile::{
+    CallArity, CompilationUnit, Instruction, InstructionAddress, InstructionDiff, LocalBindIndex,
+    LocalStackSize, ParamBindIndex, TailCall,
+};
+use werbolg_core as ir;
+use werbolg_core::id::IdF;
+
+/// Verification Error
+#[derive(Debug, Clone)]
+pub enum VerifyError {
+    /// The entry point of a function is outside of the code
+    FunOutOfCode {
+        /// the function
+        fun: FunId,
+        /// the entry point of the function
+        code_pos: InstructionAddress,
+    },
+    /// A module initialiser is using a function that doesn't exist
+    InitFunOutOfBound {
+        /// the function of the initialiser
+        fun: FunId,
+    },
+    /// A jump target is not an instruction of the function containing the jump
+    JumpOutOfFunction {
+        /// the function
+        fun: FunId,
+        /// the address of the jump
+        ip: InstructionAddress,
+        /// the displacement of the jump
+        diff: InstructionDiff,
+    },
+    /// The execution of the function can continue after its last instruction
+    FallThrough {
+        /// the function
+        fun: FunId,
+        /// the address of the last instruction of the function
+        ip: InstructionAddress,
+    },
+    /// A local bind index is not below the local stack size of the function
+    LocalOutOfBound {
+        /// the function
+        fun: FunId,
+        /// the address of the instruction
+        ip: InstructionAddress,
+        /// the local bind index
+        local_index: LocalBindIndex,
+        /// the local stack size of the function
+        stack_size: LocalStackSize,
+    },
+    /// A parameter index is not below the arity of the function
+    ParamOutOfBound {
+        /// the function
+        fun: FunId,
+        /// the address of the instruction
+        ip: InstructionAddress,
+        /// the parameter index
+        param_index: ParamBindIndex,
+        /// the arity of the function
+        arity: CallArity,
+    },
+    /// A literal doesn't exist
+    LitOutOfBound {
+        /// the address of the instruction
+        ip: InstructionAddress,
+        /// the literal id
+        lit: LitId,
+    },
+    /// A function doesn't exist
+    FunOutOfBound {
+        /// the address of the instruction
+        ip: InstructionAddress,
+        /// the function id
+        funid: FunId,
+    },
+    /// A function or NIF called directly with a different number of arguments than its arity
+    CallArityMismatch {
+        /// the address of the instruction
+        ip: InstructionAddress,
+        /// the function or NIF id
+        funid: ValueFun,
+        /// the arity of the function or NIF
+        expected: CallArity,
+        /// the number of arguments of the call
+        got: CallArity,
+    },
+    /// A NIF doesn't exist in the execution environment
+    NifOutOfBound {
+        /// the address of the instruction
+        ip: InstructionAddress,
+        /// the NIF id
+        nifid: NifId,
+    },
+    /// A constructor doesn't exist
+    ConstrOutOfBound {
+        /// the address of the instruction
+        ip: InstructionAddress,
+        /// the constructor id
+        constr: ConstrId,
+    },
+    /// A global doesn't exist, or is stored to while not defined by a module
+    GlobalOutOfBound {
+        /// the address of the instruction
+        ip: InstructionAddress,
+        /// the global id
+        global: GlobalId,
+    },
+    /// An instruction is using more values than present on the value stack
+    StackUnderflow {
+        /// the function
+        fun: FunId,
+        /// the address of the instruction
+        ip: InstructionAddress,
+        /// the number of values used by the instruction
+        needed: usize,
+        /// the number of values on the value stack
+        depth: usize,
+    },
+    /// The value stack has a different depth depending on the path taken to an instruction
+    StackDepthMismatch {
+        /// the function
+        fun: FunId,
+        /// the address of the instruction
+        ip: InstructionAddress,
+        /// the depth from the first path reaching the instruction
+        expected: usize,
+        /// the depth from another path reaching the instruction
+        got: usize,
+    },
+}
+
+/// Verify the code of the compilation unit against the execution environment it will run in
+///
+/// This needs to be called on any compilation unit that has not been generated by
+/// the compiler with the same environment (e.g. loaded from bytecode), as the
+/// execution doesn't check the validity of the instructions.
+pub fn verify<A, L, T, V>(
+    module: &CompilationUnit<L>,
+    environ: &ExecutionEnviron<A, L, T, V>,
+) -> Result<(), VerifyError> {
+    let env_globals = environ.globals.next_id().as_index();
+    let module_globals: usize = module.inits.iter().map(|init| init.globals.len()).sum();
+    let limits = Limits {
+        lits: module.lits.next_id().as_index(),
+        funs: module.funs.next_id().as_index(),
+        constrs: module.constrs.vecdata.next_id().as_index(),
+        nifs: environ.nifs.next_id().as_index(),
+        env_globals,
+        globals: env_globals + module_globals,
+    };
+    let nifs_arity = environ
+        .nifs
+        .iter()
+        .map(|(_, nif)| nif.arity)
+        .collect::<Vec<_>>();
+
+    for init in module.inits.iter() {
+        if init.fun.as_index() >= limits.funs {
+            return Err(VerifyError::InitFunOutOfBound { fun: init.fun });
+        }
+    }
+
+    let code_len = module.code.next_id().as_index();
+    let mut entry_points = Vec::new();
+    for (fun, fundef) in module.funs.iter() {
+        if fundef.code_pos.as_index() >= code_len {
+            return Err(VerifyError::FunOutOfCode {
+                fun,
+                code_pos: fundef.code_pos,
+            });
+        }
+        entry_points.push(fundef.code_pos.as_index());
+    }
+    entry_points.sort();
+    entry_points.dedup();
+
+    for (fun, fundef) in module.funs.iter() {
+        let start = fundef.code_pos.as_index();
+        let next_entry = entry_points.partition_point(|pos| *pos <= start);
+        let end = entry_points.get(next_entry).copied().unwrap_or(code_len);
+        let fv = FunVerifier {
+            fun,
+            arity: fundef.arity,
+            stack_size: fundef.stack_size,
+            start,
+            end,
+            limits: &limits,
+            nifs_arity: &nifs_arity,
+        };
+        fv.verify(module)?;
+    }
+    Ok(())
+}
+
+/// The number of each kind of ids that the code can refer to
+struct Limits {
+    lits: usize,
+    funs: usize,
+    constrs: usize,
+    nifs: usize,
+    env_globals: usize,
+    globals: usize,
+}
+
+struct FunVerifier<'a> {
+    fun: FunId,
+    arity: CallArity,
+    stack_size: LocalStackSize,
+    start: usize,
+    end: usize,
+    limits: &'a Limits,
+    /// The arity of each NIF of the environment, None for the NIFs taking any number of arguments
+    nifs_arity: &'a [Option<CallArity>],
+}
+
+/// What happens after an instruction has been executed
+enum Next {
+    /// Continue with the next instruction
+    Continue,
+    /// Continue with the next instruction, or with the jump target
+    Branch(InstructionDiff),
+    /// Continue with the jump target
+    Jump(InstructionDiff),
+    /// Leave the function
+    Leave,
+}
+
+impl<'a> FunVerifier<'a> {
+    fn verify<L>(&self, module: &CompilationUnit<L>) -> Result<(), VerifyError> {
+        // the depth of the value stack when reaching each instruction of the function
+        let mut depths: Vec<Option<usize>> = vec![None; self.end - self.start];
+        let mut work = vec![(self.start, 0)];
+
+        while let Some((index, depth)) = work.pop() {
+            let ip = InstructionAddress::from_collection_len(index);
+            match depths[index - self.start] {
+                Some(expected) if expected == depth => continue,
+                Some(expected) => {
+                    return Err(VerifyError::StackDepthMismatch {
+                        fun: self.fun,
+                        ip,
+                        expected,
+                        got: depth,
+                    });
+                }
+                None => depths[index - self.start] = Some(depth),
+            }
+
+            let instruction = &module.code[ip];
+            let (pops, pushes, next) = self.instruction(module, ip, instruction)?;
+            if depth < pops {
+                return Err(VerifyError::StackUnderflow {
+                    fun: self.fun,
+                    ip,
+                    needed: pops,
+                    depth,
+                });
+            }
+            let depth = depth - pops + pushes;
+
+            match next {
+                Next::Continue => work.push((self.next(ip)?, depth)),
+                Next::Branch(diff) => {
+                    work.push((self.next(ip)?, depth));
+                    work.push((self.jump_target(ip, diff)?, depth));
+                }
+                Next::Jump(diff) => work.push((self.jump_target(ip, diff)?, depth)),
+                Next::Leave => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn next(&self, ip: InstructionAddress) -> Result<usize, VerifyError> {
+        let next = ip.as_index() + 1;
+        if next >= self.end {
+            return Err(VerifyError::FallThrough { fun: self.fun, ip });
+        }
+        Ok(next)
+    }
+
+    fn jump_target(
+        &self,
+        ip: InstructionAddress,
+        diff: InstructionDiff,
+    ) -> Result<usize, VerifyError> {
+        match ip.next().checked_add(diff).map(|target| target.as_index()) {
+            Some(target) if target >= self.start && target < self.end => Ok(target),
+            _ => Err(VerifyError::JumpOutOfFunction {
+                fun: self.fun,
+                ip,
+                diff,
+            }),
+        }
+    }
+
+    /// Check the operands of the instruction, and return the number of values it pops from
+    /// the value stack, the number of values it pushes, and where the execution continues
+    fn instruction<L>(
+        &self,
+        module: &CompilationUnit<L>,
+        ip: InstructionAddress,
+        instruction: &Instruction,
+    ) -> Result<(usize, usize, Next), VerifyError> {
+        let limits = self.limits;
+        let r = match instruction {
+            Instruction::PushLiteral(lit) => {
+                self.check_lit(ip, *lit)?;
+                (0, 1, Next::Continue)
+            }
+            Instruction::TestLiteral(lit) => {
+                self.check_lit(ip, *lit)?;
+                (1, 1, Next::Continue)
+            }
+            Instruction::PushDummy => (0, 1, Next::Continue),
+            Instruction::FetchGlobal(global) => {
+                if global.as_index() >= limits.globals {
+                    return Err(VerifyError::GlobalOutOfBound {
+                        ip,
+                        global: *global,
+                    });
+                }
+                (0, 1, Next::Continue)
+            }
+            Instruction::StoreGlobal(global) => {
+                let index = global.as_index();
+                if index < limits.env_globals || index >= limits.globals {
+                    return Err(VerifyError::GlobalOutOfBound {
+                        ip,
+                        global: *global,
+                    });
+                }
+                (1, 0, Next::Continue)
+            }
+            Instruction::FetchNif(nifid) => {
+                self.check_nif(ip, *nifid)?;
+                (0, 1, Next::Continue)
+            }
+            Instruction::FetchFun(funid) => {
+                self.check_fun(ip, *funid)?;
+                (0, 1, Next::Continue)
+            }
+            Instruction::FetchStackParam(param_index) => {
+                if param_index.0 >= self.arity.0 {
+                    return Err(VerifyError::ParamOutOfBound {
+                        fun: self.fun,
+                        ip,
+                        param_index: *param_index,
+                        arity: self.arity,
+                    });
+                }
+                (0, 1, Next::Continue)
+            }
+            Instruction::FetchStackLocal(local_index) => {
+                self.check_local(ip, *local_index)?;
+                (0, 1, Next::Continue)
+            }
+            Instruction::LocalBind(local_index) | Instruction::StoreLocal(local_index) => {
+                self.check_local(ip, *local_index)?;
+                (1, 0, Next::Continue)
+            }
+            // the number of captured values is only known from the closure value at runtime
+            Instruction::FetchCaptured(_) => (0, 1, Next::Continue),
+            Instruction::AccessField(constr, _) | Instruction::TestConstr(constr) => {
+                self.check_constr(ip, *constr)?;
+                (1, 1, Next::Continue)
+            }
+            Instruction::AccessTuple(_)
+            | Instruction::CheckTuple(_)
+            | Instruction::IndexConst(_) => (1, 1, Next::Continue),
+            Instruction::MakeRef | Instruction::RefGet => (1, 1, Next::Continue),
+            Instruction::Index => (2, 1, Next::Continue),
+            Instruction::MakeClosure(funid, nb_captured) => {
+                self.check_fun(ip, *funid)?;
+                (nb_captured.0 as usize, 1, Next::Continue)
+            }
+            Instruction::MakeStruct(constr, nb_fields) => {
+                self.check_constr(ip, *constr)?;
+                (nb_fields.0 as usize, 1, Next::Continue)
+            }
+            Instruction::MakeTuple(nb_elements) => (nb_elements.0 as usize, 1, Next::Continue),
+            Instruction::RefSet => (2, 0, Next::Continue),
+            Instruction::IgnoreOne => (1, 0, Next::Continue),
+            Instruction::CallNif(nifid, arity) => {
+                self.check_nif(ip, *nifid)?;
+                if let Some(expected) = self.nifs_arity[nifid.as_index()]
+                    && expected != *arity
+                {
+                    return Err(VerifyError::CallArityMismatch {
+                        ip,
+                        funid: ValueFun::Native(*nifid),
+                        expected,
+                        got: *arity,
+                    });
+                }
+                (arity.0 as usize, 1, Next::Continue)
+            }
+            Instruction::Call(TailCall::Yes, arity) => (arity.0 as usize + 1, 0, Next::Leave),
+            Instruction::Call(TailCall::No, arity) => (arity.0 as usize + 1, 1, Next::Continue),
+            Instruction::CallFun(funid, arity, tc) => {
+                self.check_fun(ip, *funid)?;
+                // the arity is not checked by the execution of a direct call
+                let expected = module.funs[*funid].arity;
+                if expected != *arity {
+                    return Err(VerifyError::CallArityMismatch {
+                        ip,
+                        funid: ValueFun::Fun(*funid),
+                        expected,
+                        got: *arity,
+                    });
+                }
+                match tc {
+                    TailCall::Yes => (arity.0 as usize, 0, Next::Leave),
+                    TailCall::No => (arity.0 as usize, 1, Next::Continue),
+                }
+            }
+            Instruction::Jump(diff) => (0, 0, Next::Jump(*diff)),
+            Instruction::CondJump(diff) => (1, 0, Next::Branch(*diff)),
+            Instruction::Ret => (1, 0, Next::Leave),
+        };
+        Ok(r)
+    }
+
+    fn check_local(
+        &self,
+        ip: InstructionAddress,
+        local_index: LocalBindIndex,
+    ) -> Result<(), VerifyError> {
+        if local_index.0 >= self.stack_size.0 {
+            return Err(VerifyError::LocalOutOfBound {
+                fun: self.fun,
+                ip,
+                local_index,
+                stack_size: self.stack_size,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_lit(&self, ip: InstructionAddress, lit: LitId) -> Result<(), VerifyError> {
+        if lit.as_index() >= self.limits.lits {
+            return Err(VerifyError::LitOutOfBound { ip, lit });
+        }
+        Ok(())
+    }
+
+    fn check_fun(&self, ip: InstructionAddress, funid: FunId) -> Result<(), VerifyError> {
+        if funid.as_index() >= self.limits.funs {
+            return Err(VerifyError::FunOutOfBound { ip, funid });
+        }
+        Ok(())
+    }
+
+    fn check_nif(&self, ip: InstructionAddress, nifid: NifId) -> Result<(), VerifyError> {
+        if nifid.as_index() >= self.limits.nifs {
+            return Err(VerifyError::NifOutOfBound { ip, nifid });
+        }
+        Ok(())
+    }
+
+    fn check_constr(&self, ip: InstructionAddress, constr: ConstrId) -> Result<(), VerifyError> {
+        if constr.as_index() >= self.limits.constrs {
+            return Err(VerifyError::ConstrOutOfBound { ip, constr });
+        }
+        Ok(())
+    }
+}
@@ -10,14 +10,17 @@ pub enum Ast {
     /// List of expression '(a b c)'
     List(ListExpr),
     // (define (id args) expr+)
-    // (define id expr+)
     Define(Spanned<Ident>, Vec<Variable>, Vec<Spanned<Ast>>),
+    // (define id expr+)
+    DefineValue(Spanned<Ident>, Vec<Spanned<Ast>>),
     // (lambda (args) expr+)
     Lambda(Vec<Variable>, Vec<Spanned<Ast>>),
     // (struct id (field+))
     Struct(Spanned<Ident>, Vec<Spanned<Ident>>),
     // (if cond then_expr else_expr)
     If(Box<Spanned<Ast>>, Box<Spanned<Ast>>, Box<Spanned<Ast>>),
+    // (nth list index)
+    Nth(Box<Spanned<Ast>>, Box<Spanned<Ast>>),
 }
 
 impl Ast {
@@ -103,6 +103,16 @@ fn remap_err(e: parse::ParseError) -> ParseError {
             note: Some(format!("expecting to use `if (cond) (then) (else)`")),
             kind: ParseErrorKind::Str(format!("if required 3 parameters, but {} given", nb_args)),
         },
+        parse::ParseError::NthArityFailed { nth_span, nb_args } => ParseError {
+            context: None,
+            location: nth_span,
+            description: format!(
+                "nth parameter doesn't have the right number of arguments, expecting 2 but {} given",
+                nb_args
+            ),
+            note: Some(String::from("expecting to use `nth (list) (index)`")),
+            kind: ParseErrorKind::Str(format!("nth required 2 parameters, but {} given", nb_args)),
+        },
         parse::ParseError::DefineArgumentNotIdent {
             define_span,
             arg_span: _,
@@ -172,33 +182,29 @@ fn exprs_into_let(exprs: Vec<Spanned<Ast>>) -> Result<ir::Expr, ParseError> {
 
     while let Some(e) = exprs.next() {
         match e.inner {
+            Ast::DefineValue(name, body) => {
+                let bind_expr = exprs_into_let(body)?;
+                accumulator = ir::Expr::Let(
+                    ir::Binder::Ident(name.clone().unspan()),
+                    Box::new(bind_expr),
+                    Box::new(accumulator),
+                );
+            }
             Ast::Define(name, args, body) => {
-                if args.len() == 0 && body.len() == 1 {
-                    let bind_expr = expr(body[0].clone())?;
-                    accumulator = ir::Expr::Let(
-                        ir::Binder::Ident(name.clone().unspan()),
-                        Box::new(bind_expr),
-                        Box::new(accumulator),
-                    );
+                let body = exprs_into_let(body)?;
+                let span_args = if !args.is_empty() {
+                    spans_merge(&mut args.iter().map(|sargs| &sargs.0.span))
                 } else {
-                    let body = exprs_into_let(body)?;
-                    let span_args = if args.len() > 0 {
-                        spans_merge(&mut args.iter().map(|sargs| &sargs.0.span))
-                    } else {
-                        name.span.clone()
-                    };
-                    accumulator = ir::Expr::Let(
-                        ir::Binder::Ident(name.clone().unspan()),
-                        Box::new(ir::Expr::Lambda(
-                            span_args,
-                            Box::new(ir::FunImpl {
-                                vars: args,
-                                body: body,
-                            }),
-                        )),
-                        Box::new(accumulator),
-                    )
-                }
+                    name.span.clone()
+                };
+                accumulator = ir::Expr::Let(
+                    ir::Binder::Ident(name.clone().unspan()),
+                    Box::new(ir::Expr::Lambda(
+                        span_args,
+                        Box::new(ir::FunImpl { vars: args, body }),
+                    )),
+                    Box::new(accumulator),
+                )
             }
             x => {
                 return Err(ParseError {
@@ -235,6 +241,10 @@ fn statement(ast: Spanned<Ast>) -> Result<ir::Statement, ParseError> {
             then_expr: Box::new(spanned_expr(then_expr.as_ref().clone())?),
             else_expr: Box::new(spanned_expr(else_expr.as_ref().clone())?),
         })),
+        Ast::Nth(list_expr, index_expr) => Ok(ir::Statement::Expr(expr(Spanned::new(
+            ast.span,
+            Ast::Nth(list_expr, index_expr),
+        ))?)),
         Ast::Lambda(args, body) => {
             let body = exprs_into_let(body)?;
             Ok(ir::Statement::Expr(ir::Expr::Lambda(
@@ -259,9 +269,24 @@ fn statement(ast: Spanned<Ast>) -> Result<ir::Statement, ParseError> {
                 },
             ))
         }
+        Ast::DefineValue(name, body) => {
+            let body = exprs_into_let(body)?;
+            Ok(ir::Statement::Value(
+                ast.span,
+                ir::ValueDef {
+                    privacy: ir::Privacy::Public,
+                    name: name.unspan(),
+                },
+                body,
+            ))
+        }
         Ast::Struct(name, fields) => Ok(ir::Statement::Struct(
             ast.span,
-            ir::StructDef { name, fields },
+            ir::StructDef {
+                privacy: ir::Privacy::Public,
+                name,
+                fields,
+            },
         )),
     }
 }
@@ -289,7 +314,22 @@ fn expr(ast: Spanned<Ast>) -> Result<ir::Expr, ParseError> {
             then_expr: Box::new(spanned_expr(then_expr.as_ref().clone())?),
             else_expr: Box::new(spanned_expr(else_expr.as_ref().clone())?),
         }),
-        Ast::Define(_, _, _) => Err(ParseError {
+        Ast::Nth(list_expr, index_expr) => {
+            // a number literal index is known statically
+            let index = if let Ast::Literal(ast::Literal::Number(n)) = &index_expr.inner
+                && let Ok(i) = n.parse::<u32>()
+            {
+                ir::IndexExpr::Static(i)
+            } else {
+                ir::IndexExpr::Dynamic(Box::new(expr(*index_expr)?))
+            };
+            Ok(ir::Expr::Index(
+                ast.span,
+                Box::new(expr(*list_expr)?),
+                index,
+            ))
+        }
+        Ast::Define(_, _, _) | Ast::DefineValue(_, _) => Err(ParseError {
             context: None,
             location: ast.span,
             description: format!(""),
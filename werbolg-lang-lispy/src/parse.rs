@@ -2,7 +2,7 @@
 
 use super::ast::{Ast, Literal};
 use super::token::{Token, UnknownToken};
-use alloc::{boxed::Box, vec, vec::Vec};
+use alloc::{boxed::Box, vec::Vec};
 use logos::Logos;
 use werbolg_core::{Ident, Span, Spanned, Variable, span_merge, spans_merge};
 use werbolg_lang_common::hex_decode;
@@ -54,6 +54,10 @@ pub enum ParseError {
         if_span: Span,
         nb_args: usize,
     },
+    NthArityFailed {
+        nth_span: Span,
+        nb_args: usize,
+    },
     DefineEmptyName {
         define_span: Span,
         args_span: Span,
@@ -125,6 +129,8 @@ impl<'a> Parser<'a> {
                     parse_struct(list_span.clone(), exprs).map(|a| Spanned::new(list_span, a))
                 } else if first_elem.atom_eq("if") {
                     parse_if(list_span.clone(), exprs).map(|a| Spanned::new(list_span, a))
+                } else if first_elem.atom_eq("nth") {
+                    parse_nth(list_span.clone(), exprs).map(|a| Spanned::new(list_span, a))
                 } else {
                     Ok(Spanned::new(list_span.clone(), Ast::List(exprs)))
                 }
@@ -219,6 +225,20 @@ fn parse_if(list_span: Span, mut exprs: Vec<Spanned<Ast>>) -> Result<Ast, ParseE
     ))
 }
 
+fn parse_nth(list_span: Span, mut exprs: Vec<Spanned<Ast>>) -> Result<Ast, ParseError> {
+    vec_drop_start(&mut exprs, 1);
+    if exprs.len() != 2 {
+        return Err(ParseError::NthArityFailed {
+            nth_span: list_span,
+            nb_args: exprs.len(),
+        });
+    }
+    let mut e = exprs.into_iter();
+    let list_expr = e.next().unwrap();
+    let index_expr = e.next().unwrap();
+    Ok(Ast::Nth(Box::new(list_expr), Box::new(index_expr)))
+}
+
 fn parse_lambda(_list_span: Span, mut exprs: Vec<Spanned<Ast>>) -> Result<Ast, ParseError> {
     let vars = parse_atom_list(&exprs[1])?
         .into_iter()
@@ -273,9 +293,9 @@ fn parse_define(list_span: Span, mut exprs: Vec<Spanned<Ast>>) -> Result<Ast, Pa
                 })
                 .collect::<Result<Vec<_>, _>>()?;
 
-            (ident, args)
+            (ident, Some(args))
         }
-        Ast::Atom(id) => (id.clone(), vec![]),
+        Ast::Atom(id) => (id.clone(), None),
         _ => {
             return Err(ParseError::DefineArgumentNotList {
                 define_span: list_span,
@@ -286,7 +306,11 @@ fn parse_define(list_span: Span, mut exprs: Vec<Spanned<Ast>>) -> Result<Ast, Pa
 
     // drop 'define' atom and first name or list of name+args
     vec_drop_start(&mut exprs, 2);
-    Ok(Ast::Define(Spanned::new(span_name, ident), args, exprs))
+    let name = Spanned::new(span_name, ident);
+    match args {
+        Some(args) => Ok(Ast::Define(name, args, exprs)),
+        None => Ok(Ast::DefineValue(name, exprs)),
+    }
 }
 
 fn parse_struct(list_span: Span, exprs: Vec<Spanned<Ast>>) -> Result<Ast, ParseError> {
@@ -367,6 +391,7 @@ impl<'a> Iterator for Parser<'a> {
 mod tests {
     use super::*;
     use alloc::string::String;
+    use alloc::vec;
     use werbolg_core::Spanned;
 
     fn match_expr(e1: &Ast, e2: &Ast) -> bool {
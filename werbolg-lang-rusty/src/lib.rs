@@ -93,6 +93,15 @@ fn rewrite_expr_spanbox(span_expr: &(parse::Expr, parse::Span)) -> Box<Spanned<i
     Box::new(Spanned::new(span, expr))
 }
 
+fn rewrite_binder(binder: &parse::Binder) -> ir::Binder {
+    match binder {
+        parse::Binder::Ident(name) => ir::Binder::Ident(ir::Ident::from(name.as_str())),
+        parse::Binder::Tuple(binders) => {
+            ir::Binder::Tuple(binders.iter().map(rewrite_binder).collect())
+        }
+    }
+}
+
 fn rewrite_expr(span_expr: &(parse::Expr, parse::Span)) -> ir::Expr {
     match &span_expr.0 {
         parse::Expr::Error => todo!(),
@@ -105,8 +114,12 @@ fn rewrite_expr(span_expr: &(parse::Expr, parse::Span)) -> ir::Expr {
             span_expr.1.clone(),
             ir::Path::relative(ir::Ident::from(l.as_str())),
         ),
-        parse::Expr::Let(name, bind, then) => ir::Expr::Let(
-            ir::Binder::Ident(ir::Ident::from(name.as_str())),
+        parse::Expr::Tuple(elements) => ir::Expr::Tuple(
+            span_expr.1.clone(),
+            elements.iter().map(rewrite_expr).collect::<Vec<_>>(),
+        ),
+        parse::Expr::Let(binder, bind, then) => ir::Expr::Let(
+            rewrite_binder(binder),
             Box::new(rewrite_expr(bind)),
             Box::new(rewrite_expr(then)),
         ),
@@ -133,11 +146,49 @@ fn rewrite_expr(span_expr: &(parse::Expr, parse::Span)) -> ir::Expr {
             }
             ir::Expr::Call(span_expr.1.clone(), exprs)
         }
+        parse::Expr::Index(container, index) => ir::Expr::Index(
+            span_expr.1.clone(),
+            Box::new(rewrite_expr(container)),
+            ir::IndexExpr::Dynamic(Box::new(rewrite_expr(index))),
+        ),
         parse::Expr::If(cond, then_expr, else_expr) => ir::Expr::If {
             span: span_expr.1.clone(),
             cond: rewrite_expr_spanbox(cond),
             then_expr: rewrite_expr_spanbox(then_expr),
             else_expr: rewrite_expr_spanbox(else_expr),
         },
+        parse::Expr::Loop(body) => {
+            ir::Expr::Loop(span_expr.1.clone(), Box::new(rewrite_expr(body)))
+        }
+        parse::Expr::While(cond, body) => ir::Expr::While {
+            span: span_expr.1.clone(),
+            cond: rewrite_expr_spanbox(cond),
+            body: rewrite_expr_spanbox(body),
+        },
+        parse::Expr::Break(val) => ir::Expr::Break(
+            span_expr.1.clone(),
+            val.as_ref().map(|val| Box::new(rewrite_expr(val))),
+        ),
+        parse::Expr::Continue => ir::Expr::Continue(span_expr.1.clone()),
+        parse::Expr::Return(val) => ir::Expr::Return(
+            span_expr.1.clone(),
+            val.as_ref().map(|val| Box::new(rewrite_expr(val))),
+        ),
+        parse::Expr::Assign(name, val) => ir::Expr::Assign(
+            span_expr.1.clone(),
+            Spanned::new(name.1.clone(), ir::Ident::from(name.0.as_str())),
+            Box::new(rewrite_expr(val)),
+        ),
+        parse::Expr::RefNew(val) => {
+            ir::Expr::RefNew(span_expr.1.clone(), Box::new(rewrite_expr(val)))
+        }
+        parse::Expr::RefGet(cell) => {
+            ir::Expr::RefGet(span_expr.1.clone(), Box::new(rewrite_expr(cell)))
+        }
+        parse::Expr::RefSet(cell, val) => ir::Expr::RefSet(
+            span_expr.1.clone(),
+            Box::new(rewrite_expr(cell)),
+            Box::new(rewrite_expr(val)),
+        ),
     }
 }
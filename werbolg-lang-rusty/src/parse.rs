@@ -1,6 +1,6 @@
 // this is chumsky nanorust example without the evaluation
 
-use alloc::{boxed::Box, string::String, string::ToString, vec::Vec};
+use alloc::{boxed::Box, string::String, string::ToString, vec, vec::Vec};
 use chumsky::{prelude::*, stream::Stream};
 use core::fmt;
 
@@ -21,6 +21,12 @@ enum Token {
     Let,
     If,
     Else,
+    Loop,
+    While,
+    Break,
+    Continue,
+    Return,
+    Ref,
 }
 
 impl fmt::Display for Token {
@@ -37,6 +43,12 @@ impl fmt::Display for Token {
             Token::Let => write!(f, "let"),
             Token::If => write!(f, "if"),
             Token::Else => write!(f, "else"),
+            Token::Loop => write!(f, "loop"),
+            Token::While => write!(f, "while"),
+            Token::Break => write!(f, "break"),
+            Token::Continue => write!(f, "continue"),
+            Token::Return => write!(f, "return"),
+            Token::Ref => write!(f, "ref"),
         }
     }
 }
@@ -71,6 +83,12 @@ fn lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = Simple<char>> {
         "let" => Token::Let,
         "if" => Token::If,
         "else" => Token::Else,
+        "loop" => Token::Loop,
+        "while" => Token::While,
+        "break" => Token::Break,
+        "continue" => Token::Continue,
+        "return" => Token::Return,
+        "ref" => Token::Ref,
         "true" => Token::Bool(ident),
         "false" => Token::Bool(ident),
         "null" => Token::Null,
@@ -126,11 +144,35 @@ pub enum Expr {
     Literal(Literal),
     List(Vec<Spanned<Self>>),
     Local(String),
-    Let(String, Box<Spanned<Self>>, Box<Spanned<Self>>),
+    Tuple(Vec<Spanned<Self>>),
+    Let(Binder, Box<Spanned<Self>>, Box<Spanned<Self>>),
     Then(Box<Spanned<Self>>, Box<Spanned<Self>>),
     Binary(Box<Spanned<Self>>, BinaryOp, Box<Spanned<Self>>),
     Call(Box<Spanned<Self>>, Vec<Spanned<Self>>),
+    Index(Box<Spanned<Self>>, Box<Spanned<Self>>),
     If(Box<Spanned<Self>>, Box<Spanned<Self>>, Box<Spanned<Self>>),
+    Loop(Box<Spanned<Self>>),
+    While(Box<Spanned<Self>>, Box<Spanned<Self>>),
+    Break(Option<Box<Spanned<Self>>>),
+    Continue,
+    Return(Option<Box<Spanned<Self>>>),
+    Assign(Spanned<String>, Box<Spanned<Self>>),
+    RefNew(Box<Spanned<Self>>),
+    RefGet(Box<Spanned<Self>>),
+    RefSet(Box<Spanned<Self>>, Box<Spanned<Self>>),
+}
+
+// An operation applying to the expression before it, e.g. `f(a)` or `a[i]`
+enum Postfix {
+    Call(Vec<Spanned<Expr>>),
+    Index(Box<Spanned<Expr>>),
+}
+
+// The left side of a let expression
+#[derive(Debug)]
+pub enum Binder {
+    Ident(String),
+    Tuple(Vec<Binder>),
 }
 
 // A function node in the AST.
@@ -159,25 +201,68 @@ fn expr_parser() -> impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + C
                 .separated_by(just(Token::Ctrl(',')))
                 .allow_trailing();
 
+            // An ident or a tuple of binders, like (a, (b, c))
+            let binder = recursive(|binder| {
+                ident.map(Binder::Ident).or(binder
+                    .separated_by(just(Token::Ctrl(',')))
+                    .allow_trailing()
+                    .delimited_by(just(Token::Ctrl('(')), just(Token::Ctrl(')')))
+                    .map(Binder::Tuple))
+            });
+
             // A let expression
             let let_ = just(Token::Let)
-                .ignore_then(ident)
+                .ignore_then(binder)
                 .then_ignore(just(Token::Op("=".to_string())))
-                .then(raw_expr)
+                .then(raw_expr.clone())
                 .then_ignore(just(Token::Ctrl(';')))
                 .then(expr.clone())
                 .map(|((name, val), body)| Expr::Let(name, Box::new(val), Box::new(body)));
 
+            // A break expression, with an optional value for the loop
+            let break_ = just(Token::Break)
+                .ignore_then(raw_expr.clone().or_not())
+                .map(|val| Expr::Break(val.map(Box::new)));
+
+            let continue_ = just(Token::Continue).map(|_| Expr::Continue);
+
+            // A return expression, with an optional value for the function
+            let return_ = just(Token::Return)
+                .ignore_then(raw_expr.clone().or_not())
+                .map(|val| Expr::Return(val.map(Box::new)));
+
+            // A new reference cell
+            let ref_ = just(Token::Ref)
+                .ignore_then(raw_expr.clone())
+                .map(|val| Expr::RefNew(Box::new(val)));
+
             let list = items
                 .clone()
                 .delimited_by(just(Token::Ctrl('[')), just(Token::Ctrl(']')))
                 .map(Expr::List);
 
+            // A tuple needs at least one comma, to be different from a parenthesised expression
+            let tuple = expr
+                .clone()
+                .then_ignore(just(Token::Ctrl(',')))
+                .then(items.clone())
+                .delimited_by(just(Token::Ctrl('(')), just(Token::Ctrl(')')))
+                .map(|(first, rest)| {
+                    let mut elements = vec![first];
+                    elements.extend(rest);
+                    Expr::Tuple(elements)
+                });
+
             // 'Atoms' are expressions that contain no ambiguity
             let atom = val
                 .or(ident.map(Expr::Local))
                 .or(let_)
+                .or(break_)
+                .or(continue_)
+                .or(return_)
+                .or(ref_)
                 .or(list)
+                .or(tuple)
                 .map_with_span(|expr, span| (expr, span))
                 // Atoms can also just be normal expressions, but surrounded with parentheses
                 .or(expr
@@ -204,26 +289,40 @@ fn expr_parser() -> impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + C
                     |span| (Expr::Error, span),
                 ));
 
-            // Function calls have very high precedence so we prioritise them
+            // Function calls and indexing have very high precedence so we prioritise them
             let call = atom
                 .then(
                     items
                         .delimited_by(just(Token::Ctrl('(')), just(Token::Ctrl(')')))
-                        .map_with_span(|args, span: Span| (args, span))
+                        .map(Postfix::Call)
+                        .or(expr
+                            .clone()
+                            .delimited_by(just(Token::Ctrl('[')), just(Token::Ctrl(']')))
+                            .map(|index| Postfix::Index(Box::new(index))))
+                        .map_with_span(|postfix, span: Span| (postfix, span))
                         .repeated(),
                 )
-                .foldl(|f, args| {
-                    let span = f.1.start..args.1.end;
-                    (Expr::Call(Box::new(f), args.0), span)
+                .foldl(|f, postfix| {
+                    let span = f.1.start..postfix.1.end;
+                    match postfix.0 {
+                        Postfix::Call(args) => (Expr::Call(Box::new(f), args), span),
+                        Postfix::Index(index) => (Expr::Index(Box::new(f), index), span),
+                    }
                 });
 
+            // Dereference of a reference cell
+            let deref = just(Token::Op("*".to_string()))
+                .ignore_then(call.clone())
+                .map_with_span(|cell, span: Span| (Expr::RefGet(Box::new(cell)), span))
+                .or(call.clone());
+
             // Product ops (multiply and divide) have equal precedence
             let op = just(Token::Op("*".to_string()))
                 .to(BinaryOp::Mul)
                 .or(just(Token::Op("/".to_string())).to(BinaryOp::Div));
-            let product = call
+            let product = deref
                 .clone()
-                .then(op.then(call).repeated())
+                .then(op.then(deref).repeated())
                 .foldl(|a, (op, b)| {
                     let span = a.1.start..b.1.end;
                     (Expr::Binary(Box::new(a), op, Box::new(b)), span)
@@ -253,7 +352,21 @@ fn expr_parser() -> impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + C
                     (Expr::Binary(Box::new(a), op, Box::new(b)), span)
                 });
 
-            compare
+            // Assignments of a local or of the content of a reference cell
+            let assign = ident
+                .map_with_span(|name, span: Span| (name, span))
+                .then_ignore(just(Token::Op("=".to_string())))
+                .then(raw_expr.clone())
+                .map_with_span(|(name, val), span: Span| (Expr::Assign(name, Box::new(val)), span));
+            let ref_set = just(Token::Op("*".to_string()))
+                .ignore_then(call)
+                .then_ignore(just(Token::Op("=".to_string())))
+                .then(raw_expr.clone())
+                .map_with_span(|(cell, val), span: Span| {
+                    (Expr::RefSet(Box::new(cell), Box::new(val)), span)
+                });
+
+            assign.or(ref_set).or(compare)
         });
 
         // Blocks are expressions but delimited with braces
@@ -296,8 +409,19 @@ fn expr_parser() -> impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + C
                 })
         });
 
-        // Both blocks and `if` are 'block expressions' and can appear in the place of statements
-        let block_expr = block.or(if_).labelled("block");
+        let loop_ = just(Token::Loop)
+            .ignore_then(block.clone())
+            .map_with_span(|body, span: Span| (Expr::Loop(Box::new(body)), span));
+
+        let while_ = just(Token::While)
+            .ignore_then(expr.clone())
+            .then(block.clone())
+            .map_with_span(|(cond, body), span: Span| {
+                (Expr::While(Box::new(cond), Box::new(body)), span)
+            });
+
+        // Blocks, `if` and loops are 'block expressions' and can appear in the place of statements
+        let block_expr = block.or(if_).or(loop_).or(while_).labelled("block");
 
         let block_chain = block_expr
             .clone()
@@ -337,6 +461,7 @@ fn funcs_parser() -> impl Parser<Token, Vec<(String, Span, Func)>, Error = Simpl
     // Argument lists are just identifiers separated by commas, surrounded by parentheses
     let args = ident
         .clone()
+        .map_with_span(|arg, span| (arg, span))
         .separated_by(just(Token::Ctrl(',')))
         .allow_trailing()
         .delimited_by(just(Token::Ctrl('(')), just(Token::Ctrl(')')))
@@ -369,7 +494,7 @@ fn funcs_parser() -> impl Parser<Token, Vec<(String, Span, Func)>, Error = Simpl
                 Func {
                     args: args
                         .into_iter()
-                        .map(|a| Variable(ir::Spanned::new(name.1.clone(), ir::Ident(a))))
+                        .map(|(a, span)| Variable(ir::Spanned::new(span, ir::Ident(a))))
                         .collect(),
                     body,
                 },
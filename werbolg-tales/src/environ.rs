@@ -1,6 +1,6 @@
 use super::value::{self, Value};
-use werbolg_compile::{CallArity, CompilationError, Environment};
-use werbolg_core::{AbsPath, Ident, Literal, Namespace, Span};
+use werbolg_compile::{CallArity, CompilationError, Environment, LiteralCodec};
+use werbolg_core::{AbsPath, Ident, Literal, Namespace, NifId, Span};
 use werbolg_exec::{ExecutionError, NIF, NIFCall, WAllocator};
 
 fn nif_plus<A: WAllocator>(_: &A, args: &[Value]) -> Result<Value, ExecutionError> {
@@ -56,6 +56,10 @@ fn nif_le<A: WAllocator>(_: &A, args: &[Value]) -> Result<Value, ExecutionError>
     Ok(Value::Bool(ret))
 }
 
+fn nif_list<A: WAllocator>(_: &A, args: &[Value]) -> Result<Value, ExecutionError> {
+    Ok(Value::List(args.into()))
+}
+
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub enum MyLiteral {
     Bool(bool),
@@ -88,12 +92,44 @@ pub fn literal_mapper(span: Span, lit: Literal) -> Result<MyLiteral, Compilation
     }
 }
 
-pub fn create_env() -> Environment<NIF<crate::DummyAlloc, MyLiteral, (), Value>, Value> {
+fn encode_literal(lit: &MyLiteral, out: &mut Vec<u8>) {
+    match lit {
+        MyLiteral::Bool(b) => out.extend_from_slice(&[0, *b as u8]),
+        MyLiteral::Int(n) => {
+            out.push(1);
+            out.extend_from_slice(&n.to_le_bytes())
+        }
+    }
+}
+
+fn decode_literal(bytes: &[u8]) -> Option<MyLiteral> {
+    match bytes {
+        [0, 0] => Some(MyLiteral::Bool(false)),
+        [0, 1] => Some(MyLiteral::Bool(true)),
+        [1, n @ ..] => Some(MyLiteral::Int(value::ValueInt::from_le_bytes(
+            n.try_into().ok()?,
+        ))),
+        _ => None,
+    }
+}
+
+pub fn literal_codec() -> LiteralCodec<MyLiteral> {
+    LiteralCodec {
+        encode: encode_literal,
+        decode: decode_literal,
+    }
+}
+
+pub type TalesEnvironment = Environment<NIF<crate::DummyAlloc, MyLiteral, (), Value>, Value>;
+
+/// Create the environment, also returning the NIF to use as sequence constructor
+pub fn create_env() -> (TalesEnvironment, NifId) {
     macro_rules! add_pure_nif {
         ($env:ident, $i:literal, $arity:literal, $e:expr) => {
-            let nif = NIFCall::Pure($e).info($i, CallArity::try_from($arity as usize).unwrap());
+            let arity = CallArity::try_from($arity as usize).unwrap();
+            let nif = NIFCall::Pure($e).info($i, arity);
             let path = AbsPath::new(&Namespace::root(), &Ident::from($i));
-            $env.add_nif(&path, nif).unwrap();
+            $env.add_nif_with_arity(&path, nif, arity).unwrap();
         };
     }
 
@@ -105,5 +141,10 @@ pub fn create_env() -> Environment<NIF<crate::DummyAlloc, MyLiteral, (), Value>,
     add_pure_nif!(env, "<=", 2, nif_le);
     add_pure_nif!(env, "neg", 1, nif_neg);
 
-    env
+    // the sequence constructor takes any number of elements
+    let list = NIFCall::Pure(nif_list).info_variadic("list");
+    let list_path = AbsPath::new(&Namespace::root(), &Ident::from("list"));
+    let list_nif = env.add_nif(&list_path, list).unwrap();
+
+    (env, list_nif)
 }
@@ -4,8 +4,11 @@ use super::environ;
 use super::value::Value;
 use super::{Frontend, TalesParams};
 use hashbrown::HashSet;
-use werbolg_compile::{Environment, InstructionAddress, code_dump, compile};
-use werbolg_core::{AbsPath, Ident, Module, Namespace, id::IdF};
+use werbolg_compile::{
+    CompilationError, Environment, InstructionAddress, code_dump, compile_with_warnings,
+    read_bytecode,
+};
+use werbolg_core::{AbsPath, Ident, Module, Namespace, NifId, id::IdF};
 use werbolg_exec::{ExecutionEnviron, ExecutionMachine, ExecutionParams, NIF, WAllocator};
 use werbolg_lang_common::{Report, ReportKind, Source};
 
@@ -48,7 +51,9 @@ pub fn run_frontend(
     };
 
     if params.dump_ir {
-        std::println!("{:#?}", module);
+        let mut out = String::new();
+        werbolg_core::text::write_module(&mut out, &module).expect("writing to string work");
+        std::print!("{}", out);
     }
     Ok((source, module))
 }
@@ -63,7 +68,8 @@ pub fn report_print(source: &Source, report: Report) -> Result<(), Box<dyn Error
 pub fn run_compile<A>(
     params: &TalesParams,
     env: &mut Environment<NIF<A, environ::MyLiteral, (), Value>, Value>,
-    source: Source,
+    sequence_constructor: NifId,
+    source: &Source,
     module: Module,
 ) -> Result<werbolg_compile::CompilationUnit<environ::MyLiteral>, Box<dyn Error>> {
     let module_ns = Namespace::root().append(Ident::from("main"));
@@ -71,19 +77,70 @@ pub fn run_compile<A>(
 
     let compilation_params = werbolg_compile::CompilationParams {
         literal_mapper: environ::literal_mapper,
-        sequence_constructor: None,
+        sequence_constructor: Some(sequence_constructor),
+        warning_levels: params.warning_levels.clone(),
     };
 
-    let exec_module = match compile(&compilation_params, modules, env) {
-        Err(e) => {
-            if let Some(span) = e.span() {
-                let report = Report::new(ReportKind::Error, format!("Compilation Error: {:?}", e))
-                    .lines_before(1)
-                    .lines_after(1)
-                    .highlight(span, format!("compilation error here"));
-                report_print(&source, report)?;
+    let mut warnings = Vec::new();
+    let compiled = compile_with_warnings(&compilation_params, modules, env, &mut warnings);
+    for warning in warnings {
+        let report = Report::new(
+            ReportKind::Warning,
+            format!("Compilation Warning: {:?}", warning.kind),
+        )
+        .code(warning.code().to_string())
+        .lines_before(1)
+        .lines_after(1)
+        .highlight(warning.span, "warning here".into());
+        report_print(source, report)?;
+    }
+
+    let exec_module = match compiled {
+        Err(es) => {
+            for e in es.iter() {
+                let (context, error) = e.split_context();
+                if let Some(span) = error.span() {
+                    let header = match error {
+                        CompilationError::MissingSymbol(_, path, _) => {
+                            format!("Compilation Error: MissingSymbol({:?})", path)
+                        }
+                        _ => format!("Compilation Error: {:?}", error),
+                    };
+                    let mut report = Report::new(ReportKind::Error, header)
+                        .lines_before(1)
+                        .lines_after(1)
+                        .highlight(span, "compilation error here".into());
+                    if let Some(context) = context {
+                        report = report.note(context.into());
+                    }
+                    match error {
+                        CompilationError::DeniedWarning(warning) => {
+                            report = report.code(warning.code().to_string());
+                        }
+                        CompilationError::MissingSymbol(_, _, suggestions) => {
+                            if !suggestions.similar.is_empty() {
+                                let similar = suggestions
+                                    .similar
+                                    .iter()
+                                    .map(|path| format!("`{}`", path_name(path.idents().iter())))
+                                    .collect::<Vec<_>>();
+                                report =
+                                    report.note(format!("did you mean {}?", similar.join(" or ")));
+                            }
+                            for abspath in suggestions.not_imported.iter() {
+                                let name = path_name(abspath.components().map(|(_, ident)| ident));
+                                report =
+                                    report.note(format!("`{}` exists but is not imported", name));
+                            }
+                        }
+                        _ => {}
+                    }
+                    report_print(source, report)?;
+                } else {
+                    println!("compilation error: {:?}", e);
+                }
             }
-            return Err(format!("compilation error {:?}", e).into());
+            return Err(format!("{} compilation error(s)", es.len()).into());
         }
         Ok(m) => m,
     };
@@ -97,6 +154,28 @@ pub fn run_compile<A>(
     Ok(exec_module)
 }
 
+pub fn run_load_bytecode(
+    params: &TalesParams,
+    args: &[String],
+) -> Result<werbolg_compile::CompilationUnit<environ::MyLiteral>, Box<dyn Error>> {
+    if args.is_empty() {
+        crate::help();
+        return Err("no file specified".into());
+    }
+
+    let data = std::fs::read(&args[0])?;
+    let exec_module = read_bytecode(&data, &environ::literal_codec())
+        .map_err(|e| format!("bytecode error {:?}", e))?;
+
+    if params.dump_instr {
+        let mut out = String::new();
+        code_dump(&mut out, &exec_module.code, &exec_module.funs).expect("writing to string work");
+        println!("{}", out);
+    }
+
+    Ok(exec_module)
+}
+
 pub struct DummyAlloc;
 
 impl WAllocator for DummyAlloc {
@@ -105,15 +184,15 @@ impl WAllocator for DummyAlloc {
 
 pub fn run_exec(
     params: &TalesParams,
+    source: Option<&Source>,
     ee: werbolg_exec::WerRefCount<ExecutionEnviron<DummyAlloc, environ::MyLiteral, (), Value>>,
     exec_module: werbolg_exec::WerRefCount<werbolg_compile::CompilationUnit<environ::MyLiteral>>,
 ) -> Result<(), Box<dyn Error>> {
     let module_ns = Namespace::root().append(Ident::from("main"));
 
     let entry_point = exec_module
-        .funs_tbl
-        .get(&AbsPath::new(&module_ns, &Ident::from("main")))
-        .expect("existing function as entry point");
+        .get_public_fun(&AbsPath::new(&module_ns, &Ident::from("main")))
+        .expect("existing public function as entry point");
 
     let execution_params = ExecutionParams {
         literal_to_value: environ::literal_to_value,
@@ -146,12 +225,53 @@ pub fn run_exec(
 
     match ret {
         Err(e) => {
-            let mut out = String::new();
-            em.debug_state(&mut out).unwrap();
+            // the source is only the one of the main module, so other spans cannot be shown
+            let source_span = |ip: InstructionAddress| {
+                let span = em.module.instruction_span(ip)?;
+                let source = source?;
+                (em.module.source_map.namespace(ip) == Some(&module_ns)).then_some((source, span))
+            };
+            if let Some((source, span)) = source_span(em.ip) {
+                let report = Report::new(ReportKind::Error, format!("Execution Error: {:?}", e))
+                    .lines_before(1)
+                    .lines_after(1)
+                    .highlight(span.clone(), "execution error here".into());
+                report_print(source, report)?;
+            } else {
+                let mut out = String::new();
+                em.debug_state(&mut out).unwrap();
+
+                println!("error: {:?} at {}", e, em.ip);
+                println!("{}", out);
+            }
 
-            println!("error: {:?} at {}", e, em.ip);
-            println!("{}", out);
-            return Err(format!("execution error").into());
+            println!("backtrace:");
+            for (i, frame) in em.backtrace().iter().enumerate() {
+                let name = match (&frame.name, frame.fun) {
+                    (Some(name), _) => name.0.clone(),
+                    (None, Some(funid)) => format!("<anonymous {:?}>", funid),
+                    (None, None) => "<unknown>".into(),
+                };
+                let location = source_span(frame.ip)
+                    .and_then(|(source, span)| {
+                        let start = source.lines_map.resolve(span.start)?;
+                        Some(format!(
+                            "{}:{}:{}",
+                            source.file_unit.filename,
+                            start.line().0 + 1,
+                            start.col() + 1
+                        ))
+                    })
+                    .unwrap_or_else(|| format!("ip {}", frame.ip));
+                print!("  {:>2}: {} at {}", i, name, location);
+                match frame.tail_calls {
+                    0 => {}
+                    1 => print!(" (1 frame elided by tail call)"),
+                    n => print!(" ({} frames elided by tail calls)", n),
+                }
+                println!();
+            }
+            Err("execution error".into())
         }
         Ok(val) => {
             println!("{:?}", val);
@@ -160,6 +280,13 @@ pub fn run_exec(
     }
 }
 
+fn path_name<'a>(idents: impl Iterator<Item = &'a Ident>) -> String {
+    idents
+        .map(|ident| ident.0.as_str())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
 fn get_file(path: &std::path::Path) -> std::io::Result<Source> {
     let path = std::path::PathBuf::from(&path);
     let content = std::fs::read_to_string(&path).expect("file read");
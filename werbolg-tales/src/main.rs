@@ -9,6 +9,7 @@ mod value;
 use environ::create_env;
 use exec::*;
 use params::{Frontend, TalesParams};
+use werbolg_compile::{WarningCode, WarningLevel, WarningLevels};
 use werbolg_exec::WerRefCount;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -20,6 +21,9 @@ enum Flag {
     ExecStepTrace,
     StepAddress(u64),
     Frontend(Frontend),
+    Bytecode,
+    WriteBytecode(String),
+    Warning(WarningCode, WarningLevel),
 }
 
 fn version() {
@@ -39,10 +43,22 @@ Options:
   --exec-step-trace   Trace every step of execution
   --step-address <a>  Address to print a debug trace
   --frontend <value>  Set the frontend to use a specific frontend
+  --bytecode          Run a bytecode file instead of a source file
+  --write-bytecode <f>
+                      Compile the source file to the bytecode file <f> instead of running it
+  --allow <warning>   Do not report the warning (e.g. unused-binding)
+  --deny <warning>    Report the warning as a compilation error
     "#
     );
 }
 
+fn warning_flag(name: &str, level: WarningLevel) -> Result<Flag, String> {
+    match WarningCode::from_name(name) {
+        Some(code) => Ok(Flag::Warning(code, level)),
+        None => Err(format!("unknown warning {}", name)),
+    }
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let options = args::ArgOptions {
         short: &[],
@@ -83,6 +99,22 @@ fn main() -> Result<(), Box<dyn Error>> {
                     }
                 })),
             ),
+            (
+                "bytecode",
+                args::FlagDescr::NoArg(Box::new(|| Flag::Bytecode)),
+            ),
+            (
+                "write-bytecode",
+                args::FlagDescr::Arg(Box::new(|s| Ok(Flag::WriteBytecode(s)))),
+            ),
+            (
+                "allow",
+                args::FlagDescr::Arg(Box::new(|s| warning_flag(&s, WarningLevel::Allow))),
+            ),
+            (
+                "deny",
+                args::FlagDescr::Arg(Box::new(|s| warning_flag(&s, WarningLevel::Deny))),
+            ),
         ],
     };
     let (flags, args) = args::args(options)?;
@@ -116,6 +148,20 @@ fn main() -> Result<(), Box<dyn Error>> {
             _ => None,
         })
         .last();
+    let bytecode = flags.contains(&Flag::Bytecode);
+    let write_bytecode = flags
+        .iter()
+        .filter_map(|f| match f {
+            Flag::WriteBytecode(f) => Some(f.clone()),
+            _ => None,
+        })
+        .next_back();
+    let mut warning_levels = WarningLevels::default();
+    for flag in flags.iter() {
+        if let Flag::Warning(code, level) = flag {
+            warning_levels.set(*code, *level)
+        }
+    }
 
     let params = TalesParams {
         dump_ir,
@@ -123,17 +169,34 @@ fn main() -> Result<(), Box<dyn Error>> {
         exec_step_trace,
         step_address,
         frontend,
+        bytecode,
+        write_bytecode,
+        warning_levels,
     };
 
-    let (source, module) = run_frontend(&params, &args)?;
+    let (mut env, sequence_constructor) = create_env();
+    let (compile_unit, source) = if params.bytecode {
+        (run_load_bytecode(&params, &args)?, None)
+    } else {
+        let (source, module) = run_frontend(&params, &args)?;
+        let compile_unit = run_compile(&params, &mut env, sequence_constructor, &source, module)?;
+        (compile_unit, Some(source))
+    };
 
-    let mut env = create_env();
-    let compile_unit = run_compile(&params, &mut env, source, module)?;
+    if let Some(path) = &params.write_bytecode {
+        let bytecode = werbolg_compile::write_bytecode(&compile_unit, &environ::literal_codec());
+        std::fs::write(path, bytecode)?;
+        return Ok(());
+    }
 
     let ee = werbolg_exec::WerRefCount::new(
         werbolg_exec::ExecutionEnviron::from_compile_environment(env.finalize()),
     );
-    run_exec(&params, ee, WerRefCount::new(compile_unit))?;
+    if params.bytecode {
+        werbolg_exec::verify(&compile_unit, &ee)
+            .map_err(|e| format!("invalid bytecode {:?}", e))?;
+    }
+    run_exec(&params, source.as_ref(), ee, WerRefCount::new(compile_unit))?;
 
     Ok(())
 }
@@ -1,9 +1,14 @@
+use werbolg_compile::WarningLevels;
+
 pub struct TalesParams {
     pub dump_ir: bool,
     pub dump_instr: bool,
     pub exec_step_trace: bool,
     pub step_address: Vec<u64>,
     pub frontend: Option<Frontend>,
+    pub bytecode: bool,
+    pub write_bytecode: Option<String>,
+    pub warning_levels: WarningLevels,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -0,0 +1,297 @@
+//! Helpers to write IR modules by hand, for constructs not (yet) supported by `module!`
+
+use alloc::{boxed::Box, string::ToString, vec::Vec};
+use werbolg_core::{Ident, Literal, Path, PathType, Span, Spanned, Variable, ir};
+
+fn span() -> Span {
+    0..0
+}
+
+/// Create a relative path from a `::` separated string, e.g. `"Shape::Circle"`
+fn path(s: &str) -> Path {
+    Path::new_raw(PathType::Relative, s.split("::").map(Ident::from).collect())
+}
+
+pub fn module(statements: Vec<ir::Statement>) -> ir::Module {
+    ir::Module { statements }
+}
+
+pub fn use_(namespace: &str, hiding: &[&str], renames: &[(&str, &str)]) -> ir::Statement {
+    ir::Statement::Use(
+        span(),
+        ir::Use {
+            namespace: path(namespace),
+            hiding: hiding.iter().map(|h| Ident::from(*h)).collect(),
+            renames: renames
+                .iter()
+                .map(|(x, y)| (Ident::from(*x), Ident::from(*y)))
+                .collect(),
+        },
+    )
+}
+
+pub fn fun(name: &str, vars: &[&str], body: ir::Expr) -> ir::Statement {
+    ir::Statement::Function(
+        span(),
+        ir::FunDef {
+            privacy: ir::Privacy::Public,
+            name: Ident::from(name),
+        },
+        funimpl(vars, body),
+    )
+}
+
+pub fn fun_private(name: &str, vars: &[&str], body: ir::Expr) -> ir::Statement {
+    ir::Statement::Function(
+        span(),
+        ir::FunDef {
+            privacy: ir::Privacy::Private,
+            name: Ident::from(name),
+        },
+        funimpl(vars, body),
+    )
+}
+
+pub fn value(name: &str, body: ir::Expr) -> ir::Statement {
+    ir::Statement::Value(
+        span(),
+        ir::ValueDef {
+            privacy: ir::Privacy::Public,
+            name: Ident::from(name),
+        },
+        body,
+    )
+}
+
+pub fn statement(expr: ir::Expr) -> ir::Statement {
+    ir::Statement::Expr(expr)
+}
+
+fn funimpl(vars: &[&str], body: ir::Expr) -> ir::FunImpl {
+    ir::FunImpl {
+        vars: vars
+            .iter()
+            .map(|v| Variable(Spanned::new(span(), Ident::from(*v))))
+            .collect(),
+        body,
+    }
+}
+
+pub fn struct_(name: &str, fields: &[&str]) -> ir::Statement {
+    ir::Statement::Struct(span(), structdef(name, fields))
+}
+
+pub fn struct_private(name: &str, fields: &[&str]) -> ir::Statement {
+    ir::Statement::Struct(
+        span(),
+        ir::StructDef {
+            privacy: ir::Privacy::Private,
+            ..structdef(name, fields)
+        },
+    )
+}
+
+pub fn enum_(name: &str, variants: &[(&str, &[&str])]) -> ir::Statement {
+    ir::Statement::Enum(
+        span(),
+        ir::EnumDef {
+            privacy: ir::Privacy::Public,
+            name: Spanned::new(span(), Ident::from(name)),
+            variants: variants
+                .iter()
+                .map(|(name, fields)| ir::Variant(structdef(name, fields)))
+                .collect(),
+        },
+    )
+}
+
+fn structdef(name: &str, fields: &[&str]) -> ir::StructDef {
+    ir::StructDef {
+        privacy: ir::Privacy::Public,
+        name: Spanned::new(span(), Ident::from(name)),
+        fields: fields
+            .iter()
+            .map(|f| Spanned::new(span(), Ident::from(*f)))
+            .collect(),
+    }
+}
+
+pub fn int(n: u64) -> ir::Expr {
+    ir::Expr::Literal(span(), Literal::number(&n.to_string()))
+}
+
+pub fn var(name: &str) -> ir::Expr {
+    ir::Expr::Path(span(), path(name))
+}
+
+pub fn call(f: ir::Expr, args: Vec<ir::Expr>) -> ir::Expr {
+    let mut exprs = Vec::with_capacity(args.len() + 1);
+    exprs.push(f);
+    exprs.extend(args);
+    ir::Expr::Call(span(), exprs)
+}
+
+pub fn struct_seq(constr: &str, fields: Vec<ir::Expr>) -> ir::Expr {
+    ir::Expr::Struct(
+        span(),
+        Spanned::new(span(), path(constr)),
+        ir::FieldsExpr::Sequential(fields),
+    )
+}
+
+pub fn struct_named(constr: &str, fields: Vec<(&str, ir::Expr)>) -> ir::Expr {
+    ir::Expr::Struct(
+        span(),
+        Spanned::new(span(), path(constr)),
+        ir::FieldsExpr::Named(
+            fields
+                .into_iter()
+                .map(|(f, e)| (Spanned::new(span(), Ident::from(f)), e))
+                .collect(),
+        ),
+    )
+}
+
+pub fn sequence(elements: Vec<ir::Expr>) -> ir::Expr {
+    ir::Expr::Sequence(span(), elements)
+}
+
+pub fn index(container: ir::Expr, index: ir::Expr) -> ir::Expr {
+    ir::Expr::Index(
+        span(),
+        Box::new(container),
+        ir::IndexExpr::Dynamic(Box::new(index)),
+    )
+}
+
+pub fn index_static(container: ir::Expr, index: u32) -> ir::Expr {
+    ir::Expr::Index(span(), Box::new(container), ir::IndexExpr::Static(index))
+}
+
+pub fn tuple(elements: Vec<ir::Expr>) -> ir::Expr {
+    ir::Expr::Tuple(span(), elements)
+}
+
+pub fn lambda(vars: &[&str], body: ir::Expr) -> ir::Expr {
+    ir::Expr::Lambda(span(), Box::new(funimpl(vars, body)))
+}
+
+pub fn let_(name: &str, bind: ir::Expr, then: ir::Expr) -> ir::Expr {
+    let_binder(ir::Binder::Ident(Ident::from(name)), bind, then)
+}
+
+pub fn let_binder(binder: ir::Binder, bind: ir::Expr, then: ir::Expr) -> ir::Expr {
+    ir::Expr::Let(binder, Box::new(bind), Box::new(then))
+}
+
+pub fn if_(cond: ir::Expr, then_expr: ir::Expr, else_expr: ir::Expr) -> ir::Expr {
+    ir::Expr::If {
+        span: span(),
+        cond: Box::new(Spanned::new(span(), cond)),
+        then_expr: Box::new(Spanned::new(span(), then_expr)),
+        else_expr: Box::new(Spanned::new(span(), else_expr)),
+    }
+}
+
+pub fn loop_(body: ir::Expr) -> ir::Expr {
+    ir::Expr::Loop(span(), Box::new(body))
+}
+
+pub fn while_(cond: ir::Expr, body: ir::Expr) -> ir::Expr {
+    ir::Expr::While {
+        span: span(),
+        cond: Box::new(Spanned::new(span(), cond)),
+        body: Box::new(Spanned::new(span(), body)),
+    }
+}
+
+pub fn break_(value: Option<ir::Expr>) -> ir::Expr {
+    ir::Expr::Break(span(), value.map(Box::new))
+}
+
+pub fn continue_() -> ir::Expr {
+    ir::Expr::Continue(span())
+}
+
+pub fn return_(value: Option<ir::Expr>) -> ir::Expr {
+    ir::Expr::Return(span(), value.map(Box::new))
+}
+
+pub fn assign(name: &str, value: ir::Expr) -> ir::Expr {
+    ir::Expr::Assign(
+        span(),
+        Spanned::new(span(), Ident::from(name)),
+        Box::new(value),
+    )
+}
+
+pub fn ref_new(value: ir::Expr) -> ir::Expr {
+    ir::Expr::RefNew(span(), Box::new(value))
+}
+
+pub fn ref_get(cell: ir::Expr) -> ir::Expr {
+    ir::Expr::RefGet(span(), Box::new(cell))
+}
+
+pub fn ref_set(cell: ir::Expr, value: ir::Expr) -> ir::Expr {
+    ir::Expr::RefSet(span(), Box::new(cell), Box::new(value))
+}
+
+pub fn match_(expr: ir::Expr, arms: Vec<(ir::Pattern, ir::Expr)>) -> ir::Expr {
+    ir::Expr::Match {
+        span: span(),
+        expr: Box::new(expr),
+        arms: arms
+            .into_iter()
+            .map(|(pattern, expr)| ir::MatchArm {
+                span: span(),
+                pattern,
+                expr,
+            })
+            .collect(),
+    }
+}
+
+pub fn pat_int(n: u64) -> ir::Pattern {
+    ir::Pattern::Literal(span(), Literal::number(&n.to_string()))
+}
+
+pub fn pat(binder: ir::Binder) -> ir::Pattern {
+    ir::Pattern::Binder(binder)
+}
+
+pub fn ignore() -> ir::Binder {
+    ir::Binder::Ignore
+}
+
+pub fn bind(name: &str) -> ir::Binder {
+    ir::Binder::Ident(Ident::from(name))
+}
+
+pub fn bind_named(
+    constr: &str,
+    fields: &[(&str, ir::Binder)],
+    ellipsis: ir::BindEllipsis,
+) -> ir::Binder {
+    ir::Binder::Deconstruct(
+        path(constr),
+        ir::FieldsBinder::Named(
+            fields
+                .iter()
+                .map(|(f, b)| (Ident::from(*f), b.clone()))
+                .collect(),
+            ellipsis,
+        ),
+    )
+}
+
+pub fn bind_seq(constr: &str, fields: &[ir::Binder], ellipsis: ir::BindEllipsis) -> ir::Binder {
+    ir::Binder::Deconstruct(
+        path(constr),
+        ir::FieldsBinder::Sequential(fields.to_vec(), ellipsis),
+    )
+}
+
+pub fn bind_tuple(elements: &[ir::Binder]) -> ir::Binder {
+    ir::Binder::Tuple(elements.to_vec())
+}
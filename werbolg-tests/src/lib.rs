@@ -2,16 +2,20 @@
 extern crate alloc;
 extern crate proc_macro;
 
+mod build;
 mod tests;
 mod value;
 
-use alloc::vec;
+use alloc::{vec, vec::Vec};
 use value::Value;
-use werbolg_compile::{CallArity, CompilationError, Environment, compile as comp};
+use werbolg_compile::{
+    CallArity, CompilationError, CompilationUnit, CompilationWarning, Environment, WarningLevels,
+    compile_with_warnings,
+};
 use werbolg_core::Literal;
-use werbolg_core::{AbsPath, Ident, Namespace, Span};
+use werbolg_core::{AbsPath, FunId, Ident, Namespace, Span};
 use werbolg_exec::{
-    ExecutionEnviron, ExecutionError, ExecutionMachine, ExecutionParams, NIFCall, WAllocator,
+    ExecutionEnviron, ExecutionError, ExecutionMachine, ExecutionParams, NIF, NIFCall, WAllocator,
     WerRefCount,
 };
 
@@ -35,6 +39,10 @@ fn nif_expect_bool_eq(_: &DummyAlloc, args: &[Value]) -> Result<Value, Execution
     Ok(Value::Bool(ret))
 }
 
+fn nif_list(_: &DummyAlloc, args: &[Value]) -> Result<Value, ExecutionError> {
+    Ok(Value::List(args.into()))
+}
+
 fn nif_int_eq(_: &DummyAlloc, args: &[Value]) -> Result<Value, ExecutionError> {
     let n1 = args[0].int()?;
     let n2 = args[1].int()?;
@@ -50,7 +58,7 @@ fn nif_expect_int_eq(_: &DummyAlloc, args: &[Value]) -> Result<Value, ExecutionE
     Ok(Value::Bool(ret))
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum MyLiteral {
     Bool(bool),
     Int(u64),
@@ -82,38 +90,111 @@ fn literal_mapper(span: Span, lit: Literal) -> Result<MyLiteral, CompilationErro
 }
 
 pub fn execute(mod1: werbolg_core::Module) -> Result<Value, ExecutionError> {
+    execute_modules(vec![("main", mod1)])
+}
+
+/// Execute `main::main` with the modules each defined in a top level namespace
+pub fn execute_modules(mods: Vec<(&str, werbolg_core::Module)>) -> Result<Value, ExecutionError> {
+    let (exec_module, environ) = compile_modules(mods);
+    execute_compiled(exec_module, environ)
+}
+
+/// The environment of the tests, with the NIFs available to all the modules
+pub type TestEnvironment = Environment<NIF<DummyAlloc, MyLiteral, (), Value>, Value>;
+
+/// Compile the modules each defined in a top level namespace, with the tests NIFs
+pub fn compile_modules(
+    mods: Vec<(&str, werbolg_core::Module)>,
+) -> (CompilationUnit<MyLiteral>, TestEnvironment) {
+    try_compile_modules(mods).expect("no compilation error")
+}
+
+/// Compile the modules like `compile_modules`, returning the compilation errors
+pub fn try_compile_modules(
+    mods: Vec<(&str, werbolg_core::Module)>,
+) -> Result<(CompilationUnit<MyLiteral>, TestEnvironment), Vec<CompilationError>> {
+    compile_modules_warnings(mods, WarningLevels::default(), &mut Vec::new())
+}
+
+/// Compile the modules like `try_compile_modules`, with the warnings levels and
+/// adding the warnings reported to `warnings`
+pub fn compile_modules_warnings(
+    mods: Vec<(&str, werbolg_core::Module)>,
+    warning_levels: WarningLevels,
+    warnings: &mut Vec<CompilationWarning>,
+) -> Result<(CompilationUnit<MyLiteral>, TestEnvironment), Vec<CompilationError>> {
     macro_rules! add_pure_nif {
         ($env:ident, $i:literal, $arity:literal, $e:expr) => {
-            let nif = NIFCall::Pure($e).info($i, CallArity::try_from($arity as usize).unwrap());
+            let arity = CallArity::try_from($arity as usize).unwrap();
+            let nif = NIFCall::Pure($e).info($i, arity);
             let path = AbsPath::new(&Namespace::root(), &Ident::from($i));
-            $env.add_nif(&path, nif).unwrap();
+            $env.add_nif_with_arity(&path, nif, arity).unwrap();
         };
     }
-    let module_ns = Namespace::root().append(Ident::from("main"));
-    let modules = vec![(module_ns.clone(), mod1)];
+    let modules = mods
+        .into_iter()
+        .map(|(name, m)| (Namespace::root().append(Ident::from(name)), m))
+        .collect();
     let mut environ = Environment::new();
     add_pure_nif!(environ, "expect_bool", 2, nif_expect_bool_eq);
     add_pure_nif!(environ, "bool_eq", 2, nif_bool_eq);
     add_pure_nif!(environ, "expect_int", 2, nif_expect_int_eq);
     add_pure_nif!(environ, "int_eq", 2, nif_int_eq);
+    // the sequence constructor takes any number of elements
+    let list = NIFCall::Pure(nif_list).info_variadic("list");
+    let list_path = AbsPath::new(&Namespace::root(), &Ident::from("list"));
+    let list_nif = environ.add_nif(&list_path, list).unwrap();
     let compilation_params = werbolg_compile::CompilationParams {
         literal_mapper,
-        sequence_constructor: None,
+        sequence_constructor: Some(list_nif),
+        warning_levels,
     };
-    let exec_module =
-        comp(&compilation_params, modules, &mut environ).expect("no compilation error");
+    let exec_module = compile_with_warnings(&compilation_params, modules, &mut environ, warnings)?;
+    Ok((exec_module, environ))
+}
+
+/// Compile the modules, expecting a single compilation error, returned without its context
+pub fn compile_error(mods: Vec<(&str, werbolg_core::Module)>) -> CompilationError {
+    let Err(mut errors) = try_compile_modules(mods) else {
+        panic!("compilation should fail")
+    };
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    match errors.remove(0) {
+        CompilationError::Context(_, e) => *e,
+        e => e,
+    }
+}
+
+/// Execute `main::main` of modules already compiled
+pub fn execute_compiled(
+    exec_module: CompilationUnit<MyLiteral>,
+    environ: TestEnvironment,
+) -> Result<Value, ExecutionError> {
+    let (mut em, entry_point) = machine(exec_module, environ);
+    werbolg_exec::exec(&mut em, entry_point, &[])
+}
+
+pub type TestMachine = ExecutionMachine<DummyAlloc, MyLiteral, (), Value>;
+
+/// Create the execution machine of modules already compiled, returning it with `main::main`
+pub fn machine(
+    exec_module: CompilationUnit<MyLiteral>,
+    environ: TestEnvironment,
+) -> (TestMachine, FunId) {
+    let module_ns = Namespace::root().append(Ident::from("main"));
     let ee = ExecutionEnviron::from_compile_environment(environ.finalize());
     let entry_point = exec_module
         .funs_tbl
         .get(&AbsPath::new(&module_ns, &Ident::from("main")))
         .expect("existing function as entry point");
+    werbolg_exec::verify(&exec_module, &ee).expect("compiled code is valid");
     let execution_params = ExecutionParams { literal_to_value };
-    let mut em = ExecutionMachine::new(
+    let em = ExecutionMachine::new(
         WerRefCount::new(exec_module),
         WerRefCount::new(ee),
         execution_params,
         DummyAlloc,
         (),
     );
-    werbolg_exec::exec(&mut em, entry_point, &[])
+    (em, entry_point)
 }
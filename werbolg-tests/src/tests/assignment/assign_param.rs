@@ -0,0 +1,18 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// only the locals can be assigned
+//
+// fn set(x) {
+//     x = 2
+// }
+// fn main() {
+//     set(1)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun("set", &["x"], assign("x", int(2))),
+        fun("main", &[], call(var("set"), vec![int(1)])),
+    ])
+}
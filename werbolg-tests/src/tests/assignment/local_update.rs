@@ -0,0 +1,30 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn main() {
+//     let done = 0;
+//     let a = loop {
+//         if int_eq(done, 1) { break 5 } else { done = 1 }
+//     };
+//     expect_int(a, 5)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_(
+            "done",
+            int(0),
+            let_(
+                "a",
+                loop_(if_(
+                    call(var("int_eq"), vec![var("done"), int(1)]),
+                    break_(Some(int(5))),
+                    assign("done", int(1)),
+                )),
+                call(var("expect_int"), vec![var("a"), int(5)]),
+            ),
+        ),
+    )])
+}
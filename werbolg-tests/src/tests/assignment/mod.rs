@@ -1,4 +1,8 @@
+mod assign_param;
 mod associativity;
+mod local_update;
+mod ref_get_not_ref;
+mod ref_shared;
 
 #[test]
 fn associativity() {
@@ -6,3 +10,36 @@ fn associativity() {
     let r = crate::execute(mod1);
     assert!(r.is_ok(), "{:?}", r.err())
 }
+
+#[test]
+fn local_update() {
+    let mod1 = local_update::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn ref_shared() {
+    let mod1 = ref_shared::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn assign_param() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", assign_param::module())]);
+    assert!(matches!(e, CompilationError::AssignNotLocal(..)), "{:?}", e);
+}
+
+#[test]
+fn ref_get_not_ref() {
+    let mod1 = ref_get_not_ref::module();
+    let r = crate::execute(mod1);
+    assert!(
+        matches!(r, Err(werbolg_exec::ExecutionError::ValueNotRef { .. })),
+        "{:?}",
+        r
+    )
+}
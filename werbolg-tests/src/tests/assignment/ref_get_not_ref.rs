@@ -0,0 +1,10 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn main() {
+//     *1
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun("main", &[], ref_get(int(1)))])
+}
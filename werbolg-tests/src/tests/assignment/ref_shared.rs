@@ -0,0 +1,31 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// the reference cell is shared with the closure capturing it
+//
+// fn main() {
+//     let r = ref 1;
+//     let f = || *r = 2;
+//     f();
+//     expect_int(*r, 2)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_(
+            "r",
+            ref_new(int(1)),
+            let_(
+                "f",
+                lambda(&[], ref_set(var("r"), int(2))),
+                let_binder(
+                    ignore(),
+                    call(var("f"), vec![]),
+                    call(var("expect_int"), vec![ref_get(var("r")), int(2)]),
+                ),
+            ),
+        ),
+    )])
+}
@@ -0,0 +1,38 @@
+// fn first(t) { let (a, _) = t; a }
+// fn tail(t) { first(t) }
+// fn main() { let v = tail(1); v }
+#[allow(dead_code)]
+const TEXT: &str = r#"(fun @0..35 pub first (t@9..10) (let (tuple a (ignore)) t@27..28 a@32..33))
+(fun @36..60 pub tail (t@44..45) (call @49..57 first@49..54 t@55..56))
+(fun @61..97 pub main () (let v (call @81..88 tail@81..85 (num @86..87 1)) v@91..92))
+"#;
+
+#[test]
+fn frames() {
+    use alloc::vec::Vec;
+    use werbolg_core::Ident;
+
+    let mod1 = werbolg_core::text::parse_module(TEXT).expect("valid IR");
+    let (unit, environ) = crate::compile_modules(alloc::vec![("main", mod1)]);
+    let (mut em, entry_point) = crate::machine(unit, environ);
+    let r = werbolg_exec::exec(&mut em, entry_point, &[]);
+    assert!(r.is_err());
+
+    let frames = em.backtrace();
+    let names = frames
+        .iter()
+        .map(|frame| frame.name.clone())
+        .collect::<Vec<_>>();
+    // `tail` is replaced by `first` through the tail call
+    assert_eq!(
+        names,
+        [Some(Ident::from("first")), Some(Ident::from("main"))]
+    );
+    assert_eq!(frames[0].tail_calls, 1);
+    assert_eq!(frames[0].ip, em.ip);
+    assert_eq!(frames[1].tail_calls, 0);
+    assert!(matches!(
+        em.module.code[frames[1].ip],
+        werbolg_compile::Instruction::CallFun(_, _, werbolg_compile::TailCall::No)
+    ));
+}
@@ -0,0 +1,83 @@
+use crate::MyLiteral;
+use alloc::vec::Vec;
+use werbolg_compile::{LiteralCodec, write_bytecode};
+
+fn encode(lit: &MyLiteral, out: &mut Vec<u8>) {
+    match lit {
+        MyLiteral::Bool(b) => out.extend_from_slice(&[0, *b as u8]),
+        MyLiteral::Int(n) => {
+            out.push(1);
+            out.extend_from_slice(&n.to_le_bytes())
+        }
+    }
+}
+
+fn decode(bytes: &[u8]) -> Option<MyLiteral> {
+    match bytes {
+        [0, b] => Some(MyLiteral::Bool(*b != 0)),
+        [1, n @ ..] => Some(MyLiteral::Int(u64::from_le_bytes(n.try_into().ok()?))),
+        _ => None,
+    }
+}
+
+const CODEC: LiteralCodec<MyLiteral> = LiteralCodec { encode, decode };
+
+#[allow(dead_code)]
+fn golden_bytecode() -> Vec<u8> {
+    let mod1 = werbolg_core::text::parse_module(super::text::golden::TEXT).expect("valid IR");
+    let (unit, _) = crate::compile_modules(alloc::vec![("main", mod1)]);
+    write_bytecode(&unit, &CODEC)
+}
+
+#[test]
+fn round_trip() {
+    let mod1 = werbolg_core::text::parse_module(super::text::golden::TEXT).expect("valid IR");
+    let (unit, environ) = crate::compile_modules(alloc::vec![("main", mod1)]);
+    let bytecode = write_bytecode(&unit, &CODEC);
+    let unit2 = werbolg_compile::read_bytecode(&bytecode, &CODEC).expect("valid bytecode");
+    assert_eq!(write_bytecode(&unit2, &CODEC), bytecode);
+    let r = crate::execute_compiled(unit2, environ);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn truncated() {
+    let bytecode = golden_bytecode();
+    for len in [0, 3, 10, bytecode.len() - 1] {
+        let r = werbolg_compile::read_bytecode(&bytecode[..len], &CODEC);
+        assert!(
+            matches!(r, Err(werbolg_compile::BytecodeError::Truncated)),
+            "{}",
+            len
+        )
+    }
+}
+
+#[test]
+fn invalid_header() {
+    let mut bytecode = golden_bytecode();
+    bytecode[4] = 0xff;
+    let r = werbolg_compile::read_bytecode(&bytecode, &CODEC);
+    assert!(matches!(
+        r,
+        Err(werbolg_compile::BytecodeError::UnsupportedVersion(0xff))
+    ));
+    bytecode[0] = b'X';
+    let r = werbolg_compile::read_bytecode(&bytecode, &CODEC);
+    assert!(matches!(
+        r,
+        Err(werbolg_compile::BytecodeError::InvalidMagic)
+    ))
+}
+
+#[test]
+fn corrupted() {
+    let mut bytecode = golden_bytecode();
+    let last = bytecode.len() - 1;
+    bytecode[last] ^= 0x1;
+    let r = werbolg_compile::read_bytecode(&bytecode, &CODEC);
+    assert!(matches!(
+        r,
+        Err(werbolg_compile::BytecodeError::ChecksumMismatch { .. })
+    ))
+}
@@ -0,0 +1,24 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn main() {
+//     let a = 3;
+//     let f = |x| expect_int(a, x);
+//     f(3)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_(
+            "a",
+            int(3),
+            let_(
+                "f",
+                lambda(&["x"], call(var("expect_int"), vec![var("a"), var("x")])),
+                call(var("f"), vec![int(3)]),
+            ),
+        ),
+    )])
+}
@@ -0,0 +1,29 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// the innermost lambda capture `a` and `b` through the intermediate lambda
+//
+// fn main() {
+//     let a = 1;
+//     let g = |b| || expect_int(a, b);
+//     g(1)()
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_(
+            "a",
+            int(1),
+            let_(
+                "g",
+                lambda(
+                    &["b"],
+                    lambda(&[], call(var("expect_int"), vec![var("a"), var("b")])),
+                ),
+                call(call(var("g"), vec![int(1)]), vec![]),
+            ),
+        ),
+    )])
+}
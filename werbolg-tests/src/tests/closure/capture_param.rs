@@ -0,0 +1,19 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn constant(n) { || n }
+// fn main() { expect_int(constant(5)(), 5) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun("constant", &["n"], lambda(&[], var("n"))),
+        fun(
+            "main",
+            &[],
+            call(
+                var("expect_int"),
+                vec![call(call(var("constant"), vec![int(5)]), vec![]), int(5)],
+            ),
+        ),
+    ])
+}
@@ -0,0 +1,24 @@
+mod capture_local;
+mod capture_nested;
+mod capture_param;
+
+#[test]
+fn capture_local() {
+    let mod1 = capture_local::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn capture_param() {
+    let mod1 = capture_param::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn capture_nested() {
+    let mod1 = capture_nested::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
@@ -0,0 +1,42 @@
+mod missing_field;
+mod named;
+mod sequential_ellipsis;
+mod unknown_field;
+
+#[test]
+fn named() {
+    let mod1 = named::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn sequential_ellipsis() {
+    let mod1 = sequential_ellipsis::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn missing_field() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", missing_field::module())]);
+    assert!(
+        matches!(e, CompilationError::StructureFieldsMissing(..)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn unknown_field() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", unknown_field::module())]);
+    assert!(
+        matches!(e, CompilationError::StructureFieldNotExistant(..)),
+        "{:?}",
+        e
+    );
+}
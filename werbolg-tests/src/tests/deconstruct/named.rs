@@ -0,0 +1,31 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// struct Point { x, y }
+// fn same(p) { let Point { y, x } = p; expect_int(x, y) }
+// fn main() { same(Point(2, 2)) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        struct_("Point", &["x", "y"]),
+        fun(
+            "same",
+            &["p"],
+            let_binder(
+                bind_named(
+                    "Point",
+                    &[("y", bind("y")), ("x", bind("x"))],
+                    BindEllipsis::No,
+                ),
+                var("p"),
+                call(var("expect_int"), vec![var("x"), var("y")]),
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            call(var("same"), vec![call(var("Point"), vec![int(2), int(2)])]),
+        ),
+    ])
+}
@@ -0,0 +1,33 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// struct Point { x, y }
+// fn first(p) { let Point(x, ..) = p; x }
+// fn main() { expect_int(first(Point(1, 2)), 1) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        struct_("Point", &["x", "y"]),
+        fun(
+            "first",
+            &["p"],
+            let_binder(
+                bind_seq("Point", &[bind("x")], BindEllipsis::Yes),
+                var("p"),
+                var("x"),
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            call(
+                var("expect_int"),
+                vec![
+                    call(var("first"), vec![call(var("Point"), vec![int(1), int(2)])]),
+                    int(1),
+                ],
+            ),
+        ),
+    ])
+}
@@ -0,0 +1,23 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// struct Point { x, y }
+// fn third(p) { let Point { z, .. } = p; z }
+// fn main() { expect_int(1, 1) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        struct_("Point", &["x", "y"]),
+        fun(
+            "third",
+            &["p"],
+            let_binder(
+                bind_named("Point", &[("z", bind("z"))], BindEllipsis::Yes),
+                var("p"),
+                var("z"),
+            ),
+        ),
+        fun("main", &[], call(var("expect_int"), vec![int(1), int(1)])),
+    ])
+}
@@ -0,0 +1,76 @@
+mod tail_recursion;
+mod with_closures;
+
+use werbolg_compile::{CompilationUnit, Instruction};
+
+/// Return the instructions of the function `main::name`
+#[allow(dead_code)]
+fn instructions<L>(unit: &CompilationUnit<L>, name: &str) -> alloc::vec::Vec<Instruction> {
+    use werbolg_core::{AbsPath, Ident, Namespace};
+
+    let path = AbsPath::new(
+        &Namespace::root().append(Ident::from("main")),
+        &Ident::from(name),
+    );
+    let funid = unit.funs_tbl.get(&path).expect("existing function");
+    let start = unit.funs[funid].code_pos;
+    unit.code
+        .iter()
+        .skip_while(|(ia, _)| *ia < start)
+        .take_while(|(ia, _)| *ia == start || unit.funs.iter().all(|(_, f)| f.code_pos != *ia))
+        .map(|(_, instruction)| instruction.clone())
+        .collect()
+}
+
+#[test]
+fn emitted() {
+    use werbolg_compile::{CallArity, TailCall};
+
+    let (unit, _) = crate::compile_modules(alloc::vec![("main", with_closures::module())]);
+    let second = unit
+        .funs
+        .iter()
+        .find(|(_, f)| f.name.as_ref().map(|n| n.0.as_str()) == Some("second"))
+        .map(|(funid, _)| funid)
+        .expect("existing function");
+
+    // the statically known functions are called without fetching them first
+    let check = instructions(&unit, "check");
+    assert!(check.iter().any(|i| matches!(
+        i,
+        Instruction::CallFun(funid, CallArity(2), TailCall::No) if *funid == second
+    )));
+    assert!(!check.iter().any(|i| matches!(i, Instruction::FetchFun(_))));
+
+    // the NIFs and the function values are still called dynamically
+    assert!(matches!(
+        check.as_slice(),
+        [.., Instruction::Call(TailCall::Yes, CallArity(2))]
+    ));
+    let apply = instructions(&unit, "apply");
+    assert!(matches!(
+        apply.as_slice(),
+        [.., Instruction::Call(TailCall::Yes, CallArity(1))]
+    ));
+
+    let (unit, _) = crate::compile_modules(alloc::vec![("main", tail_recursion::module())]);
+    let swap = instructions(&unit, "swap");
+    assert!(
+        swap.iter()
+            .any(|i| matches!(i, Instruction::CallFun(_, CallArity(3), TailCall::Yes)))
+    );
+}
+
+#[test]
+fn tail_recursion() {
+    let mod1 = tail_recursion::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn with_closures() {
+    let mod1 = with_closures::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
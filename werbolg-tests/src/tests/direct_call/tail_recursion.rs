@@ -0,0 +1,36 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// enum Nat { Z, S(n) }
+// fn swap(a, b, n) { match n { Nat::S(m) => swap(b, a, m), Nat::Z => a } }
+// fn main() { expect_int(swap(1, 2, Nat::S(Nat::S(Nat::S(Nat::Z)))), 2) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    let three = (0..3).fold(var("Nat::Z"), |n, _| call(var("Nat::S"), vec![n]));
+    build::module(vec![
+        enum_("Nat", &[("Z", &[]), ("S", &["n"])]),
+        fun(
+            "swap",
+            &["a", "b", "n"],
+            match_(
+                var("n"),
+                vec![
+                    (
+                        pat(bind_seq("Nat::S", &[bind("m")], BindEllipsis::No)),
+                        call(var("swap"), vec![var("b"), var("a"), var("m")]),
+                    ),
+                    (pat(bind_seq("Nat::Z", &[], BindEllipsis::No)), var("a")),
+                ],
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            call(
+                var("expect_int"),
+                vec![call(var("swap"), vec![int(1), int(2), three]), int(2)],
+            ),
+        ),
+    ])
+}
@@ -0,0 +1,68 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn second(a, b) { b }
+// fn apply(f, x) { f(x) }
+// fn capture(x) { (|y| second(y, x))(7) }
+// fn check(a, b) { let c = second(b, a); let _ = expect_int(c, a); expect_int(b, 2) }
+// fn main() {
+//     let _ = expect_int(apply(|v| second(v, 3), 1), 3);
+//     let _ = expect_int(capture(4), 4);
+//     check(1, 2)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun("second", &["a", "b"], var("b")),
+        fun("apply", &["f", "x"], call(var("f"), vec![var("x")])),
+        fun(
+            "capture",
+            &["x"],
+            call(
+                lambda(&["y"], call(var("second"), vec![var("y"), var("x")])),
+                vec![int(7)],
+            ),
+        ),
+        fun(
+            "check",
+            &["a", "b"],
+            let_(
+                "c",
+                call(var("second"), vec![var("b"), var("a")]),
+                let_binder(
+                    ignore(),
+                    call(var("expect_int"), vec![var("c"), var("a")]),
+                    call(var("expect_int"), vec![var("b"), int(2)]),
+                ),
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                ignore(),
+                call(
+                    var("expect_int"),
+                    vec![
+                        call(
+                            var("apply"),
+                            vec![
+                                lambda(&["v"], call(var("second"), vec![var("v"), int(3)])),
+                                int(1),
+                            ],
+                        ),
+                        int(3),
+                    ],
+                ),
+                let_binder(
+                    ignore(),
+                    call(
+                        var("expect_int"),
+                        vec![call(var("capture"), vec![int(4)]), int(4)],
+                    ),
+                    call(var("check"), vec![int(1), int(2)]),
+                ),
+            ),
+        ),
+    ])
+}
@@ -0,0 +1,42 @@
+mod enum_as_value;
+mod variant_arity;
+mod variant_fields;
+mod variant_unit;
+
+#[test]
+fn variant_fields() {
+    let mod1 = variant_fields::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn variant_unit() {
+    let mod1 = variant_unit::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn variant_arity() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", variant_arity::module())]);
+    assert!(
+        matches!(e, CompilationError::StructureFieldsMismatch(_, _, 2, 1)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn enum_as_value() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", enum_as_value::module())]);
+    assert!(
+        matches!(e, CompilationError::ConstructorNotStructure(..)),
+        "{:?}",
+        e
+    );
+}
@@ -0,0 +1,12 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// enum Shape { Empty, Rect(w, h) }
+// fn main() { Shape::Rect(1) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        enum_("Shape", &[("Empty", &[]), ("Rect", &["w", "h"])]),
+        fun("main", &[], call(var("Shape::Rect"), vec![int(1)])),
+    ])
+}
@@ -0,0 +1,39 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// enum Shape { Empty, Circle(r), Rect(w, h) }
+// fn height(s) { let Shape::Rect { h, .. } = s; h }
+// fn main() { expect_int(height(Shape::Rect(2, 3)), 3) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        enum_(
+            "Shape",
+            &[("Empty", &[]), ("Circle", &["r"]), ("Rect", &["w", "h"])],
+        ),
+        fun(
+            "height",
+            &["s"],
+            let_binder(
+                bind_named("Shape::Rect", &[("h", bind("h"))], BindEllipsis::Yes),
+                var("s"),
+                var("h"),
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            call(
+                var("expect_int"),
+                vec![
+                    call(
+                        var("height"),
+                        vec![call(var("Shape::Rect"), vec![int(2), int(3)])],
+                    ),
+                    int(3),
+                ],
+            ),
+        ),
+    ])
+}
@@ -0,0 +1,21 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// enum Shape { Empty, Circle(r) }
+// fn main() { let Shape::Empty {} = Shape::Empty; expect_int(1, 1) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        enum_("Shape", &[("Empty", &[]), ("Circle", &["r"])]),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                bind_seq("Shape::Empty", &[], BindEllipsis::No),
+                var("Shape::Empty"),
+                call(var("expect_int"), vec![int(1), int(1)]),
+            ),
+        ),
+    ])
+}
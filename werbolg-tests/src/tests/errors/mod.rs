@@ -0,0 +1,240 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn a() { missing1 }
+// fn b() { missing2 }
+// value a = 1;
+// fn main() { expect_int(1, 1) }
+#[allow(dead_code)]
+fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun("a", &[], var("missing1")),
+        fun("b", &[], var("missing2")),
+        value("a", int(1)),
+        fun("main", &[], call(var("expect_int"), vec![int(1), int(1)])),
+    ])
+}
+
+// value x = missing3;
+// value y = missing4;
+#[allow(dead_code)]
+fn module_values() -> werbolg_core::Module {
+    build::module(vec![
+        value("x", var("missing3")),
+        value("y", var("missing4")),
+    ])
+}
+
+#[test]
+fn all_errors() {
+    use alloc::vec::Vec;
+    use werbolg_compile::CompilationError;
+    use werbolg_core::Ident;
+
+    let Err(errors) = crate::try_compile_modules(vec![("main", module())]) else {
+        panic!("compilation should fail")
+    };
+    let errors = errors
+        .iter()
+        .map(|e| e.split_context().1)
+        .collect::<Vec<_>>();
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(
+        matches!(errors[0], CompilationError::DuplicateSymbol(_, ident) if *ident == Ident::from("a"))
+    );
+    assert!(matches!(errors[1], CompilationError::MissingSymbol(..)));
+    assert!(matches!(errors[2], CompilationError::MissingSymbol(..)));
+}
+
+#[test]
+fn all_modules_errors() {
+    let Err(errors) =
+        crate::try_compile_modules(vec![("main", module()), ("values", module_values())])
+    else {
+        panic!("compilation should fail")
+    };
+    // the errors of the functions of the first module, and of each value of the second module
+    assert_eq!(errors.len(), 5, "{:?}", errors);
+}
+
+// fn length(l) { l }
+// fn main() { lenght(1) }
+// fn local() { let value = 1; valeu }
+// fn import() { helper() }
+#[allow(dead_code)]
+fn module_typos() -> werbolg_core::Module {
+    build::module(vec![
+        fun("length", &["l"], var("l")),
+        fun("main", &[], call(var("lenght"), vec![int(1)])),
+        fun("local", &[], let_("value", int(1), var("valeu"))),
+        fun("import", &[], call(var("helper"), vec![])),
+    ])
+}
+
+// fn helper() { 1 }
+#[allow(dead_code)]
+fn module_helper() -> werbolg_core::Module {
+    build::module(vec![fun("helper", &[], int(1))])
+}
+
+#[test]
+fn suggestions() {
+    use alloc::vec::Vec;
+    use werbolg_compile::CompilationError;
+    use werbolg_core::{AbsPath, Ident, Namespace, Path};
+
+    let Err(errors) =
+        crate::try_compile_modules(vec![("main", module_typos()), ("other", module_helper())])
+    else {
+        panic!("compilation should fail")
+    };
+    let suggestions = errors
+        .iter()
+        .map(|e| match e.split_context().1 {
+            CompilationError::MissingSymbol(_, path, suggestions) => (path, suggestions),
+            e => panic!("unexpected error {:?}", e),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(suggestions.len(), 3, "{:?}", suggestions);
+
+    let (path, lenght) = &suggestions[0];
+    assert_eq!(**path, Path::relative(Ident::from("lenght")));
+    assert_eq!(lenght.similar, [Path::relative(Ident::from("length"))]);
+    assert!(lenght.not_imported.is_empty());
+
+    let (_, valeu) = &suggestions[1];
+    assert_eq!(valeu.similar, [Path::relative(Ident::from("value"))]);
+
+    let (_, helper) = &suggestions[2];
+    assert!(helper.similar.is_empty());
+    let other = Namespace::root().append(Ident::from("other"));
+    assert_eq!(
+        helper.not_imported,
+        [AbsPath::new(&other, &Ident::from("helper"))]
+    );
+}
+
+// fn pair(a, b) { a }
+// fn main() { let f = pair; expect_int(pair(1), f(1, 2), 3) }
+#[allow(dead_code)]
+fn module_arity() -> werbolg_core::Module {
+    build::module(vec![
+        fun("pair", &["a", "b"], var("a")),
+        fun("missing_arg", &[], call(var("pair"), vec![int(1)])),
+        fun(
+            "extra_arg",
+            &[],
+            call(var("expect_int"), vec![int(1), int(2), int(3)]),
+        ),
+        // the arity of the functions called through a value is only known at execution
+        fun(
+            "main",
+            &[],
+            let_("f", var("pair"), call(var("f"), vec![int(1)])),
+        ),
+    ])
+}
+
+#[test]
+fn arity() {
+    use alloc::vec::Vec;
+    use werbolg_compile::CompilationError;
+
+    let Err(errors) = crate::try_compile_modules(vec![("main", module_arity())]) else {
+        panic!("compilation should fail")
+    };
+    let mismatches = errors
+        .iter()
+        .map(|e| match e.split_context().1 {
+            CompilationError::CallArityMismatch(_, _, expected, got) => (*expected, *got),
+            e => panic!("unexpected error {:?}", e),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(mismatches, [(2, 1), (2, 3)]);
+}
+
+// fn main() { let l = list(1, 2); list(l, 3, 4) }
+#[allow(dead_code)]
+fn module_arity_variadic() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_(
+            "l",
+            call(var("list"), vec![int(1), int(2)]),
+            call(var("list"), vec![var("l"), int(3), int(4)]),
+        ),
+    )])
+}
+
+#[test]
+fn arity_variadic() {
+    use crate::Value;
+
+    // the arity of a variadic NIF is neither checked at compilation nor at execution
+    let val = crate::execute(module_arity_variadic()).expect("no execution error");
+    let Value::List(elements) = &val else {
+        panic!("unexpected value {:?}", val)
+    };
+    assert_eq!(elements.len(), 3);
+    assert!(matches!(&elements[0], Value::List(l) if l.len() == 2));
+}
+
+// fn main() { [1] }
+#[allow(dead_code)]
+fn module_sequence() -> werbolg_core::Module {
+    build::module(vec![fun("main", &[], sequence(vec![int(1)]))])
+}
+
+#[test]
+fn arity_call_nif() {
+    use werbolg_compile::{CallArity, Instruction};
+    use werbolg_core::id::IdF;
+    use werbolg_core::{AbsPath, Ident, Namespace, NifId};
+    use werbolg_exec::{
+        ExecutionEnviron, ExecutionError, ExecutionMachine, ExecutionParams, WerRefCount,
+    };
+
+    // replace the sequence constructor by a NIF taking 2 arguments, which is still
+    // called with 1 argument, and execute the code without verifying it
+    let (mut unit, environ) = crate::compile_modules(vec![("main", module_sequence())]);
+    let pair_nif = (0..)
+        .map(NifId::from_collection_len)
+        .find(|nif| environ.nif_arity(*nif) == Some(CallArity(2)))
+        .unwrap();
+    for instruction in unit.code.iter_mut() {
+        if let Instruction::CallNif(nif, _) = instruction {
+            *nif = pair_nif
+        }
+    }
+
+    let main_path = AbsPath::new(
+        &Namespace::root().append(Ident::from("main")),
+        &Ident::from("main"),
+    );
+    let entry_point = unit.funs_tbl.get(&main_path).unwrap();
+    let ee = ExecutionEnviron::from_compile_environment(environ.finalize());
+    let execution_params = ExecutionParams {
+        literal_to_value: crate::literal_to_value,
+    };
+    let mut em = ExecutionMachine::new(
+        WerRefCount::new(unit),
+        WerRefCount::new(ee),
+        execution_params,
+        crate::DummyAlloc,
+        (),
+    );
+    let r = werbolg_exec::exec(&mut em, entry_point, &[]);
+    assert!(
+        matches!(
+            r,
+            Err(ExecutionError::ArityError {
+                expected: CallArity(2),
+                got: CallArity(1),
+                ..
+            })
+        ),
+        "{:?}",
+        r
+    );
+}
@@ -0,0 +1,21 @@
+use crate::build::{self, *};
+use alloc::{vec, vec::Vec};
+
+// mod a { fn id(x) { x } }
+// mod b { fn id(x) { x } }
+// mod main { use a; use b; fn main() { id(2) } }
+#[allow(dead_code)]
+pub fn modules() -> Vec<(&'static str, werbolg_core::Module)> {
+    vec![
+        ("a", build::module(vec![fun("id", &["x"], var("x"))])),
+        ("b", build::module(vec![fun("id", &["x"], var("x"))])),
+        (
+            "main",
+            build::module(vec![
+                use_("a", &[], &[]),
+                use_("b", &[], &[]),
+                fun("main", &[], call(var("id"), vec![int(2)])),
+            ]),
+        ),
+    ]
+}
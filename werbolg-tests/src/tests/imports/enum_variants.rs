@@ -0,0 +1,36 @@
+use crate::build::{self, *};
+use alloc::{vec, vec::Vec};
+use werbolg_core::ir::BindEllipsis;
+
+// mod shapes { enum Shape { Empty, Rect(w, h) } }
+// mod main {
+//     use shapes::Shape;
+//     fn main() { let Rect(w, _) = Rect(1, 2); expect_int(w, 1) }
+// }
+#[allow(dead_code)]
+pub fn modules() -> Vec<(&'static str, werbolg_core::Module)> {
+    vec![
+        (
+            "shapes",
+            build::module(vec![enum_(
+                "Shape",
+                &[("Empty", &[]), ("Rect", &["w", "h"])],
+            )]),
+        ),
+        (
+            "main",
+            build::module(vec![
+                use_("shapes::Shape", &[], &[]),
+                fun(
+                    "main",
+                    &[],
+                    let_binder(
+                        bind_seq("Rect", &[bind("w"), ignore()], BindEllipsis::No),
+                        call(var("Rect"), vec![int(1), int(2)]),
+                        call(var("expect_int"), vec![var("w"), int(1)]),
+                    ),
+                ),
+            ]),
+        ),
+    ]
+}
@@ -0,0 +1,18 @@
+use crate::build::{self, *};
+use alloc::{vec, vec::Vec};
+
+// mod geometry { fn id(x) { x } }
+// mod main { use geometry hiding (id); fn main() { id(2) } }
+#[allow(dead_code)]
+pub fn modules() -> Vec<(&'static str, werbolg_core::Module)> {
+    vec![
+        ("geometry", build::module(vec![fun("id", &["x"], var("x"))])),
+        (
+            "main",
+            build::module(vec![
+                use_("geometry", &["id"], &[]),
+                fun("main", &[], call(var("id"), vec![int(2)])),
+            ]),
+        ),
+    ]
+}
@@ -0,0 +1,14 @@
+use crate::build::{self, *};
+use alloc::{vec, vec::Vec};
+
+// mod main { use geometry; fn main() { expect_int(1, 1) } }
+#[allow(dead_code)]
+pub fn modules() -> Vec<(&'static str, werbolg_core::Module)> {
+    vec![(
+        "main",
+        build::module(vec![
+            use_("geometry", &[], &[]),
+            fun("main", &[], call(var("expect_int"), vec![int(1), int(1)])),
+        ]),
+    )]
+}
@@ -0,0 +1,52 @@
+mod ambiguous;
+mod enum_variants;
+mod hiding;
+mod missing_namespace;
+mod namespace;
+mod rename;
+
+#[test]
+fn namespace() {
+    let r = crate::execute_modules(namespace::modules());
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn rename() {
+    let r = crate::execute_modules(rename::modules());
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn enum_variants() {
+    let r = crate::execute_modules(enum_variants::modules());
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn hiding() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(hiding::modules());
+    assert!(matches!(e, CompilationError::MissingSymbol(..)), "{:?}", e);
+}
+
+#[test]
+fn ambiguous() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(ambiguous::modules());
+    assert!(matches!(e, CompilationError::MultipleSymbol(..)), "{:?}", e);
+}
+
+#[test]
+fn missing_namespace() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(missing_namespace::modules());
+    assert!(
+        matches!(e, CompilationError::MissingNamespace(..)),
+        "{:?}",
+        e
+    );
+}
@@ -0,0 +1,25 @@
+use crate::build::{self, *};
+use alloc::{vec, vec::Vec};
+
+// mod geometry { fn id(x) { x } }
+// mod main { use geometry; fn main() { expect_int(id(2), 2) } }
+#[allow(dead_code)]
+pub fn modules() -> Vec<(&'static str, werbolg_core::Module)> {
+    vec![
+        ("geometry", build::module(vec![fun("id", &["x"], var("x"))])),
+        (
+            "main",
+            build::module(vec![
+                use_("geometry", &[], &[]),
+                fun(
+                    "main",
+                    &[],
+                    call(
+                        var("expect_int"),
+                        vec![call(var("id"), vec![int(2)]), int(2)],
+                    ),
+                ),
+            ]),
+        ),
+    ]
+}
@@ -0,0 +1,25 @@
+use crate::build::{self, *};
+use alloc::{vec, vec::Vec};
+
+// mod geometry { fn id(x) { x } }
+// mod main { use geometry::{id as same}; fn main() { expect_int(same(2), 2) } }
+#[allow(dead_code)]
+pub fn modules() -> Vec<(&'static str, werbolg_core::Module)> {
+    vec![
+        ("geometry", build::module(vec![fun("id", &["x"], var("x"))])),
+        (
+            "main",
+            build::module(vec![
+                use_("geometry", &[], &[("id", "same")]),
+                fun(
+                    "main",
+                    &[],
+                    call(
+                        var("expect_int"),
+                        vec![call(var("same"), vec![int(2)]), int(2)],
+                    ),
+                ),
+            ]),
+        ),
+    ]
+}
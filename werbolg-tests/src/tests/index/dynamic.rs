@@ -0,0 +1,31 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn get(l, i) { l[i] }
+// fn main() {
+//     let l = [10, 20, 30];
+//     let _ = expect_int(get(l, 0), 10);
+//     expect_int(l[2], 30)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun("get", &["l", "i"], index(var("l"), var("i"))),
+        fun(
+            "main",
+            &[],
+            let_(
+                "l",
+                sequence(vec![int(10), int(20), int(30)]),
+                let_binder(
+                    ignore(),
+                    call(
+                        var("expect_int"),
+                        vec![call(var("get"), vec![var("l"), int(0)]), int(10)],
+                    ),
+                    call(var("expect_int"), vec![index(var("l"), int(2)), int(30)]),
+                ),
+            ),
+        ),
+    ])
+}
@@ -0,0 +1,43 @@
+mod dynamic;
+mod not_index;
+mod out_of_bound;
+mod tuple_static;
+
+#[test]
+fn dynamic() {
+    let mod1 = dynamic::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn tuple_static() {
+    let mod1 = tuple_static::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn out_of_bound() {
+    let mod1 = out_of_bound::module();
+    let r = crate::execute(mod1);
+    assert!(
+        matches!(
+            r,
+            Err(werbolg_exec::ExecutionError::IndexOutOfBound { index: 3, .. })
+        ),
+        "{:?}",
+        r
+    )
+}
+
+#[test]
+fn not_index() {
+    let mod1 = not_index::module();
+    let r = crate::execute(mod1);
+    assert!(
+        matches!(r, Err(werbolg_exec::ExecutionError::ValueNotIndex { .. })),
+        "{:?}",
+        r
+    )
+}
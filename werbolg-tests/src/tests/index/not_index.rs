@@ -0,0 +1,21 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// the index is a list, which isn't an index value
+//
+// fn main() {
+//     let l = [1, 2];
+//     l[l]
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_(
+            "l",
+            sequence(vec![int(1), int(2)]),
+            index(var("l"), var("l")),
+        ),
+    )])
+}
@@ -0,0 +1,15 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn main() {
+//     let l = [1, 2];
+//     l[3]
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_("l", sequence(vec![int(1), int(2)]), index(var("l"), int(3))),
+    )])
+}
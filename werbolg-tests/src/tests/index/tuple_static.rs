@@ -0,0 +1,29 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// tuples are also containers, and can be indexed by a static index
+//
+// fn main() {
+//     let t = (1, [2, 3]);
+//     let _ = expect_int(nth(t, 0), 1);
+//     expect_int(nth(nth(t, 1), 1), 3)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_(
+            "t",
+            tuple(vec![int(1), sequence(vec![int(2), int(3)])]),
+            let_binder(
+                ignore(),
+                call(var("expect_int"), vec![index_static(var("t"), 0), int(1)]),
+                call(
+                    var("expect_int"),
+                    vec![index_static(index_static(var("t"), 1), 1), int(3)],
+                ),
+            ),
+        ),
+    )])
+}
@@ -0,0 +1,22 @@
+use crate::build::{self, *};
+use alloc::{vec, vec::Vec};
+
+// mod main { use other; value x = y; fn main() { expect_int(x, 1) } }
+// mod other { use main; value y = x; }
+#[allow(dead_code)]
+pub fn modules() -> Vec<(&'static str, werbolg_core::Module)> {
+    vec![
+        (
+            "main",
+            build::module(vec![
+                use_("other", &[], &[]),
+                value("x", var("y")),
+                fun("main", &[], call(var("expect_int"), vec![var("x"), int(1)])),
+            ]),
+        ),
+        (
+            "other",
+            build::module(vec![use_("main", &[], &[]), value("y", var("x"))]),
+        ),
+    ]
+}
@@ -0,0 +1,22 @@
+use crate::build::{self, *};
+use alloc::{vec, vec::Vec};
+
+// mod main { use config; value y = get(); fn main() { expect_int(y, 3) } }
+// mod config { value x = 3; fn get() { x } }
+#[allow(dead_code)]
+pub fn modules() -> Vec<(&'static str, werbolg_core::Module)> {
+    vec![
+        (
+            "main",
+            build::module(vec![
+                use_("config", &[], &[]),
+                value("y", call(var("get"), vec![])),
+                fun("main", &[], call(var("expect_int"), vec![var("y"), int(3)])),
+            ]),
+        ),
+        (
+            "config",
+            build::module(vec![value("x", int(3)), fun("get", &[], var("x"))]),
+        ),
+    ]
+}
@@ -0,0 +1,57 @@
+mod cycle;
+mod dependency_order;
+mod recursive_dependency;
+mod statement;
+mod used_before_definition;
+mod values;
+
+#[test]
+fn values() {
+    let r = crate::execute(values::module());
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn dependency_order() {
+    let r = crate::execute_modules(dependency_order::modules());
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn recursive_dependency() {
+    let r = crate::execute_modules(recursive_dependency::modules());
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn used_before_definition() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", used_before_definition::module())]);
+    assert!(
+        matches!(e, CompilationError::ValueUsedBeforeDefinition(..)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn cycle() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(cycle::modules());
+    assert!(
+        matches!(e, CompilationError::ModuleInitCycle(..)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn statement() {
+    use crate::Value;
+
+    // the statement is evaluated by the initialiser, before the entry point
+    let r = crate::execute(statement::module());
+    assert!(matches!(r, Ok(Value::Bool(false))), "{:?}", r);
+}
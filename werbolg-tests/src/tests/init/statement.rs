@@ -0,0 +1,19 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// the statement records the result of the NIF in a module value
+//
+// value recorded = ref 0;
+// *recorded = int_eq(1, 2);
+// fn main() { *recorded }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        value("recorded", ref_new(int(0))),
+        statement(ref_set(
+            var("recorded"),
+            call(var("int_eq"), vec![int(1), int(2)]),
+        )),
+        fun("main", &[], ref_get(var("recorded"))),
+    ])
+}
@@ -0,0 +1,14 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// value x = y;
+// value y = 2;
+// fn main() { expect_int(x, 2) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        value("x", var("y")),
+        value("y", int(2)),
+        fun("main", &[], call(var("expect_int"), vec![var("x"), int(2)])),
+    ])
+}
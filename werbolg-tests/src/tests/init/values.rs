@@ -0,0 +1,14 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// value x = 2;
+// value y = x;
+// fn main() { expect_int(y, 2) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        value("x", int(2)),
+        value("y", var("x")),
+        fun("main", &[], call(var("expect_int"), vec![var("y"), int(2)])),
+    ])
+}
@@ -0,0 +1,29 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// the values already pushed for the calls are dropped when breaking out of the loops
+//
+// fn main() {
+//     let a = loop {
+//         expect_int(loop { expect_int(1, break 1) }, break 2)
+//     };
+//     expect_int(a, 2)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_(
+            "a",
+            loop_(call(
+                var("expect_int"),
+                vec![
+                    loop_(call(var("expect_int"), vec![int(1), break_(Some(int(1)))])),
+                    break_(Some(int(2))),
+                ],
+            )),
+            call(var("expect_int"), vec![var("a"), int(2)]),
+        ),
+    )])
+}
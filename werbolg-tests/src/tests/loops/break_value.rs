@@ -0,0 +1,25 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn main() {
+//     let a = loop {
+//         if int_eq(1, 2) { continue } else { break 3 }
+//     };
+//     expect_int(a, 3)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_(
+            "a",
+            loop_(if_(
+                call(var("int_eq"), vec![int(1), int(2)]),
+                continue_(),
+                break_(Some(int(3))),
+            )),
+            call(var("expect_int"), vec![var("a"), int(3)]),
+        ),
+    )])
+}
@@ -0,0 +1,23 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// a lambda body is not part of the loop it is defined in
+//
+// fn main() {
+//     loop {
+//         let f = || continue;
+//         break f()
+//     }
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        loop_(let_(
+            "f",
+            lambda(&[], continue_()),
+            break_(Some(call(var("f"), vec![]))),
+        )),
+    )])
+}
@@ -0,0 +1,31 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// the locals of the loop body are not visible after the loop
+//
+// fn main() {
+//     let a = 1;
+//     let b = loop { let a = 2; break a };
+//     expect_int(a, 1);
+//     expect_int(b, 2)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_(
+            "a",
+            int(1),
+            let_(
+                "b",
+                loop_(let_("a", int(2), break_(Some(var("a"))))),
+                let_binder(
+                    ignore(),
+                    call(var("expect_int"), vec![var("a"), int(1)]),
+                    call(var("expect_int"), vec![var("b"), int(2)]),
+                ),
+            ),
+        ),
+    )])
+}
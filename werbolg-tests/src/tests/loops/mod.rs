@@ -0,0 +1,66 @@
+mod break_in_arguments;
+mod break_outside_loop;
+mod break_value;
+mod continue_in_lambda;
+mod local_scope;
+mod while_break;
+mod while_false;
+
+#[test]
+fn break_value() {
+    let mod1 = break_value::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn break_in_arguments() {
+    let mod1 = break_in_arguments::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn local_scope() {
+    let mod1 = local_scope::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn while_false() {
+    let mod1 = while_false::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn while_break() {
+    let mod1 = while_break::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn break_outside_loop() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", break_outside_loop::module())]);
+    assert!(
+        matches!(e, CompilationError::BreakOutsideLoop(..)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn continue_in_lambda() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", continue_in_lambda::module())]);
+    assert!(
+        matches!(e, CompilationError::ContinueOutsideLoop(..)),
+        "{:?}",
+        e
+    );
+}
@@ -0,0 +1,23 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn main() {
+//     expect_int(while int_eq(1, 1) { break 4 }, 4)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        call(
+            var("expect_int"),
+            vec![
+                while_(
+                    call(var("int_eq"), vec![int(1), int(1)]),
+                    break_(Some(int(4))),
+                ),
+                int(4),
+            ],
+        ),
+    )])
+}
@@ -0,0 +1,22 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn main() {
+//     while int_eq(1, 2) { expect_int(1, 2) };
+//     expect_int(3, 3)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_binder(
+            ignore(),
+            while_(
+                call(var("int_eq"), vec![int(1), int(2)]),
+                call(var("expect_int"), vec![int(1), int(2)]),
+            ),
+            call(var("expect_int"), vec![int(3), int(3)]),
+        ),
+    )])
+}
@@ -0,0 +1,62 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// enum Shape { Empty, Circle(r), Rect(w, h) }
+// fn size(s) { match s { Shape::Empty => 0, Shape::Circle(r) => r, Shape::Rect { h, .. } => h } }
+// fn main() {
+//     let _ = expect_int(size(Shape::Empty), 0);
+//     let _ = expect_int(size(Shape::Circle(5)), 5);
+//     expect_int(size(Shape::Rect(2, 3)), 3)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        enum_(
+            "Shape",
+            &[("Empty", &[]), ("Circle", &["r"]), ("Rect", &["w", "h"])],
+        ),
+        fun(
+            "size",
+            &["s"],
+            match_(
+                var("s"),
+                vec![
+                    (pat(bind_seq("Shape::Empty", &[], BindEllipsis::No)), int(0)),
+                    (
+                        pat(bind_seq("Shape::Circle", &[bind("r")], BindEllipsis::No)),
+                        var("r"),
+                    ),
+                    (
+                        pat(bind_named(
+                            "Shape::Rect",
+                            &[("h", bind("h"))],
+                            BindEllipsis::Yes,
+                        )),
+                        var("h"),
+                    ),
+                ],
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                ignore(),
+                expect_size(var("Shape::Empty"), 0),
+                let_binder(
+                    ignore(),
+                    expect_size(call(var("Shape::Circle"), vec![int(5)]), 5),
+                    expect_size(call(var("Shape::Rect"), vec![int(2), int(3)]), 3),
+                ),
+            ),
+        ),
+    ])
+}
+
+fn expect_size(shape: werbolg_core::ir::Expr, expected: u64) -> werbolg_core::ir::Expr {
+    call(
+        var("expect_int"),
+        vec![call(var("size"), vec![shape]), int(expected)],
+    )
+}
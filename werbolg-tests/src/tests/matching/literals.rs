@@ -0,0 +1,42 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn f(n) { match n { 0 => 10, 1 => 11, x => x } }
+// fn main() {
+//     let _ = expect_int(f(0), 10);
+//     let _ = expect_int(f(1), 11);
+//     expect_int(f(7), 7)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun(
+            "f",
+            &["n"],
+            match_(
+                var("n"),
+                vec![
+                    (pat_int(0), int(10)),
+                    (pat_int(1), int(11)),
+                    (pat(bind("x")), var("x")),
+                ],
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                ignore(),
+                expect_f(0, 10),
+                let_binder(ignore(), expect_f(1, 11), expect_f(7, 7)),
+            ),
+        ),
+    ])
+}
+
+fn expect_f(n: u64, expected: u64) -> werbolg_core::ir::Expr {
+    call(
+        var("expect_int"),
+        vec![call(var("f"), vec![int(n)]), int(expected)],
+    )
+}
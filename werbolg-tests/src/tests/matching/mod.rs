@@ -0,0 +1,63 @@
+mod enum_variants;
+mod literals;
+mod nested;
+mod non_exhaustive;
+mod non_exhaustive_literals;
+mod unreachable;
+
+#[test]
+fn enum_variants() {
+    let mod1 = enum_variants::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn literals() {
+    let mod1 = literals::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn nested() {
+    let mod1 = nested::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn non_exhaustive() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", non_exhaustive::module())]);
+    assert!(
+        matches!(e, CompilationError::MatchNonExhaustive(..)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn non_exhaustive_literals() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", non_exhaustive_literals::module())]);
+    assert!(
+        matches!(e, CompilationError::MatchNonExhaustive(..)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn unreachable() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", unreachable::module())]);
+    assert!(
+        matches!(e, CompilationError::MatchArmUnreachable(..)),
+        "{:?}",
+        e
+    );
+}
@@ -0,0 +1,68 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// enum Opt { None, Some(v) }
+// fn f(o) { let r = match o { Opt::Some(Opt::Some(x)) => x, Opt::Some(_) => 1, Opt::None => 0 }; r }
+// fn main() {
+//     let _ = expect_int(f(Opt::Some(Opt::Some(5))), 5);
+//     let _ = expect_int(f(Opt::Some(Opt::None)), 1);
+//     expect_int(f(Opt::None), 0)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        enum_("Opt", &[("None", &[]), ("Some", &["v"])]),
+        fun(
+            "f",
+            &["o"],
+            // the match is not in tail position, to check the jump to the end of the match
+            let_(
+                "r",
+                match_(
+                    var("o"),
+                    vec![
+                        (
+                            pat(bind_seq(
+                                "Opt::Some",
+                                &[bind_seq("Opt::Some", &[bind("x")], BindEllipsis::No)],
+                                BindEllipsis::No,
+                            )),
+                            var("x"),
+                        ),
+                        (
+                            pat(bind_seq("Opt::Some", &[ignore()], BindEllipsis::No)),
+                            int(1),
+                        ),
+                        (pat(bind_seq("Opt::None", &[], BindEllipsis::No)), int(0)),
+                    ],
+                ),
+                var("r"),
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                ignore(),
+                expect_f(some(some(int(5))), 5),
+                let_binder(
+                    ignore(),
+                    expect_f(some(var("Opt::None")), 1),
+                    expect_f(var("Opt::None"), 0),
+                ),
+            ),
+        ),
+    ])
+}
+
+fn some(v: werbolg_core::ir::Expr) -> werbolg_core::ir::Expr {
+    call(var("Opt::Some"), vec![v])
+}
+
+fn expect_f(o: werbolg_core::ir::Expr, expected: u64) -> werbolg_core::ir::Expr {
+    call(
+        var("expect_int"),
+        vec![call(var("f"), vec![o]), int(expected)],
+    )
+}
@@ -0,0 +1,12 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn main() { match 1 { 0 => 0, 1 => 1 } }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        match_(int(1), vec![(pat_int(0), int(0)), (pat_int(1), int(1))]),
+    )])
+}
@@ -0,0 +1,27 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// enum Opt { None, Some(v) }
+// fn main() { match Opt::None { Opt::Some(_) => 1, x => 0, Opt::None => 2 } }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        enum_("Opt", &[("None", &[]), ("Some", &["v"])]),
+        fun(
+            "main",
+            &[],
+            match_(
+                var("Opt::None"),
+                vec![
+                    (
+                        pat(bind_seq("Opt::Some", &[ignore()], BindEllipsis::No)),
+                        int(1),
+                    ),
+                    (pat(bind("x")), int(0)),
+                    (pat(bind_seq("Opt::None", &[], BindEllipsis::No)), int(2)),
+                ],
+            ),
+        ),
+    ])
+}
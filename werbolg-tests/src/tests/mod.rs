@@ -1,5 +1,25 @@
 mod assignment;
+mod backtrace;
+mod bytecode;
 mod call;
+mod closure;
+mod deconstruct;
+mod direct_call;
+mod enums;
+mod errors;
+mod imports;
+mod index;
+mod init;
+mod loops;
+mod matching;
 mod numbers;
+mod privacy;
 mod r#return;
+mod serialize;
+mod sourcemap;
+mod structure;
+mod text;
+mod tuples;
 mod variable;
+mod verify;
+mod warnings;
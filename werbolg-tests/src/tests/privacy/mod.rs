@@ -0,0 +1,25 @@
+mod private_fun;
+mod private_struct;
+mod same_module;
+
+#[test]
+fn same_module() {
+    let r = crate::execute_modules(same_module::modules());
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn private_fun() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(private_fun::modules());
+    assert!(matches!(e, CompilationError::PrivateSymbol(..)), "{:?}", e);
+}
+
+#[test]
+fn private_struct() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(private_struct::modules());
+    assert!(matches!(e, CompilationError::PrivateSymbol(..)), "{:?}", e);
+}
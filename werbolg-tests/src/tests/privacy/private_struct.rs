@@ -0,0 +1,26 @@
+use crate::build::{self, *};
+use alloc::{vec, vec::Vec};
+
+// mod geometry { struct Point { x, y } }
+// mod main { fn main() { let _ = geometry::Point { x: 1, y: 2 }; expect_int(1, 1) } }
+#[allow(dead_code)]
+pub fn modules() -> Vec<(&'static str, werbolg_core::Module)> {
+    vec![
+        (
+            "geometry",
+            build::module(vec![struct_private("Point", &["x", "y"])]),
+        ),
+        (
+            "main",
+            build::module(vec![fun(
+                "main",
+                &[],
+                let_binder(
+                    ignore(),
+                    struct_named("geometry::Point", vec![("x", int(1)), ("y", int(2))]),
+                    call(var("expect_int"), vec![int(1), int(1)]),
+                ),
+            )]),
+        ),
+    ]
+}
@@ -0,0 +1,31 @@
+use crate::build::{self, *};
+use alloc::{vec, vec::Vec};
+
+// mod geometry { fn id(x) { x } pub fn apply(x) { id(x) } }
+// mod main { use geometry; fn main() { expect_int(apply(2), 2) } }
+#[allow(dead_code)]
+pub fn modules() -> Vec<(&'static str, werbolg_core::Module)> {
+    vec![
+        (
+            "geometry",
+            build::module(vec![
+                fun_private("id", &["x"], var("x")),
+                fun("apply", &["x"], call(var("id"), vec![var("x")])),
+            ]),
+        ),
+        (
+            "main",
+            build::module(vec![
+                use_("geometry", &[], &[]),
+                fun(
+                    "main",
+                    &[],
+                    call(
+                        var("expect_int"),
+                        vec![call(var("apply"), vec![int(2)]), int(2)],
+                    ),
+                ),
+            ]),
+        ),
+    ]
+}
@@ -0,0 +1,26 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// the values already pushed for the call are dropped by the return
+//
+// fn early() {
+//     expect_int(1, return 2)
+// }
+// fn main() {
+//     expect_int(early(), 2)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun(
+            "early",
+            &[],
+            call(var("expect_int"), vec![int(1), return_(Some(int(2)))]),
+        ),
+        fun(
+            "main",
+            &[],
+            call(var("expect_int"), vec![call(var("early"), vec![]), int(2)]),
+        ),
+    ])
+}
@@ -0,0 +1,16 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// return is only valid in a function
+//
+// return 1;
+// fn main() {
+//     1
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        statement(return_(Some(int(1)))),
+        fun("main", &[], int(1)),
+    ])
+}
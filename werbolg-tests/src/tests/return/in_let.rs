@@ -0,0 +1,49 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn guard(x) {
+//     let a = if int_eq(x, 0) { return 10 } else { x };
+//     expect_int(a, 1);
+//     20
+// }
+// fn main() {
+//     expect_int(guard(0), 10);
+//     expect_int(guard(1), 20)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun(
+            "guard",
+            &["x"],
+            let_(
+                "a",
+                if_(
+                    call(var("int_eq"), vec![var("x"), int(0)]),
+                    return_(Some(int(10))),
+                    var("x"),
+                ),
+                let_binder(
+                    ignore(),
+                    call(var("expect_int"), vec![var("a"), int(1)]),
+                    int(20),
+                ),
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                ignore(),
+                call(
+                    var("expect_int"),
+                    vec![call(var("guard"), vec![int(0)]), int(10)],
+                ),
+                call(
+                    var("expect_int"),
+                    vec![call(var("guard"), vec![int(1)]), int(20)],
+                ),
+            ),
+        ),
+    ])
+}
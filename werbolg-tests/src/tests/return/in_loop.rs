@@ -0,0 +1,20 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn first() {
+//     loop { return 3 }
+// }
+// fn main() {
+//     expect_int(first(), 3)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun("first", &[], loop_(return_(Some(int(3))))),
+        fun(
+            "main",
+            &[],
+            call(var("expect_int"), vec![call(var("first"), vec![]), int(3)]),
+        ),
+    ])
+}
@@ -1,5 +1,9 @@
 mod at_top_level;
+mod in_arguments;
 mod in_function;
+mod in_initialiser;
+mod in_let;
+mod in_loop;
 
 #[test]
 fn at_top_level() {
@@ -19,3 +23,36 @@ fn in_function() {
     let r = crate::execute(mod1);
     assert!(r.is_ok(), "{:?}", r.err())
 }
+
+#[test]
+fn in_let() {
+    let mod1 = in_let::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn in_arguments() {
+    let mod1 = in_arguments::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn in_loop() {
+    let mod1 = in_loop::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn in_initialiser() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", in_initialiser::module())]);
+    assert!(
+        matches!(e, CompilationError::ReturnOutsideFunction(..)),
+        "{:?}",
+        e
+    );
+}
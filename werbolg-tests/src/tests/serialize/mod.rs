@@ -0,0 +1,28 @@
+#[test]
+fn module_round_trip() {
+    let mod1 = werbolg_core::text::parse_module(super::text::golden::TEXT).expect("valid IR");
+    let json = serde_json::to_string(&mod1).expect("serializable module");
+    let mod2: werbolg_core::Module = serde_json::from_str(&json).expect("deserializable module");
+    assert_eq!(alloc::format!("{:?}", mod1), alloc::format!("{:?}", mod2));
+    let r = crate::execute(mod2);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn compilation_unit_round_trip() {
+    let mod1 = werbolg_core::text::parse_module(super::text::golden::TEXT).expect("valid IR");
+    let (unit, environ) = crate::compile_modules(alloc::vec![("main", mod1)]);
+    let json = serde_json::to_string(&unit).expect("serializable compilation unit");
+    let unit2: werbolg_compile::CompilationUnit<crate::MyLiteral> =
+        serde_json::from_str(&json).expect("deserializable compilation unit");
+    assert_eq!(dump(&unit), dump(&unit2));
+    let r = crate::execute_compiled(unit2, environ);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[allow(dead_code)]
+fn dump(unit: &werbolg_compile::CompilationUnit<crate::MyLiteral>) -> alloc::string::String {
+    let mut out = alloc::string::String::new();
+    werbolg_compile::code_dump(&mut out, &unit.code, &unit.funs).expect("writing to string work");
+    out
+}
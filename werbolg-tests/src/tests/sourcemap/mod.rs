@@ -0,0 +1,67 @@
+use werbolg_compile::{CompilationUnit, Instruction};
+use werbolg_core::{AbsPath, Span};
+
+/// Return the spans of the instructions of the function at path, filtered by f
+#[allow(dead_code)]
+fn spans<L, F: Fn(&Instruction) -> bool>(
+    unit: &CompilationUnit<L>,
+    path: &AbsPath,
+    f: F,
+) -> alloc::vec::Vec<Option<Span>> {
+    let funid = unit.funs_tbl.get(path).expect("existing function");
+    let start = unit.funs[funid].code_pos;
+    unit.code
+        .iter()
+        .skip_while(|(ia, _)| *ia < start)
+        .take_while(|(ia, _)| *ia == start || unit.funs.iter().all(|(_, f)| f.code_pos != *ia))
+        .filter(|(_, instruction)| f(instruction))
+        .map(|(ia, _)| unit.instruction_span(ia).cloned())
+        .collect()
+}
+
+#[test]
+fn instructions() {
+    use werbolg_compile::TailCall;
+    use werbolg_core::{Ident, Namespace};
+
+    let mod1 = werbolg_core::text::parse_module(super::text::golden::TEXT).expect("valid IR");
+    let (unit, _) = crate::compile_modules(alloc::vec![("main", mod1)]);
+    let main_ns = Namespace::root().append(Ident::from("main"));
+    let main = AbsPath::new(&main_ns, &Ident::from("main"));
+
+    // each call is mapped to the whole call expression, from the innermost
+    let calls = spans(&unit, &main, |i| {
+        matches!(i, Instruction::Call(..) | Instruction::CallFun(..))
+    });
+    assert_eq!(calls, [Some(211..228), Some(200..232)]);
+    let tail_calls = spans(&unit, &main, |i| {
+        matches!(
+            i,
+            Instruction::Call(TailCall::Yes, _) | Instruction::CallFun(_, _, TailCall::Yes)
+        )
+    });
+    assert_eq!(tail_calls, [Some(200..232)]);
+
+    // the literals are mapped to themselves, and the first one is the `x` field of the point
+    let literals = spans(&unit, &main, |i| matches!(i, Instruction::PushLiteral(_)));
+    assert_eq!(literals[0], Some(155..156));
+
+    let funid = unit.funs_tbl.get(&main).unwrap();
+    assert_eq!(unit.fun_namespace(funid), Some(&main_ns));
+}
+
+#[test]
+fn execution_error() {
+    let text = r#"(fun @0..40 pub main ()
+  (let (tuple a b) (num @20..21 1) a@38..39))"#;
+    let mod1 = werbolg_core::text::parse_module(text).expect("valid IR");
+    let (unit, environ) = crate::compile_modules(alloc::vec![("main", mod1)]);
+    let (mut em, entry_point) = crate::machine(unit, environ);
+    let r = werbolg_exec::exec(&mut em, entry_point, &[]);
+    assert!(matches!(
+        r,
+        Err(werbolg_exec::ExecutionError::TupleLengthMismatch { .. })
+    ));
+    // the destructuring of the value is mapped to the value bound
+    assert_eq!(em.module.instruction_span(em.ip), Some(&(20..21)));
+}
@@ -0,0 +1,67 @@
+mod named;
+mod named_duplicate;
+mod named_missing;
+mod sequential;
+mod too_many_fields;
+
+#[test]
+fn sequential() {
+    let mod1 = sequential::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn named() {
+    let mod1 = named::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn named_missing() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", named_missing::module())]);
+    assert!(
+        matches!(e, CompilationError::StructureFieldsMissing(..)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn named_duplicate() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", named_duplicate::module())]);
+    assert!(
+        matches!(e, CompilationError::StructureFieldDuplicate(..)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn too_many_fields() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", too_many_fields::module())]);
+    assert!(
+        matches!(e, CompilationError::CallTooManyArguments(_, 256)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn too_many_fields_call() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", too_many_fields::module_call())]);
+    assert!(
+        matches!(e, CompilationError::CallTooManyArguments(_, 256)),
+        "{:?}",
+        e
+    );
+}
@@ -0,0 +1,21 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// struct Point { x, y }
+// fn main() { let Point(x, ..) = Point { y: 2, x: 1 }; expect_int(x, 1) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        struct_("Point", &["x", "y"]),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                bind_seq("Point", &[bind("x")], BindEllipsis::Yes),
+                struct_named("Point", vec![("y", int(2)), ("x", int(1))]),
+                call(var("expect_int"), vec![var("x"), int(1)]),
+            ),
+        ),
+    ])
+}
@@ -0,0 +1,12 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// struct Point { x, y }
+// fn main() { Point { x: 1 } }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        struct_("Point", &["x", "y"]),
+        fun("main", &[], struct_named("Point", vec![("x", int(1))])),
+    ])
+}
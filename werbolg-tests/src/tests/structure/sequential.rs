@@ -0,0 +1,21 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// struct Point { x, y }
+// fn main() { let Point(x, y) = Point(1, 2); expect_int(y, 2) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        struct_("Point", &["x", "y"]),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                bind_seq("Point", &[bind("x"), bind("y")], BindEllipsis::No),
+                struct_seq("Point", vec![int(1), int(2)]),
+                call(var("expect_int"), vec![var("y"), int(2)]),
+            ),
+        ),
+    ])
+}
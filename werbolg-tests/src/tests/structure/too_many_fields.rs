@@ -0,0 +1,35 @@
+use crate::build::{self, *};
+use alloc::{format, string::String, vec, vec::Vec};
+
+// struct Big { f0, .., f255 }
+// fn main() { Big(0, .., 0) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    let fields = (0..256).map(|i| format!("f{}", i)).collect::<Vec<String>>();
+    let fields = fields.iter().map(|f| f.as_str()).collect::<Vec<_>>();
+    build::module(vec![
+        struct_("Big", &fields),
+        fun(
+            "main",
+            &[],
+            struct_seq("Big", (0..256).map(|_| int(0)).collect()),
+        ),
+    ])
+}
+
+// struct Big { f0, .., f255 }
+// fn main() { Big(0, .., 0) }
+// with the structure created by calling its constructor
+#[allow(dead_code)]
+pub fn module_call() -> werbolg_core::Module {
+    let fields = (0..256).map(|i| format!("f{}", i)).collect::<Vec<String>>();
+    let fields = fields.iter().map(|f| f.as_str()).collect::<Vec<_>>();
+    build::module(vec![
+        struct_("Big", &fields),
+        fun(
+            "main",
+            &[],
+            call(var("Big"), (0..256).map(|_| int(0)).collect()),
+        ),
+    ])
+}
@@ -0,0 +1,28 @@
+// struct Point { x, y }
+// enum Opt { None, Some(v) }
+// fn get(o, default) { match o { Opt::Some(v) => v, Opt::None => default } }
+// fn main() {
+//     let p = Point { x: 1, y: 2 };
+//     let (a, _) = (p.x, 3);
+//     expect_int(get(Opt::Some(a), 0), 1)
+// }
+#[allow(dead_code)]
+pub const TEXT: &str = r#"(struct @0..22 pub Point@7..12 (x@15..16 y@18..19))
+(enum @23..49 pub Opt@28..31 (variant pub None@34..38 ()) (variant pub Some@40..44 (v@45..46)))
+(fun @50..125 pub get (o@57..58 default@60..67)
+  (match @71..123 o@77..78
+    (arm @81..96 (destruct Opt::Some seq v) v@95..96)
+    (arm @98..121 (destruct Opt::None seq) default@114..121)))
+(fun @126..234 pub main ()
+  (let p
+    (make-named @144..164 Point@144..149
+      (x@152..153 (num @155..156 1))
+      (y@158..159 (num @161..162 2)))
+    (let (tuple a (ignore))
+      (tuple @186..194 (field p@187..188 Point@187..188 x@189..190) (num @192..193 3))
+      (call @200..232 expect_int@200..210
+        (call @211..228 get@211..214
+          (call @215..224 Opt::Some@215..224 a@225..226)
+          (num @227..228 0))
+        (num @230..231 1)))))
+"#;
@@ -0,0 +1,44 @@
+pub(super) mod golden;
+mod round_trip;
+
+use alloc::string::String;
+use werbolg_core::text;
+
+#[allow(dead_code)]
+fn print(module: &werbolg_core::Module) -> String {
+    let mut out = String::new();
+    text::write_module(&mut out, module).expect("writing to string work");
+    out
+}
+
+#[test]
+fn golden_execute() {
+    let mod1 = text::parse_module(golden::TEXT).expect("valid textual IR");
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn golden_print() {
+    let mod1 = text::parse_module(golden::TEXT).expect("valid textual IR");
+    assert_eq!(print(&mod1), golden::TEXT)
+}
+
+#[test]
+fn round_trip() {
+    let mod1 = round_trip::module();
+    let text1 = print(&mod1);
+    let parsed = text::parse_module(&text1).expect("printed IR is valid");
+    assert_eq!(alloc::format!("{:?}", parsed), alloc::format!("{:?}", mod1));
+    assert_eq!(print(&parsed), text1)
+}
+
+#[test]
+fn unknown_keyword() {
+    let r = text::parse_expr("(call @0..1 f@0..1 (frobnicate @0..1))");
+    assert!(
+        matches!(&r, Err(e) if e.kind == text::TextErrorKind::UnknownKeyword(String::from("frobnicate"))),
+        "{:?}",
+        r
+    )
+}
@@ -0,0 +1,83 @@
+use crate::build::{self, *};
+use alloc::{boxed::Box, vec};
+use werbolg_core::ir::{self, BindEllipsis};
+use werbolg_core::{Literal, Path, PathType, Spanned};
+
+// use other hiding hidden, a as b
+// struct Pair { first, second } (private)
+// const ZERO = 0
+// fn main("with space", "quote\"d") {
+//     let r = ref [1, 2];
+//     if int_eq((*r)[0], 1) {
+//         while ZERO { match () { 1 => break, _ => continue } }
+//     } else {
+//         loop { return |x| x = r.3 }
+//     }
+// }
+// ::main::Pair { first: 1, second: 2 }.first
+// match ["new\nline \"quoted\"", #[0x00, 0x01, 0xfe], 1.5] { Pair { first: f, .. } => f, () => a := Pair() }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        use_("other", &["hidden"], &[("a", "b")]),
+        struct_private("Pair", &["first", "second"]),
+        value("ZERO", int(0)),
+        fun(
+            "main",
+            &["with space", "quote\"d"],
+            let_(
+                "r",
+                ref_new(sequence(vec![int(1), int(2)])),
+                if_(
+                    call(
+                        var("int_eq"),
+                        vec![index(ref_get(var("r")), int(0)), int(1)],
+                    ),
+                    while_(
+                        var("ZERO"),
+                        match_(
+                            tuple(vec![]),
+                            vec![(pat_int(1), break_(None)), (pat(ignore()), continue_())],
+                        ),
+                    ),
+                    loop_(return_(Some(lambda(
+                        &["x"],
+                        assign("x", index_static(var("r"), 3)),
+                    )))),
+                ),
+            ),
+        ),
+        statement(ir::Expr::Field(
+            Box::new(struct_named(
+                "Pair",
+                vec![("first", int(1)), ("second", int(2))],
+            )),
+            Spanned::new(
+                3..7,
+                Path::new_raw(PathType::Absolute, vec!["main".into(), "Pair".into()]),
+            ),
+            Spanned::new(8..13, "first".into()),
+        )),
+        statement(match_(
+            sequence(vec![
+                ir::Expr::Literal(1..2, Literal::String("new\nline \"quoted\"".into())),
+                ir::Expr::Literal(3..4, Literal::Bytes(vec![0, 1, 0xfe].into())),
+                ir::Expr::Literal(5..6, Literal::Decimal("1.5".into())),
+            ]),
+            vec![
+                (
+                    pat(bind_named(
+                        "Pair",
+                        &[("first", bind("f"))],
+                        BindEllipsis::Yes,
+                    )),
+                    var("f"),
+                ),
+                (
+                    pat(bind_tuple(&[])),
+                    ref_set(var("a"), struct_seq("Pair", vec![])),
+                ),
+            ],
+        )),
+    ])
+}
@@ -0,0 +1,29 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn first(t) { match t { (a, b) => a, (a, b, c) => a } }
+// fn main() { first((1, 2)) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun(
+            "first",
+            &["t"],
+            match_(
+                var("t"),
+                vec![
+                    (pat(bind_tuple(&[bind("a"), bind("b")])), var("a")),
+                    (
+                        pat(bind_tuple(&[bind("a"), bind("b"), bind("c")])),
+                        var("a"),
+                    ),
+                ],
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            call(var("first"), vec![tuple(vec![int(1), int(2)])]),
+        ),
+    ])
+}
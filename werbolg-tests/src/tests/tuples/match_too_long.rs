@@ -0,0 +1,25 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// the tuple pattern has less elements than the tuple value, which is only known at execution
+//
+// fn first(t) { match t { (a, _) => a } }
+// fn main() { first((1, 2, 3)) }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun(
+            "first",
+            &["t"],
+            match_(
+                var("t"),
+                vec![(pat(bind_tuple(&[bind("a"), ignore()])), var("a"))],
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            call(var("first"), vec![tuple(vec![int(1), int(2), int(3)])]),
+        ),
+    ])
+}
@@ -0,0 +1,62 @@
+use crate::build::{self, *};
+use alloc::vec;
+use werbolg_core::ir::BindEllipsis;
+
+// enum Opt { None, Some(v) }
+// fn first_or(t) { match t { (Opt::Some(x), _) => x, (Opt::None, y) => y } }
+// fn main() {
+//     let _ = expect_int(first_or((Opt::Some(1), 2)), 1);
+//     expect_int(first_or((Opt::None, 3)), 3)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        enum_("Opt", &[("None", &[]), ("Some", &["v"])]),
+        fun(
+            "first_or",
+            &["t"],
+            match_(
+                var("t"),
+                vec![
+                    (
+                        pat(bind_tuple(&[
+                            bind_seq("Opt::Some", &[bind("x")], BindEllipsis::No),
+                            ignore(),
+                        ])),
+                        var("x"),
+                    ),
+                    (
+                        pat(bind_tuple(&[
+                            bind_seq("Opt::None", &[], BindEllipsis::No),
+                            bind("y"),
+                        ])),
+                        var("y"),
+                    ),
+                ],
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                ignore(),
+                expect_first_or(call(var("Opt::Some"), vec![int(1)]), 2, 1),
+                expect_first_or(var("Opt::None"), 3, 3),
+            ),
+        ),
+    ])
+}
+
+fn expect_first_or(
+    first: werbolg_core::ir::Expr,
+    second: u64,
+    expected: u64,
+) -> werbolg_core::ir::Expr {
+    call(
+        var("expect_int"),
+        vec![
+            call(var("first_or"), vec![tuple(vec![first, int(second)])]),
+            int(expected),
+        ],
+    )
+}
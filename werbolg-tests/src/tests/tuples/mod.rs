@@ -0,0 +1,116 @@
+mod bind;
+mod match_lengths;
+mod match_too_long;
+mod match_tuple;
+mod nested;
+mod return_pair;
+mod too_long;
+mod too_short;
+mod value_prefix;
+mod value_too_short;
+
+#[test]
+fn bind() {
+    let mod1 = bind::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn return_pair() {
+    let mod1 = return_pair::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn nested() {
+    let mod1 = nested::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn match_tuple() {
+    let mod1 = match_tuple::module();
+    let r = crate::execute(mod1);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn too_short() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", too_short::module())]);
+    assert!(
+        matches!(e, CompilationError::TupleBinderMismatch(_, 2, 3)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn too_long() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", too_long::module())]);
+    assert!(
+        matches!(e, CompilationError::TupleBinderMismatch(_, 2, 1)),
+        "{:?}",
+        e
+    );
+}
+
+#[test]
+fn value_prefix() {
+    let mod1 = value_prefix::module();
+    let r = crate::execute(mod1);
+    assert!(
+        matches!(
+            r,
+            Err(werbolg_exec::ExecutionError::TupleLengthMismatch { .. })
+        ),
+        "{:?}",
+        r
+    )
+}
+
+#[test]
+fn value_too_short() {
+    let mod1 = value_too_short::module();
+    let r = crate::execute(mod1);
+    assert!(
+        matches!(
+            r,
+            Err(werbolg_exec::ExecutionError::TupleLengthMismatch { .. })
+        ),
+        "{:?}",
+        r
+    )
+}
+
+#[test]
+fn match_too_long() {
+    let mod1 = match_too_long::module();
+    let r = crate::execute(mod1);
+    assert!(
+        matches!(
+            r,
+            Err(werbolg_exec::ExecutionError::TupleLengthMismatch { .. })
+        ),
+        "{:?}",
+        r
+    )
+}
+
+#[test]
+fn match_lengths() {
+    use werbolg_compile::CompilationError;
+
+    let e = crate::compile_error(alloc::vec![("main", match_lengths::module())]);
+    assert!(
+        matches!(e, CompilationError::MatchArmIncompatible(..)),
+        "{:?}",
+        e
+    );
+}
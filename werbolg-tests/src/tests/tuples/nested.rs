@@ -0,0 +1,29 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn main() {
+//     let (a, (b, c)) = (1, (2, 3));
+//     let _ = expect_int(a, 1);
+//     let _ = expect_int(b, 2);
+//     expect_int(c, 3)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_binder(
+            bind_tuple(&[bind("a"), bind_tuple(&[bind("b"), bind("c")])]),
+            tuple(vec![int(1), tuple(vec![int(2), int(3)])]),
+            let_binder(
+                ignore(),
+                call(var("expect_int"), vec![var("a"), int(1)]),
+                let_binder(
+                    ignore(),
+                    call(var("expect_int"), vec![var("b"), int(2)]),
+                    call(var("expect_int"), vec![var("c"), int(3)]),
+                ),
+            ),
+        ),
+    )])
+}
@@ -0,0 +1,28 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn swap(a, b) { (b, a) }
+// fn main() {
+//     let (x, y) = swap(1, 2);
+//     let _ = expect_int(x, 2);
+//     expect_int(y, 1)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun("swap", &["a", "b"], tuple(vec![var("b"), var("a")])),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                bind_tuple(&[bind("x"), bind("y")]),
+                call(var("swap"), vec![int(1), int(2)]),
+                let_binder(
+                    ignore(),
+                    call(var("expect_int"), vec![var("x"), int(2)]),
+                    call(var("expect_int"), vec![var("y"), int(1)]),
+                ),
+            ),
+        ),
+    ])
+}
@@ -0,0 +1,21 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// the binder has more elements than the tuple
+//
+// fn main() {
+//     let (a, b, c) = (1, 2);
+//     a
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![fun(
+        "main",
+        &[],
+        let_binder(
+            bind_tuple(&[bind("a"), bind("b"), bind("c")]),
+            tuple(vec![int(1), int(2)]),
+            var("a"),
+        ),
+    )])
+}
@@ -0,0 +1,25 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// the binder has less elements than the tuple value, which is only known at execution
+//
+// fn triple() { (1, 2, 3) }
+// fn main() {
+//     let (a, b) = triple();
+//     expect_int(b, 2)
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun("triple", &[], tuple(vec![int(1), int(2), int(3)])),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                bind_tuple(&[bind("a"), bind("b")]),
+                call(var("triple"), vec![]),
+                call(var("expect_int"), vec![var("b"), int(2)]),
+            ),
+        ),
+    ])
+}
@@ -0,0 +1,25 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// the binder has more elements than the tuple value
+//
+// fn pair() { (1, 2) }
+// fn main() {
+//     let (a, b, c) = pair();
+//     a
+// }
+#[allow(dead_code)]
+pub fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun("pair", &[], tuple(vec![int(1), int(2)])),
+        fun(
+            "main",
+            &[],
+            let_binder(
+                bind_tuple(&[bind("a"), bind("b"), bind("c")]),
+                call(var("pair"), vec![]),
+                var("a"),
+            ),
+        ),
+    ])
+}
@@ -0,0 +1,240 @@
+use crate::value::Value;
+use crate::{DummyAlloc, MyLiteral};
+use alloc::{vec, vec::Vec};
+use werbolg_compile::{
+    CallArity, CompilationUnit, FunDef, Instruction, InstructionAddress, InstructionDiff,
+    LocalStackSize,
+};
+use werbolg_core::LitId;
+use werbolg_core::id::IdF;
+use werbolg_core::idvec::IdVec;
+use werbolg_exec::{ExecutionEnviron, VerifyError};
+
+type TestExecutionEnviron = ExecutionEnviron<DummyAlloc, MyLiteral, (), Value>;
+
+/// Replace the code of a compiled unit by one literal and a function for each code, laid out in order
+#[allow(dead_code)]
+fn unit(funs: Vec<(u8, u16, Vec<Instruction>)>) -> CompilationUnit<MyLiteral> {
+    let mod1 = werbolg_core::text::parse_module(super::text::golden::TEXT).expect("valid IR");
+    let (mut unit, _) = crate::compile_modules(vec![("main", mod1)]);
+    unit.lits = IdVec::new();
+    unit.lits.push(MyLiteral::Int(1));
+    unit.funs = IdVec::new();
+    unit.code = IdVec::new();
+    unit.inits = Vec::new();
+    for (arity, stack_size, instructions) in funs {
+        unit.funs.push(FunDef {
+            name: None,
+            arity: CallArity(arity),
+            stack_size: LocalStackSize(stack_size),
+            code_pos: unit.code.next_id(),
+        });
+        for instruction in instructions {
+            unit.code.push(instruction);
+        }
+    }
+    unit
+}
+
+#[allow(dead_code)]
+fn verify(funs: Vec<(u8, u16, Vec<Instruction>)>) -> Result<(), VerifyError> {
+    let ee: TestExecutionEnviron = ExecutionEnviron {
+        nifs: IdVec::new(),
+        globals: IdVec::new(),
+    };
+    werbolg_exec::verify(&unit(funs), &ee)
+}
+
+/// The displacement of a jump at `from` to reach `to`
+#[allow(dead_code)]
+fn diff(from: usize, to: usize) -> InstructionDiff {
+    InstructionAddress::from_collection_len(to) - InstructionAddress::from_collection_len(from + 1)
+}
+
+#[allow(dead_code)]
+fn lit() -> Instruction {
+    Instruction::PushLiteral(LitId::from_collection_len(0))
+}
+
+#[test]
+fn compiled() {
+    let mod1 = werbolg_core::text::parse_module(super::text::golden::TEXT).expect("valid IR");
+    let (unit, environ) = crate::compile_modules(vec![("main", mod1)]);
+    let ee = ExecutionEnviron::from_compile_environment(environ.finalize());
+    let r = werbolg_exec::verify(&unit, &ee);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn valid() {
+    use werbolg_compile::{LocalBindIndex, ParamBindIndex, TailCall};
+    use werbolg_core::FunId;
+
+    let branches = vec![
+        lit(),
+        Instruction::CondJump(diff(1, 4)),
+        lit(),
+        Instruction::Jump(diff(3, 5)),
+        lit(),
+        Instruction::LocalBind(LocalBindIndex(0)),
+        Instruction::FetchStackParam(ParamBindIndex(0)),
+        Instruction::FetchStackLocal(LocalBindIndex(0)),
+        Instruction::FetchFun(FunId::from_collection_len(1)),
+        Instruction::Call(TailCall::Yes, CallArity(1)),
+    ];
+    let r = verify(vec![
+        (1, 1, branches),
+        (1, 0, vec![lit(), Instruction::Ret]),
+    ]);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn out_of_bound() {
+    use werbolg_compile::{LocalBindIndex, ParamBindIndex, TailCall};
+    use werbolg_core::{FunId, NifId};
+
+    let r = verify(vec![(
+        0,
+        1,
+        vec![Instruction::FetchStackLocal(LocalBindIndex(1))],
+    )]);
+    assert!(matches!(r, Err(VerifyError::LocalOutOfBound { .. })));
+
+    let r = verify(vec![(
+        1,
+        0,
+        vec![Instruction::FetchStackParam(ParamBindIndex(1))],
+    )]);
+    assert!(matches!(r, Err(VerifyError::ParamOutOfBound { .. })));
+
+    let lit = Instruction::PushLiteral(LitId::from_collection_len(1));
+    let r = verify(vec![(0, 0, vec![lit])]);
+    assert!(matches!(r, Err(VerifyError::LitOutOfBound { .. })));
+
+    let fetch = Instruction::FetchFun(FunId::from_collection_len(1));
+    let r = verify(vec![(0, 0, vec![fetch])]);
+    assert!(matches!(r, Err(VerifyError::FunOutOfBound { .. })));
+
+    let call = Instruction::CallNif(NifId::from_collection_len(0), CallArity(0));
+    let r = verify(vec![(0, 0, vec![call])]);
+    assert!(matches!(r, Err(VerifyError::NifOutOfBound { .. })));
+
+    let call = Instruction::CallFun(FunId::from_collection_len(1), CallArity(0), TailCall::Yes);
+    let r = verify(vec![(0, 0, vec![call])]);
+    assert!(matches!(r, Err(VerifyError::FunOutOfBound { .. })));
+}
+
+#[test]
+fn call_fun_arity() {
+    use werbolg_compile::TailCall;
+    use werbolg_core::FunId;
+
+    // the arity of a direct call is not checked by the execution
+    let call = Instruction::CallFun(FunId::from_collection_len(1), CallArity(1), TailCall::No);
+    let r = verify(vec![
+        (0, 0, vec![lit(), call, Instruction::Ret]),
+        (2, 0, vec![lit(), Instruction::Ret]),
+    ]);
+    assert!(matches!(
+        r,
+        Err(VerifyError::CallArityMismatch {
+            expected: CallArity(2),
+            got: CallArity(1),
+            ..
+        })
+    ));
+
+    let call = Instruction::CallFun(FunId::from_collection_len(1), CallArity(1), TailCall::Yes);
+    let r = verify(vec![
+        (0, 0, vec![lit(), call]),
+        (1, 0, vec![lit(), Instruction::Ret]),
+    ]);
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn call_nif_arity() {
+    use werbolg_core::NifId;
+    use werbolg_exec::{ExecutionError, NIFCall};
+
+    fn nif_first(_: &DummyAlloc, args: &[Value]) -> Result<Value, ExecutionError> {
+        Ok(args[0].clone())
+    }
+
+    let mut ee: TestExecutionEnviron = ExecutionEnviron {
+        nifs: IdVec::new(),
+        globals: IdVec::new(),
+    };
+    ee.nifs
+        .push(NIFCall::Pure(nif_first).info("pair", CallArity(2)));
+    ee.nifs.push(NIFCall::Pure(nif_first).info_variadic("any"));
+
+    let call = Instruction::CallNif(NifId::from_collection_len(0), CallArity(1));
+    let r = werbolg_exec::verify(
+        &unit(vec![(0, 0, vec![lit(), call, Instruction::Ret])]),
+        &ee,
+    );
+    assert!(matches!(
+        r,
+        Err(VerifyError::CallArityMismatch {
+            expected: CallArity(2),
+            got: CallArity(1),
+            ..
+        })
+    ));
+
+    // the arity of a NIF taking any number of arguments is not checked
+    let call = Instruction::CallNif(NifId::from_collection_len(1), CallArity(1));
+    let r = werbolg_exec::verify(
+        &unit(vec![(0, 0, vec![lit(), call, Instruction::Ret])]),
+        &ee,
+    );
+    assert!(r.is_ok(), "{:?}", r.err())
+}
+
+#[test]
+fn jump_out_of_function() {
+    let r = verify(vec![(0, 0, vec![Instruction::Jump(diff(0, 2))])]);
+    assert!(matches!(r, Err(VerifyError::JumpOutOfFunction { .. })));
+
+    // jumping in the middle of the next function
+    let r = verify(vec![
+        (0, 0, vec![Instruction::Jump(diff(0, 2))]),
+        (0, 0, vec![lit(), lit(), Instruction::Ret]),
+    ]);
+    assert!(matches!(r, Err(VerifyError::JumpOutOfFunction { .. })));
+}
+
+#[test]
+fn fall_through() {
+    let r = verify(vec![
+        (0, 0, vec![lit()]),
+        (0, 0, vec![lit(), Instruction::Ret]),
+    ]);
+    assert!(matches!(r, Err(VerifyError::FallThrough { .. })))
+}
+
+#[test]
+fn stack_depth() {
+    let r = verify(vec![(0, 0, vec![Instruction::Ret])]);
+    assert!(matches!(
+        r,
+        Err(VerifyError::StackUnderflow {
+            needed: 1,
+            depth: 0,
+            ..
+        })
+    ));
+
+    // one path pushes one more value than the other before joining
+    let code = vec![
+        lit(),
+        Instruction::CondJump(diff(1, 3)),
+        lit(),
+        lit(),
+        Instruction::Ret,
+    ];
+    let r = verify(vec![(0, 0, code)]);
+    assert!(matches!(r, Err(VerifyError::StackDepthMismatch { .. })))
+}
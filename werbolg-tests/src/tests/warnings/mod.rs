@@ -0,0 +1,98 @@
+use crate::build::{self, *};
+use alloc::vec;
+
+// fn unused_helper() { 1 }
+// fn helper() { 2 }
+// fn f(a, b, _c) { let a = 3; let unused = 4; let _unused = 5; let _ = 6; a }
+// fn main() { f(helper(), 0, 0) }
+#[allow(dead_code)]
+fn module() -> werbolg_core::Module {
+    build::module(vec![
+        fun_private("unused_helper", &[], int(1)),
+        fun_private("helper", &[], int(2)),
+        fun(
+            "f",
+            &["a", "b", "_c"],
+            let_(
+                "a",
+                int(3),
+                let_(
+                    "unused",
+                    int(4),
+                    let_("_unused", int(5), let_binder(ignore(), int(6), var("a"))),
+                ),
+            ),
+        ),
+        fun(
+            "main",
+            &[],
+            call(var("f"), vec![call(var("helper"), vec![]), int(0), int(0)]),
+        ),
+    ])
+}
+
+#[allow(dead_code)]
+fn warnings(
+    levels: werbolg_compile::WarningLevels,
+) -> Result<
+    alloc::vec::Vec<werbolg_compile::CompilationWarning>,
+    alloc::vec::Vec<werbolg_compile::CompilationError>,
+> {
+    let mut warnings = vec![];
+    crate::compile_modules_warnings(vec![("main", module())], levels, &mut warnings)?;
+    Ok(warnings)
+}
+
+#[test]
+fn reported() {
+    use alloc::vec::Vec;
+    use werbolg_compile::{WarningKind, WarningLevels};
+    use werbolg_core::Ident;
+
+    let warnings = warnings(WarningLevels::default()).expect("no compilation error");
+    let kinds = warnings.into_iter().map(|w| w.kind).collect::<Vec<_>>();
+    let expected = [
+        WarningKind::IgnoredLiteral,
+        WarningKind::UnusedParam(Ident::from("a")),
+        WarningKind::UnusedParam(Ident::from("b")),
+        WarningKind::ShadowedParam(Ident::from("a")),
+        WarningKind::UnusedBinding(Ident::from("unused")),
+        WarningKind::UnusedFunction(Ident::from("unused_helper")),
+    ];
+    assert_eq!(kinds, expected);
+}
+
+#[test]
+fn levels() {
+    use werbolg_compile::{CompilationError, WarningCode, WarningLevel, WarningLevels};
+
+    let mut levels = WarningLevels::default();
+    for code in WarningCode::ALL {
+        levels.set(code, WarningLevel::Allow);
+    }
+    let warnings = warnings(levels.clone()).expect("no compilation error");
+    assert!(warnings.is_empty(), "{:?}", warnings);
+
+    levels.set(WarningCode::UnusedFunction, WarningLevel::Deny);
+    let Err(errors) = self::warnings(levels) else {
+        panic!("denied warning should fail the compilation")
+    };
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        &errors[0],
+        CompilationError::DeniedWarning(w) if w.code() == WarningCode::UnusedFunction
+    ));
+}
+
+#[test]
+fn codes() {
+    use werbolg_compile::WarningCode;
+
+    for code in WarningCode::ALL {
+        assert_eq!(WarningCode::from_name(code.as_str()), Some(code));
+    }
+    assert_eq!(
+        WarningCode::from_name("unused-binding"),
+        Some(WarningCode::UnusedBinding)
+    );
+}
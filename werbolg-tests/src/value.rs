@@ -1,4 +1,7 @@
-use werbolg_core::{ConstrId, ValueFun};
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use werbolg_core::{ConstrId, FunId, ValueFun};
 use werbolg_exec::{ExecutionError, Valuable, ValueKind};
 
 #[derive(Clone, Debug)]
@@ -7,6 +10,11 @@ pub enum Value {
     Bool(bool),
     Integral(u64),
     Fun(ValueFun),
+    Closure(FunId, Rc<[Value]>),
+    Struct(ConstrId, Rc<[Value]>),
+    Tuple(Rc<[Value]>),
+    List(Rc<[Value]>),
+    Ref(Rc<RefCell<Value>>),
 }
 
 impl Value {
@@ -16,6 +24,11 @@ impl Value {
             Value::Bool(_) => BOOL_KIND,
             Value::Integral(_) => INT_KIND,
             Value::Fun(_) => FUN_KIND,
+            Value::Closure(_, _) => FUN_KIND,
+            Value::Struct(_, _) => STRUCT_KIND,
+            Value::Tuple(_) => TUPLE_KIND,
+            Value::List(_) => LIST_KIND,
+            Value::Ref(_) => REF_KIND,
         }
     }
 }
@@ -24,6 +37,10 @@ pub const UNIT_KIND: ValueKind = "    unit";
 pub const BOOL_KIND: ValueKind = "    bool";
 pub const INT_KIND: ValueKind = "     int";
 pub const FUN_KIND: ValueKind = "     fun";
+pub const STRUCT_KIND: ValueKind = "  struct";
+pub const TUPLE_KIND: ValueKind = "   tuple";
+pub const LIST_KIND: ValueKind = "    list";
+pub const REF_KIND: ValueKind = "     ref";
 
 impl Valuable for Value {
     fn descriptor(&self) -> werbolg_exec::ValueKind {
@@ -37,25 +54,82 @@ impl Valuable for Value {
         }
     }
 
+    fn literal_eq(&self, literal: &Self) -> bool {
+        match (self, literal) {
+            (Value::Unit, Value::Unit) => true,
+            (Value::Bool(b1), Value::Bool(b2)) => b1 == b2,
+            (Value::Integral(n1), Value::Integral(n2)) => n1 == n2,
+            _ => false,
+        }
+    }
+
     fn fun(&self) -> Option<ValueFun> {
         match self {
             Self::Fun(valuefun) => Some(*valuefun),
+            Self::Closure(funid, _) => Some(ValueFun::Fun(*funid)),
+            _ => None,
+        }
+    }
+
+    fn captured(&self) -> Option<&[Self]> {
+        match self {
+            Self::Closure(_, captured) => Some(captured),
             _ => None,
         }
     }
 
     fn structure(&self) -> Option<(ConstrId, &[Self])> {
-        None
+        match self {
+            Self::Struct(constr, fields) => Some((*constr, fields)),
+            _ => None,
+        }
+    }
+
+    fn index(&self, index: usize) -> Option<&Self> {
+        match self {
+            Self::Tuple(elements) | Self::List(elements) => elements.get(index),
+            _ => None,
+        }
+    }
+
+    fn as_index(&self) -> Option<usize> {
+        match self {
+            Self::Integral(n) => usize::try_from(*n).ok(),
+            _ => None,
+        }
+    }
+
+    fn reference(&self) -> Option<&RefCell<Self>> {
+        match self {
+            Self::Ref(cell) => Some(cell),
+            _ => None,
+        }
     }
 
-    fn index(&self, _index: usize) -> Option<&Self> {
-        None
+    fn make_conditional(b: bool) -> Self {
+        Value::Bool(b)
     }
 
     fn make_fun(fun: ValueFun) -> Self {
         Value::Fun(fun)
     }
 
+    fn make_closure(fun: FunId, captured: Vec<Self>) -> Self {
+        Value::Closure(fun, captured.into())
+    }
+
+    fn make_struct(constr: ConstrId, fields: Vec<Self>) -> Self {
+        Value::Struct(constr, fields.into())
+    }
+
+    fn make_tuple(elements: Vec<Self>) -> Self {
+        Value::Tuple(elements.into())
+    }
+
+    fn make_ref(value: Self) -> Self {
+        Value::Ref(Rc::new(RefCell::new(value)))
+    }
+
     fn make_dummy() -> Self {
         Value::Unit
     }